@@ -1,4 +1,5 @@
-use blog_client::{BlogClientGrpc, BlogClientHttp, BlogClientTrait};
+use blog_client::{BlogClientError, BlogClientGrpc, BlogClientHttp, BlogClientTrait};
+use chrono::{DateTime, Utc};
 use clap::Parser;
 use uuid::Uuid;
 
@@ -10,6 +11,10 @@ struct Cli {
     #[clap(short, long)]
     server: Option<String>,
 
+    /// Use this token instead of the saved one, e.g. a personal access token
+    #[clap(long)]
+    token: Option<String>,
+
     #[clap(subcommand)]
     command: Command,
 }
@@ -29,6 +34,22 @@ enum Command {
         username: String,
         #[clap(long)]
         password: String,
+        /// Authenticator app or recovery code, if two-factor authentication is enabled
+        #[clap(long)]
+        code: Option<String>,
+    },
+    /// Exchange the saved refresh token for a new token pair
+    Refresh,
+    /// Revoke the saved tokens and delete them locally
+    Logout {
+        /// Log out of every session, not just this one
+        #[clap(long)]
+        all: bool,
+    },
+    /// Show or change the account of the logged-in user
+    Account {
+        #[clap(subcommand)]
+        command: AccountCommand,
     },
     ListPosts {
         #[clap(long)]
@@ -41,10 +62,24 @@ enum Command {
         title: String,
         #[clap(long)]
         content: String,
+        /// RFC 3339 time, e.g. 2026-01-01T10:00:00Z
+        #[clap(long)]
+        publish_at: Option<DateTime<Utc>>,
     },
     GetPost {
         id: Uuid,
     },
+    GetPostBySlug {
+        slug: String,
+    },
+    /// Full-text search over titles and content
+    Search {
+        query: String,
+        #[clap(long)]
+        limit: Option<u32>,
+        #[clap(long)]
+        offset: Option<u32>,
+    },
     GetPosts {
         #[clap(long)]
         author_id: Option<Uuid>,
@@ -59,10 +94,62 @@ enum Command {
         title: Option<String>,
         #[clap(long)]
         content: Option<String>,
+        /// Fail instead of overwriting if the post is no longer at this version
+        #[clap(long)]
+        expected_version: Option<i32>,
     },
+    /// Moves the post to the trash
     DeletePost {
         id: Uuid,
     },
+    /// Your deleted posts, purged after the server's retention period
+    Trash,
+    RestorePost {
+        id: Uuid,
+    },
+    PublishPost {
+        id: Uuid,
+    },
+    UnpublishPost {
+        id: Uuid,
+    },
+    /// Moderators and admins only; the author still sees the post
+    HidePost {
+        id: Uuid,
+    },
+    UnhidePost {
+        id: Uuid,
+    },
+    /// Admins only: user, moderator or admin
+    SetRole {
+        #[clap(long)]
+        user_id: Uuid,
+        #[clap(long)]
+        role: String,
+    },
+}
+
+#[derive(Parser, Debug)]
+enum AccountCommand {
+    Show,
+    /// Other sessions are logged out, this one keeps working
+    ChangePassword {
+        #[clap(long)]
+        current_password: String,
+        #[clap(long)]
+        new_password: String,
+    },
+    /// A verification link is sent to the new address
+    ChangeEmail {
+        #[clap(long)]
+        email: String,
+        #[clap(long)]
+        current_password: String,
+    },
+    ChangeUsername {
+        #[clap(long)]
+        username: String,
+    },
 }
 
 #[tokio::main]
@@ -72,10 +159,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // 1. Создаём клиента (один раз)
     let mut client: Box<dyn BlogClientTrait + Send + Sync> = if args.grpc {
         let endpoint = args.server.as_deref().unwrap_or("http://127.0.0.1:50051");
-        Box::new(BlogClientGrpc::connect(endpoint).await?)
+        let mut client = BlogClientGrpc::connect(endpoint).await?;
+        if let Some(token) = args.token {
+            client.use_token(token);
+        }
+        Box::new(client)
     } else {
         let endpoint = args.server.as_deref().unwrap_or("http://127.0.0.1:8080");
-        Box::new(BlogClientHttp::connect(endpoint).await?)
+        let mut client = BlogClientHttp::connect(endpoint).await?;
+        if let Some(token) = args.token {
+            client.use_token(token);
+        }
+        Box::new(client)
     };
 
     // 2. Выполняем команду
@@ -88,20 +183,77 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             client.register(email, username, password).await?;
             println!("Successfully registered!");
         }
-        Command::Login { username, password } => {
-            client.login(username, password).await?;
+        Command::Login {
+            username,
+            password,
+            code,
+        } => {
+            match client.login(username, password).await {
+                Err(BlogClientError::MfaRequired(mfa_token)) => {
+                    let code = code.ok_or("two-factor code required, pass --code")?;
+                    client.verify_mfa(mfa_token, code).await?;
+                }
+                result => result?,
+            }
             println!("Successfully logged in!");
         }
+        Command::Refresh => {
+            client.refresh().await?;
+            println!("Tokens refreshed!");
+        }
+        Command::Logout { all } => {
+            client.logout(all).await?;
+            println!("Logged out!");
+        }
+        Command::Account { command } => match command {
+            AccountCommand::Show => {
+                let account = client.get_me().await?;
+                println!("{}", account);
+            }
+            AccountCommand::ChangePassword {
+                current_password,
+                new_password,
+            } => {
+                client
+                    .change_password(current_password, new_password)
+                    .await?;
+                println!("Password changed, other sessions were logged out!");
+            }
+            AccountCommand::ChangeEmail {
+                email,
+                current_password,
+            } => {
+                let account = client.change_email(email, current_password).await?;
+                println!(
+                    "Email changed to {}, check your inbox to verify it!",
+                    account.email
+                );
+            }
+            AccountCommand::ChangeUsername { username } => {
+                let account = client.change_username(username).await?;
+                println!("Username changed to {}!", account.username);
+            }
+        },
         Command::ListPosts { limit, offset } => {
             let posts = client.list_posts(None, limit, offset).await?;
             println!("Posts ({})", posts.len());
             for post in posts {
-                println!("- [{}] {} (by {})", post.id, post.title, post.author_id);
+                println!(
+                    "- [{}] {} /{} (by {}, {})",
+                    post.id, post.title, post.slug, post.author_id, post.status
+                );
             }
         }
-        Command::CreatePost { title, content } => {
-            let post = client.create_post(title, content).await?;
-            println!("Post created! ID: {}", post.id);
+        Command::CreatePost {
+            title,
+            content,
+            publish_at,
+        } => {
+            let post = client.create_post(title, content, publish_at).await?;
+            match post.publish_at {
+                Some(at) => println!("Post scheduled for {}! ID: {}", at, post.id),
+                None => println!("Post created! ID: {}", post.id),
+            }
         }
         Command::GetPosts {
             author_id,
@@ -113,18 +265,76 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 println!("{}", post);
             }
         }
-        Command::UpdatePost { id, title, content } => {
-            let post = client.update_post(id, title, content).await?;
+        Command::UpdatePost {
+            id,
+            title,
+            content,
+            expected_version,
+        } => {
+            let post = client
+                .update_post(id, title, content, expected_version)
+                .await?;
             println!("Post updated: {}", post)
         }
         Command::DeletePost { id } => {
             client.delete_post(id).await?;
-            println!("Post deleted!")
+            println!("Post moved to trash!")
+        }
+        Command::Trash => {
+            let posts = client.list_trash().await?;
+            println!("Trash ({})", posts.len());
+            for post in posts {
+                let deleted_at = post
+                    .deleted_at
+                    .map(|at| at.to_rfc3339())
+                    .unwrap_or_default();
+                println!("- [{}] {} (deleted {})", post.id, post.title, deleted_at);
+            }
+        }
+        Command::RestorePost { id } => {
+            let post = client.restore_post(id).await?;
+            println!("Post restored: {}", post)
+        }
+        Command::PublishPost { id } => {
+            let post = client.publish_post(id).await?;
+            println!("Post published: {}", post)
+        }
+        Command::UnpublishPost { id } => {
+            let post = client.unpublish_post(id).await?;
+            println!("Post moved to drafts: {}", post)
+        }
+        Command::HidePost { id } => {
+            let post = client.set_post_hidden(id, true).await?;
+            println!("Post hidden: {}", post)
+        }
+        Command::UnhidePost { id } => {
+            let post = client.set_post_hidden(id, false).await?;
+            println!("Post visible again: {}", post)
+        }
+        Command::SetRole { user_id, role } => {
+            let account = client.set_user_role(user_id, role).await?;
+            println!("{} is now {}", account.username, account.role)
         }
         Command::GetPost { id } => {
             let post = client.get_post_by_id(id).await?;
             println!("Post: {}", post)
         }
+        Command::Search {
+            query,
+            limit,
+            offset,
+        } => {
+            let results = client.search_posts(&query, limit, offset).await?;
+            println!("Found ({})", results.len());
+            for hit in results {
+                println!("- [{}] {} /{}", hit.post.id, hit.post.title, hit.post.slug);
+                println!("  {}", hit.snippet_text());
+            }
+        }
+        Command::GetPostBySlug { slug } => {
+            let post = client.get_post_by_slug(&slug).await?;
+            println!("Post: {}", post)
+        }
     }
 
     Ok(())
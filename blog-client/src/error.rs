@@ -14,6 +14,9 @@ pub enum BlogClientError {
     NotFound,
     #[error("Unauthorized")]
     Unauthorized,
+    /// Пароль принят, нужен код второго фактора: токен передаётся в `verify_mfa`
+    #[error("Two-factor code required")]
+    MfaRequired(String),
     #[error("Invalid request: {0}")]
     InvalidRequest(String),
     #[error("Status error: {0}")]
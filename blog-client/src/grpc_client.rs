@@ -1,11 +1,18 @@
 use crate::blog::blog_service_client::BlogServiceClient;
 use crate::blog::{
-    CreatePostRequest, DeletePostRequest, GetPostRequest, ListPostsRequest, LoginRequest,
-    RegisterRequest, UpdatePostRequest,
+    AuthResponse, ChangeEmailRequest, ChangePasswordRequest, ChangeUsernameRequest,
+    CreatePostRequest, DeletePostRequest, GetPostBySlugRequest, GetPostRequest, ListPostsRequest,
+    LoginRequest, LogoutRequest, PublishPostRequest, RefreshTokenRequest, RegisterRequest,
+    RestorePostRequest, SearchPostsRequest, SetPostHiddenRequest, SetUserRoleRequest,
+    UnpublishPostRequest, UpdatePostRequest, VerifyMfaRequest,
 };
 use crate::error::BlogClientError;
-use crate::{BlogClientTrait, Post};
+use crate::{
+    Account, BlogClientTrait, ChronoToProtobufTimestamp, Post, SearchResult, clear_tokens,
+    load_refresh_token, store_refresh_token,
+};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use std::fs;
 use tonic::Request;
 use tonic::transport::Channel;
@@ -35,10 +42,20 @@ impl BlogClientGrpc {
         fs::write(".blog_token", token).unwrap();
     }
 
+    /// Токен только на время работы клиента (например, персональный токен из CI) — в файл не пишется
+    pub fn use_token(&mut self, token: String) {
+        self.token = Some(token);
+    }
+
     pub fn token(&self) -> Option<&str> {
         self.token.as_deref()
     }
 
+    fn store_auth(&mut self, auth: AuthResponse) {
+        self.set_token(auth.access_token);
+        store_refresh_token(&auth.refresh_token);
+    }
+
     fn with_auth<T>(&self, mut req: Request<T>) -> Result<Request<T>, BlogClientError> {
         let token = if let Some(t) = &self.token {
             t.clone()
@@ -59,6 +76,12 @@ impl BlogClientGrpc {
 
         Ok(req)
     }
+
+    /// Публичные RPC: токен прикладываем, если он есть (автор видит свои черновики)
+    fn with_optional_auth<T: Clone>(&self, message: T) -> Request<T> {
+        self.with_auth(Request::new(message.clone()))
+            .unwrap_or_else(|_| Request::new(message))
+    }
 }
 
 #[async_trait(?Send)]
@@ -89,7 +112,7 @@ impl BlogClientTrait for BlogClientGrpc {
             password,
         };
         let response = self.client.register(req).await?;
-        self.set_token(response.into_inner().access_token);
+        self.store_auth(response.into_inner());
 
         Ok(())
     }
@@ -108,18 +131,113 @@ impl BlogClientTrait for BlogClientGrpc {
         }
 
         let req = LoginRequest { username, password };
-        let response = self.client.login(req).await?;
-        self.set_token(response.into_inner().access_token);
+        let auth = self.client.login(req).await?.into_inner();
+        if auth.mfa_required {
+            return Err(BlogClientError::MfaRequired(auth.mfa_token));
+        }
+        self.store_auth(auth);
+
+        Ok(())
+    }
+
+    async fn verify_mfa(&mut self, mfa_token: String, code: String) -> Result<(), BlogClientError> {
+        let req = VerifyMfaRequest { mfa_token, code };
+        let response = self.client.verify_mfa(req).await?;
+        self.store_auth(response.into_inner());
+
+        Ok(())
+    }
+
+    async fn refresh(&mut self) -> Result<(), BlogClientError> {
+        let req = RefreshTokenRequest {
+            refresh_token: load_refresh_token()?,
+        };
+        let response = self.client.refresh_token(req).await?;
+        self.store_auth(response.into_inner());
+
+        Ok(())
+    }
+
+    async fn logout(&mut self, all: bool) -> Result<(), BlogClientError> {
+        let result = if all {
+            let request = self.with_auth(Request::new(()))?;
+            self.client.logout_all(request).await
+        } else {
+            let request = self.with_auth(Request::new(LogoutRequest {
+                refresh_token: load_refresh_token().unwrap_or_default(),
+            }))?;
+            self.client.logout(request).await
+        };
+        // Локальные токены удаляем в любом случае
+        self.token = None;
+        clear_tokens();
+
+        result?;
+        Ok(())
+    }
+
+    async fn get_me(&mut self) -> Result<Account, BlogClientError> {
+        let request = self.with_auth(Request::new(()))?;
+        let response = self.client.get_me(request).await?;
+
+        Ok(response.into_inner().into())
+    }
+
+    async fn change_password(
+        &mut self,
+        current_password: String,
+        new_password: String,
+    ) -> Result<(), BlogClientError> {
+        let request = self.with_auth(Request::new(ChangePasswordRequest {
+            current_password,
+            new_password,
+        }))?;
+        self.client.change_password(request).await?;
 
         Ok(())
     }
 
+    async fn change_email(
+        &mut self,
+        email: String,
+        current_password: String,
+    ) -> Result<Account, BlogClientError> {
+        let request = self.with_auth(Request::new(ChangeEmailRequest {
+            email,
+            current_password,
+        }))?;
+        let response = self.client.change_email(request).await?;
+
+        Ok(response.into_inner().into())
+    }
+
+    async fn change_username(&mut self, username: String) -> Result<Account, BlogClientError> {
+        let request = self.with_auth(Request::new(ChangeUsernameRequest { username }))?;
+        let response = self.client.change_username(request).await?;
+
+        Ok(response.into_inner().into())
+    }
+
+    async fn set_user_role(
+        &mut self,
+        user_id: Uuid,
+        role: String,
+    ) -> Result<Account, BlogClientError> {
+        let request = self.with_auth(Request::new(SetUserRoleRequest {
+            user_id: user_id.to_string(),
+            role,
+        }))?;
+        let response = self.client.set_user_role(request).await?;
+
+        Ok(response.into_inner().into())
+    }
+
     async fn get_post_by_id(&mut self, id: Uuid) -> Result<Post, BlogClientError> {
         let response = self
             .client
-            .get_post(GetPostRequest {
+            .get_post(self.with_optional_auth(GetPostRequest {
                 post_id: id.to_string(),
-            })
+            }))
             .await?;
 
         let post = response.into_inner();
@@ -127,6 +245,17 @@ impl BlogClientTrait for BlogClientGrpc {
         Ok(post.into())
     }
 
+    async fn get_post_by_slug(&mut self, slug: &str) -> Result<Post, BlogClientError> {
+        let response = self
+            .client
+            .get_post_by_slug(self.with_optional_auth(GetPostBySlugRequest {
+                slug: slug.to_string(),
+            }))
+            .await?;
+
+        Ok(response.into_inner().into())
+    }
+
     async fn list_posts(
         &mut self,
         author_id: Option<Uuid>,
@@ -140,8 +269,13 @@ impl BlogClientTrait for BlogClientGrpc {
             limit,
             offset,
             author_id,
+            tag: None,
+            page_token: String::new(),
+            created_after: None,
+            created_before: None,
+            title_contains: None,
         };
-        let response = self.client.list_posts(req).await?;
+        let response = self.client.list_posts(self.with_optional_auth(req)).await?;
 
         let proto_posts = response.into_inner().posts;
         let posts: Vec<Post> = proto_posts.into_iter().map(Into::into).collect();
@@ -149,12 +283,43 @@ impl BlogClientTrait for BlogClientGrpc {
         Ok(posts)
     }
 
+    async fn search_posts(
+        &mut self,
+        query: &str,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Vec<SearchResult>, BlogClientError> {
+        let req = SearchPostsRequest {
+            query: query.to_string(),
+            limit: limit.unwrap_or(10).min(100) as i32,
+            offset: offset.unwrap_or(0) as i32,
+        };
+        let response = self
+            .client
+            .search_posts(self.with_optional_auth(req))
+            .await?;
+
+        Ok(response
+            .into_inner()
+            .results
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
     async fn create_post(
         &mut self,
         title: String,
         content: String,
+        publish_at: Option<DateTime<Utc>>,
     ) -> Result<Post, BlogClientError> {
-        let request = self.with_auth(Request::new(CreatePostRequest { title, content }))?;
+        let request = self.with_auth(Request::new(CreatePostRequest {
+            title,
+            content,
+            status: 0,
+            publish_at: publish_at.map(ChronoToProtobufTimestamp::into_protobuf),
+            tags: Vec::new(),
+        }))?;
 
         let response = self.client.create_post(request).await?;
         let post = response.into_inner();
@@ -167,11 +332,15 @@ impl BlogClientTrait for BlogClientGrpc {
         id: Uuid,
         title: Option<String>,
         content: Option<String>,
+        expected_version: Option<i32>,
     ) -> Result<Post, BlogClientError> {
         let request = self.with_auth(Request::new(UpdatePostRequest {
             post_id: id.to_string(),
             title,
             content,
+            publish_at: None,
+            tags: None,
+            expected_version,
         }))?;
 
         let response = self.client.update_post(request).await?;
@@ -189,4 +358,58 @@ impl BlogClientTrait for BlogClientGrpc {
 
         Ok(())
     }
+
+    async fn list_trash(&mut self) -> Result<Vec<Post>, BlogClientError> {
+        let request = self.with_auth(Request::new(()))?;
+
+        let response = self.client.list_trashed_posts(request).await?;
+
+        Ok(response
+            .into_inner()
+            .posts
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    async fn restore_post(&mut self, id: Uuid) -> Result<Post, BlogClientError> {
+        let request = self.with_auth(Request::new(RestorePostRequest {
+            post_id: id.to_string(),
+        }))?;
+
+        let response = self.client.restore_post(request).await?;
+
+        Ok(response.into_inner().into())
+    }
+
+    async fn publish_post(&mut self, id: Uuid) -> Result<Post, BlogClientError> {
+        let request = self.with_auth(Request::new(PublishPostRequest {
+            post_id: id.to_string(),
+        }))?;
+
+        let response = self.client.publish_post(request).await?;
+
+        Ok(response.into_inner().into())
+    }
+
+    async fn unpublish_post(&mut self, id: Uuid) -> Result<Post, BlogClientError> {
+        let request = self.with_auth(Request::new(UnpublishPostRequest {
+            post_id: id.to_string(),
+        }))?;
+
+        let response = self.client.unpublish_post(request).await?;
+
+        Ok(response.into_inner().into())
+    }
+
+    async fn set_post_hidden(&mut self, id: Uuid, hidden: bool) -> Result<Post, BlogClientError> {
+        let request = self.with_auth(Request::new(SetPostHiddenRequest {
+            post_id: id.to_string(),
+            hidden,
+        }))?;
+
+        let response = self.client.set_post_hidden(request).await?;
+
+        Ok(response.into_inner().into())
+    }
 }
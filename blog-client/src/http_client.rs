@@ -1,7 +1,8 @@
 use crate::BlogClientTrait;
-use crate::Post;
 use crate::error::BlogClientError;
+use crate::{Account, Post, SearchResult, clear_tokens, load_refresh_token, store_refresh_token};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -19,12 +20,25 @@ struct PostsResponse {
     posts: Vec<Post>,
 }
 
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    results: Vec<SearchResult>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct AuthResponse {
     pub access_token: String,
     pub expires_in: i64,
     #[serde(rename = "token_type")]
     pub token_type: String, // "Bearer"
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum LoginResponse {
+    MfaRequired { mfa_token: String },
+    Tokens(AuthResponse),
 }
 
 impl BlogClientHttp {
@@ -42,10 +56,20 @@ impl BlogClientHttp {
         fs::write(".blog_token", token).unwrap();
     }
 
+    /// Токен только на время работы клиента (например, персональный токен из CI) — в файл не пишется
+    pub fn use_token(&mut self, token: String) {
+        self.token = Some(token);
+    }
+
     pub fn token(&self) -> Option<&str> {
         self.token.as_deref()
     }
 
+    fn store_auth(&mut self, auth: AuthResponse) {
+        self.set_token(auth.access_token);
+        store_refresh_token(&auth.refresh_token);
+    }
+
     fn auth_header(&self) -> Result<Option<reqwest::header::HeaderValue>, BlogClientError> {
         // 1. Берём токен из self или из файла
         let token = match &self.token {
@@ -70,6 +94,25 @@ impl BlogClientHttp {
 
         Ok(Some(header))
     }
+
+    async fn change_status(&self, id: Uuid, action: &str) -> Result<Post, BlogClientError> {
+        let mut req = self
+            .client
+            .post(format!("{}/api/posts/{}/{}", self.base_url, id, action));
+
+        if let Some(h) = self.auth_header()? {
+            req = req.header(reqwest::header::AUTHORIZATION, h);
+        }
+
+        let resp = req.send().await?;
+
+        if resp.status().is_success() {
+            let post: Post = resp.json().await?;
+            Ok(post)
+        } else {
+            Err(BlogClientError::from_http_response(resp).await)
+        }
+    }
 }
 
 #[async_trait(?Send)]
@@ -93,7 +136,7 @@ impl BlogClientTrait for BlogClientHttp {
 
         if resp.status().is_success() {
             let auth: AuthResponse = resp.json().await?;
-            self.set_token(auth.access_token);
+            self.store_auth(auth);
         } else {
             return Err(BlogClientError::from_http_response(resp).await);
         };
@@ -112,9 +155,33 @@ impl BlogClientTrait for BlogClientHttp {
             .send()
             .await?;
 
+        if !resp.status().is_success() {
+            return Err(BlogClientError::from_http_response(resp).await);
+        }
+        match resp.json().await? {
+            LoginResponse::Tokens(auth) => self.store_auth(auth),
+            LoginResponse::MfaRequired { mfa_token } => {
+                return Err(BlogClientError::MfaRequired(mfa_token));
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn verify_mfa(&mut self, mfa_token: String, code: String) -> Result<(), BlogClientError> {
+        let resp = self
+            .client
+            .post(format!("{}/api/auth/login/mfa", self.base_url))
+            .json(&serde_json::json!({
+                "mfa_token": mfa_token,
+                "code": code,
+            }))
+            .send()
+            .await?;
+
         if resp.status().is_success() {
             let auth: AuthResponse = resp.json().await?;
-            self.set_token(auth.access_token);
+            self.store_auth(auth);
         } else {
             return Err(BlogClientError::from_http_response(resp).await);
         };
@@ -122,6 +189,180 @@ impl BlogClientTrait for BlogClientHttp {
         Ok(())
     }
 
+    async fn refresh(&mut self) -> Result<(), BlogClientError> {
+        let resp = self
+            .client
+            .post(format!("{}/api/auth/refresh", self.base_url))
+            .json(&serde_json::json!({
+                "refresh_token": load_refresh_token()?,
+            }))
+            .send()
+            .await?;
+
+        if resp.status().is_success() {
+            let auth: AuthResponse = resp.json().await?;
+            self.store_auth(auth);
+        } else {
+            return Err(BlogClientError::from_http_response(resp).await);
+        };
+
+        Ok(())
+    }
+
+    async fn logout(&mut self, all: bool) -> Result<(), BlogClientError> {
+        let (path, body) = if all {
+            ("logout-all", serde_json::json!({}))
+        } else {
+            (
+                "logout",
+                serde_json::json!({ "refresh_token": load_refresh_token().ok() }),
+            )
+        };
+        let mut req = self
+            .client
+            .post(format!("{}/api/auth/{}", self.base_url, path))
+            .json(&body);
+
+        if let Some(h) = self.auth_header()? {
+            req = req.header(reqwest::header::AUTHORIZATION, h);
+        }
+
+        let resp = req.send().await;
+        // Локальные токены удаляем в любом случае
+        self.token = None;
+        clear_tokens();
+
+        let resp = resp?;
+        if !resp.status().is_success() {
+            return Err(BlogClientError::from_http_response(resp).await);
+        }
+
+        Ok(())
+    }
+
+    async fn get_me(&mut self) -> Result<Account, BlogClientError> {
+        let mut req = self.client.get(format!("{}/api/users/me", self.base_url));
+
+        if let Some(h) = self.auth_header()? {
+            req = req.header(reqwest::header::AUTHORIZATION, h);
+        }
+
+        let resp = req.send().await?;
+
+        if resp.status().is_success() {
+            let account: Account = resp.json().await?;
+            Ok(account)
+        } else {
+            Err(BlogClientError::from_http_response(resp).await)
+        }
+    }
+
+    async fn change_password(
+        &mut self,
+        current_password: String,
+        new_password: String,
+    ) -> Result<(), BlogClientError> {
+        let mut req = self
+            .client
+            .put(format!("{}/api/users/me/password", self.base_url));
+
+        if let Some(h) = self.auth_header()? {
+            req = req.header(reqwest::header::AUTHORIZATION, h);
+        }
+
+        let resp = req
+            .json(&serde_json::json!({
+                "current_password": current_password,
+                "new_password": new_password,
+            }))
+            .send()
+            .await?;
+
+        if resp.status().is_success() {
+            Ok(())
+        } else {
+            Err(BlogClientError::from_http_response(resp).await)
+        }
+    }
+
+    async fn change_email(
+        &mut self,
+        email: String,
+        current_password: String,
+    ) -> Result<Account, BlogClientError> {
+        let mut req = self
+            .client
+            .put(format!("{}/api/users/me/email", self.base_url));
+
+        if let Some(h) = self.auth_header()? {
+            req = req.header(reqwest::header::AUTHORIZATION, h);
+        }
+
+        let resp = req
+            .json(&serde_json::json!({
+                "email": email,
+                "current_password": current_password,
+            }))
+            .send()
+            .await?;
+
+        if resp.status().is_success() {
+            let account: Account = resp.json().await?;
+            Ok(account)
+        } else {
+            Err(BlogClientError::from_http_response(resp).await)
+        }
+    }
+
+    async fn change_username(&mut self, username: String) -> Result<Account, BlogClientError> {
+        let mut req = self
+            .client
+            .put(format!("{}/api/users/me/username", self.base_url));
+
+        if let Some(h) = self.auth_header()? {
+            req = req.header(reqwest::header::AUTHORIZATION, h);
+        }
+
+        let resp = req
+            .json(&serde_json::json!({ "username": username }))
+            .send()
+            .await?;
+
+        if resp.status().is_success() {
+            let account: Account = resp.json().await?;
+            Ok(account)
+        } else {
+            Err(BlogClientError::from_http_response(resp).await)
+        }
+    }
+
+    async fn set_user_role(
+        &mut self,
+        user_id: Uuid,
+        role: String,
+    ) -> Result<Account, BlogClientError> {
+        let mut req = self.client.put(format!(
+            "{}/api/admin/users/{}/role",
+            self.base_url, user_id
+        ));
+
+        if let Some(h) = self.auth_header()? {
+            req = req.header(reqwest::header::AUTHORIZATION, h);
+        }
+
+        let resp = req
+            .json(&serde_json::json!({ "role": role }))
+            .send()
+            .await?;
+
+        if resp.status().is_success() {
+            let account: Account = resp.json().await?;
+            Ok(account)
+        } else {
+            Err(BlogClientError::from_http_response(resp).await)
+        }
+    }
+
     async fn get_post_by_id(&mut self, id: Uuid) -> Result<Post, BlogClientError> {
         let mut req = self
             .client
@@ -141,6 +382,26 @@ impl BlogClientTrait for BlogClientHttp {
         }
     }
 
+    // Старые slug'и отдают 301, reqwest сам переходит на актуальный адрес
+    async fn get_post_by_slug(&mut self, slug: &str) -> Result<Post, BlogClientError> {
+        let mut req = self
+            .client
+            .get(format!("{}/api/posts/by-slug/{}", self.base_url, slug));
+
+        if let Some(h) = self.auth_header()? {
+            req = req.header(reqwest::header::AUTHORIZATION, h);
+        }
+
+        let resp = req.send().await?;
+
+        if resp.status().is_success() {
+            let post: Post = resp.json().await?;
+            Ok(post)
+        } else {
+            Err(BlogClientError::from_http_response(resp).await)
+        }
+    }
+
     async fn list_posts(
         &mut self,
         author_id: Option<Uuid>,
@@ -149,14 +410,20 @@ impl BlogClientTrait for BlogClientHttp {
     ) -> Result<Vec<Post>, BlogClientError> {
         let limit = limit.unwrap_or(10).min(100) as i32;
         let offset = offset.unwrap_or(0) as i32;
-        let resp = self
-            .client
-            .get(format!(
-                "{}/api/posts?limit={}&offset={}",
-                self.base_url, limit, offset
-            ))
-            .send()
-            .await?;
+        let mut req = self.client.get(format!(
+            "{}/api/posts?limit={}&offset={}",
+            self.base_url, limit, offset
+        ));
+
+        if let Some(author_id) = author_id {
+            req = req.query(&[("author_id", author_id)]);
+        }
+
+        if let Ok(Some(h)) = self.auth_header() {
+            req = req.header(reqwest::header::AUTHORIZATION, h);
+        }
+
+        let resp = req.send().await?;
 
         if resp.status().is_success() {
             let posts: PostsResponse = resp.json().await?;
@@ -166,10 +433,42 @@ impl BlogClientTrait for BlogClientHttp {
         }
     }
 
+    async fn search_posts(
+        &mut self,
+        query: &str,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Vec<SearchResult>, BlogClientError> {
+        let limit = limit.unwrap_or(10).min(100);
+        let offset = offset.unwrap_or(0);
+        let mut req = self
+            .client
+            .get(format!("{}/api/posts/search", self.base_url))
+            .query(&[
+                ("q", query.to_string()),
+                ("limit", limit.to_string()),
+                ("offset", offset.to_string()),
+            ]);
+
+        if let Ok(Some(h)) = self.auth_header() {
+            req = req.header(reqwest::header::AUTHORIZATION, h);
+        }
+
+        let resp = req.send().await?;
+
+        if resp.status().is_success() {
+            let found: SearchResponse = resp.json().await?;
+            Ok(found.results)
+        } else {
+            Err(BlogClientError::from_http_response(resp).await)
+        }
+    }
+
     async fn create_post(
         &mut self,
         title: String,
         content: String,
+        publish_at: Option<DateTime<Utc>>,
     ) -> Result<Post, BlogClientError> {
         let mut req = self.client.post(format!("{}/api/posts", self.base_url));
 
@@ -181,6 +480,7 @@ impl BlogClientTrait for BlogClientHttp {
             .json(&serde_json::json!({
                 "title": title,
                 "content": content,
+                "publish_at": publish_at,
             }))
             .send()
             .await?;
@@ -198,6 +498,7 @@ impl BlogClientTrait for BlogClientHttp {
         id: Uuid,
         title: Option<String>,
         content: Option<String>,
+        expected_version: Option<i32>,
     ) -> Result<Post, BlogClientError> {
         let mut req = self
             .client
@@ -207,6 +508,10 @@ impl BlogClientTrait for BlogClientHttp {
             req = req.header(reqwest::header::AUTHORIZATION, h);
         }
 
+        if let Some(version) = expected_version {
+            req = req.header(reqwest::header::IF_MATCH, format!("\"{version}\""));
+        }
+
         let resp = req
             .json(&serde_json::json!({
                 "title": title,
@@ -240,4 +545,40 @@ impl BlogClientTrait for BlogClientHttp {
             Err(BlogClientError::from_http_response(resp).await)
         }
     }
+
+    async fn list_trash(&mut self) -> Result<Vec<Post>, BlogClientError> {
+        let mut req = self
+            .client
+            .get(format!("{}/api/posts/trash", self.base_url));
+
+        if let Some(h) = self.auth_header()? {
+            req = req.header(reqwest::header::AUTHORIZATION, h);
+        }
+
+        let resp = req.send().await?;
+
+        if resp.status().is_success() {
+            let posts: PostsResponse = resp.json().await?;
+            Ok(posts.posts)
+        } else {
+            Err(BlogClientError::from_http_response(resp).await)
+        }
+    }
+
+    async fn restore_post(&mut self, id: Uuid) -> Result<Post, BlogClientError> {
+        self.change_status(id, "restore").await
+    }
+
+    async fn publish_post(&mut self, id: Uuid) -> Result<Post, BlogClientError> {
+        self.change_status(id, "publish").await
+    }
+
+    async fn unpublish_post(&mut self, id: Uuid) -> Result<Post, BlogClientError> {
+        self.change_status(id, "unpublish").await
+    }
+
+    async fn set_post_hidden(&mut self, id: Uuid, hidden: bool) -> Result<Post, BlogClientError> {
+        self.change_status(id, if hidden { "hide" } else { "unhide" })
+            .await
+    }
 }
@@ -1,10 +1,14 @@
 pub use crate::error::BlogClientError;
 use async_trait::async_trait;
-use blog::Post as ProtoPost;
-use chrono::{DateTime, NaiveDateTime, Utc};
+use blog::{
+    Account as ProtoAccount, Post as ProtoPost, PostStatus as ProtoPostStatus,
+    SearchResult as ProtoSearchResult,
+};
+use chrono::{DateTime, Utc};
 use derive_more::Display;
 use prost_types::Timestamp;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
 mod error;
@@ -14,17 +18,85 @@ mod http_client;
 pub use grpc_client::BlogClientGrpc;
 pub use http_client::BlogClientHttp;
 
+/// Refresh-токен живёт рядом с `.blog_token`
+const REFRESH_TOKEN_FILE: &str = ".blog_refresh_token";
+
+fn store_refresh_token(token: &str) {
+    std::fs::write(REFRESH_TOKEN_FILE, token).unwrap();
+}
+
+/// Удаляет сохранённые токены; отсутствующие файлы не ошибка
+fn clear_tokens() {
+    let _ = std::fs::remove_file(".blog_token");
+    let _ = std::fs::remove_file(REFRESH_TOKEN_FILE);
+}
+
+fn load_refresh_token() -> Result<String, BlogClientError> {
+    let token = std::fs::read_to_string(REFRESH_TOKEN_FILE)
+        .map_err(|_| BlogClientError::Unauthorized)?
+        .trim()
+        .to_string();
+    if token.is_empty() {
+        return Err(BlogClientError::Unauthorized);
+    }
+    Ok(token)
+}
+
 pub mod blog {
     tonic::include_proto!("blog");
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Display)]
+#[serde(rename_all = "lowercase")]
+pub enum PostStatus {
+    #[default]
+    #[display("draft")]
+    Draft,
+    #[display("published")]
+    Published,
+    #[display("archived")]
+    Archived,
+    #[display("scheduled")]
+    Scheduled,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Display)]
-#[display("Post {{ id: {}, title: {}, author_id: {} }}", id, title, author_id)]
+#[display(
+    "Post {{ id: {}, title: {}, author_id: {}, status: {} }}",
+    id,
+    title,
+    author_id,
+    status
+)]
 pub struct Post {
     pub id: Uuid,
     pub author_id: Uuid,
     pub title: String,
+    #[serde(default)]
+    pub slug: String,
     pub content: String,
+    #[serde(default)]
+    pub status: PostStatus,
+    #[serde(default)]
+    pub published_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub publish_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Вид реакции -> количество
+    #[serde(default)]
+    pub reactions: BTreeMap<String, i64>,
+    #[serde(default)]
+    pub my_reactions: Vec<String>,
+    /// Скрыт модератором; такой пост виден только автору
+    #[serde(default)]
+    pub hidden_at: Option<DateTime<Utc>>,
+    /// Пост в корзине; виден только в `list_trash`
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Растёт с каждым изменением; передаётся в `update_post` для защиты от перезаписи
+    #[serde(default)]
+    pub version: i32,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
 }
@@ -33,17 +105,103 @@ impl From<ProtoPost> for Post {
     fn from(p: ProtoPost) -> Self {
         let id = Uuid::parse_str(p.post_id.as_str()).unwrap();
         let author_id = Uuid::parse_str(p.author_id.as_str()).unwrap();
+        let status = match p.status() {
+            ProtoPostStatus::Published => PostStatus::Published,
+            ProtoPostStatus::Archived => PostStatus::Archived,
+            ProtoPostStatus::Scheduled => PostStatus::Scheduled,
+            ProtoPostStatus::Draft | ProtoPostStatus::Unspecified => PostStatus::Draft,
+        };
         Post {
             id,
             title: p.title,
             content: p.content,
             author_id,
+            status,
+            published_at: p.published_at.map(ProtobufToChrono::into_chrono),
+            publish_at: p.publish_at.map(ProtobufToChrono::into_chrono),
+            tags: p.tags,
+            slug: p.slug,
+            reactions: p.reactions.into_iter().collect(),
+            my_reactions: p.my_reactions,
+            hidden_at: p.hidden_at.map(ProtobufToChrono::into_chrono),
+            deleted_at: p.deleted_at.map(ProtobufToChrono::into_chrono),
+            version: p.version,
             created_at: Some(p.created_at.unwrap().to_chrono()),
             updated_at: Some(p.updated_at.unwrap().to_chrono()),
         }
     }
 }
 
+/// Результат полнотекстового поиска: пост и фрагмент в виде экранированного HTML с совпадениями в `<mark>`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResult {
+    #[serde(flatten)]
+    pub post: Post,
+    pub rank: f32,
+    pub snippet: String,
+}
+
+impl SearchResult {
+    /// Сниппет обычным текстом: без `<mark>` и с раскрытыми HTML-сущностями, для вывода в терминал
+    pub fn snippet_text(&self) -> String {
+        self.snippet
+            .replace("<mark>", "")
+            .replace("</mark>", "")
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&#39;", "'")
+            .replace("&amp;", "&")
+    }
+}
+
+impl From<ProtoSearchResult> for SearchResult {
+    fn from(r: ProtoSearchResult) -> Self {
+        SearchResult {
+            post: r.post.expect("search result without post").into(),
+            rank: r.rank,
+            snippet: r.snippet,
+        }
+    }
+}
+
+/// Профиль владельца токена
+#[derive(Debug, Clone, Serialize, Deserialize, Display)]
+#[display(
+    "Account {{ id: {}, username: {}, email: {}, role: {}, email_verified: {}, totp_enabled: {} }}",
+    id,
+    username,
+    email,
+    role,
+    email_verified,
+    totp_enabled
+)]
+pub struct Account {
+    pub id: Uuid,
+    pub username: String,
+    pub email: String,
+    /// `user`, `moderator` или `admin`
+    #[serde(default)]
+    pub role: String,
+    pub email_verified: bool,
+    pub totp_enabled: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<ProtoAccount> for Account {
+    fn from(a: ProtoAccount) -> Self {
+        Account {
+            id: Uuid::parse_str(a.user_id.as_str()).unwrap(),
+            username: a.username,
+            email: a.email,
+            role: a.role,
+            email_verified: a.email_verified,
+            totp_enabled: a.totp_enabled,
+            created_at: a.created_at.unwrap_or_default().into_chrono(),
+        }
+    }
+}
+
 pub trait ChronoToProtobufTimestamp {
     fn into_protobuf(self) -> Timestamp;
     fn to_protobuf(&self) -> Timestamp;
@@ -63,19 +221,18 @@ impl ChronoToProtobufTimestamp for DateTime<Utc> {
     }
 
     fn to_protobuf(&self) -> Timestamp {
-        self.clone().into_protobuf()
+        (*self).into_protobuf()
     }
 }
 
 impl ProtobufToChrono for Timestamp {
     fn into_chrono(self) -> DateTime<Utc> {
-        let naive = NaiveDateTime::from_timestamp_opt(self.seconds, self.nanos as u32)
-            .expect("Invalid protobuf Timestamp");
-        DateTime::<Utc>::from_utc(naive, Utc)
+        DateTime::from_timestamp(self.seconds, self.nanos as u32)
+            .expect("Invalid protobuf Timestamp")
     }
 
     fn to_chrono(&self) -> DateTime<Utc> {
-        self.clone().into_chrono()
+        (*self).into_chrono()
     }
 }
 
@@ -87,24 +244,67 @@ pub trait BlogClientTrait: Send + Sync + 'static {
         email: String,
         password: String,
     ) -> Result<(), BlogClientError>;
+    /// При включённом TOTP возвращает `BlogClientError::MfaRequired`
     async fn login(&mut self, email: String, password: String) -> Result<(), BlogClientError>;
+    /// Второй шаг входа: код из приложения или код восстановления
+    async fn verify_mfa(&mut self, mfa_token: String, code: String) -> Result<(), BlogClientError>;
+    /// Меняет сохранённый refresh-токен на новую пару токенов
+    async fn refresh(&mut self) -> Result<(), BlogClientError>;
+    /// Отзывает токены на сервере (`all` — на всех устройствах) и удаляет их локально
+    async fn logout(&mut self, all: bool) -> Result<(), BlogClientError>;
+    async fn get_me(&mut self) -> Result<Account, BlogClientError>;
+    /// Остальные сессии на сервере при этом закрываются
+    async fn change_password(
+        &mut self,
+        current_password: String,
+        new_password: String,
+    ) -> Result<(), BlogClientError>;
+    async fn change_email(
+        &mut self,
+        email: String,
+        current_password: String,
+    ) -> Result<Account, BlogClientError>;
+    async fn change_username(&mut self, username: String) -> Result<Account, BlogClientError>;
+    /// Только для администраторов
+    async fn set_user_role(
+        &mut self,
+        user_id: Uuid,
+        role: String,
+    ) -> Result<Account, BlogClientError>;
     async fn get_post_by_id(&mut self, id: Uuid) -> Result<Post, BlogClientError>;
+    async fn get_post_by_slug(&mut self, slug: &str) -> Result<Post, BlogClientError>;
     async fn list_posts(
         &mut self,
         author_id: Option<Uuid>,
         limit: Option<u32>,
         offset: Option<u32>,
     ) -> Result<Vec<Post>, BlogClientError>;
+    async fn search_posts(
+        &mut self,
+        query: &str,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Vec<SearchResult>, BlogClientError>;
     async fn create_post(
         &mut self,
         title: String,
         content: String,
+        publish_at: Option<DateTime<Utc>>,
     ) -> Result<Post, BlogClientError>;
+    /// С `expected_version` сервер отклонит правку, если пост с тех пор изменился
     async fn update_post(
         &mut self,
         id: Uuid,
         title: Option<String>,
         content: Option<String>,
+        expected_version: Option<i32>,
     ) -> Result<Post, BlogClientError>;
+    /// Переносит пост в корзину
     async fn delete_post(&mut self, id: Uuid) -> Result<(), BlogClientError>;
+    async fn list_trash(&mut self) -> Result<Vec<Post>, BlogClientError>;
+    async fn restore_post(&mut self, id: Uuid) -> Result<Post, BlogClientError>;
+    async fn publish_post(&mut self, id: Uuid) -> Result<Post, BlogClientError>;
+    async fn unpublish_post(&mut self, id: Uuid) -> Result<Post, BlogClientError>;
+    /// Только для модераторов и администраторов
+    async fn set_post_hidden(&mut self, id: Uuid, hidden: bool) -> Result<Post, BlogClientError>;
 }
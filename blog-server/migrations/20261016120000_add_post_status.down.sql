@@ -0,0 +1,6 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS idx_posts_published;
+ALTER TABLE posts
+    DROP COLUMN published_at,
+    DROP COLUMN status;
+DROP TYPE post_status;
@@ -0,0 +1,13 @@
+-- Add up migration script here
+CREATE TYPE post_status AS ENUM ('draft', 'published', 'archived');
+
+-- Уже существующие посты были публичными, поэтому считаем их опубликованными
+ALTER TABLE posts
+    ADD COLUMN status       post_status NOT NULL DEFAULT 'published',
+    ADD COLUMN published_at TIMESTAMPTZ;
+
+UPDATE posts SET published_at = created_at;
+
+ALTER TABLE posts ALTER COLUMN status SET DEFAULT 'draft';
+
+CREATE INDEX idx_posts_published ON posts (created_at DESC) WHERE status = 'published';
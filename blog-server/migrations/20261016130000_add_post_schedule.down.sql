@@ -0,0 +1,16 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS idx_posts_publish_at;
+
+UPDATE posts SET status = 'draft' WHERE status = 'scheduled';
+ALTER TABLE posts DROP COLUMN publish_at;
+
+-- Значение из enum удалить нельзя, поэтому пересоздаём тип
+DROP INDEX IF EXISTS idx_posts_published;
+ALTER TABLE posts ALTER COLUMN status DROP DEFAULT;
+ALTER TYPE post_status RENAME TO post_status_old;
+CREATE TYPE post_status AS ENUM ('draft', 'published', 'archived');
+ALTER TABLE posts
+    ALTER COLUMN status TYPE post_status USING status::text::post_status;
+ALTER TABLE posts ALTER COLUMN status SET DEFAULT 'draft';
+DROP TYPE post_status_old;
+CREATE INDEX idx_posts_published ON posts (created_at DESC) WHERE status = 'published';
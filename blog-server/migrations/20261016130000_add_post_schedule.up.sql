@@ -0,0 +1,8 @@
+-- Add up migration script here
+ALTER TYPE post_status ADD VALUE IF NOT EXISTS 'scheduled';
+
+ALTER TABLE posts
+    ADD COLUMN publish_at TIMESTAMPTZ;
+
+-- Фоновый публикатор выбирает просроченные посты по этому индексу
+CREATE INDEX idx_posts_publish_at ON posts (publish_at) WHERE publish_at IS NOT NULL;
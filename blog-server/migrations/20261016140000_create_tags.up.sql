@@ -0,0 +1,15 @@
+-- Add up migration script here
+CREATE TABLE tags
+(
+    id   UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL UNIQUE CHECK (trim(name) <> '')
+);
+
+CREATE TABLE post_tags
+(
+    post_id UUID NOT NULL REFERENCES posts (id) ON DELETE CASCADE,
+    tag_id  UUID NOT NULL REFERENCES tags (id) ON DELETE CASCADE,
+    PRIMARY KEY (post_id, tag_id)
+);
+
+CREATE INDEX idx_post_tags_tag_id ON post_tags (tag_id);
@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS post_slug_history;
+
+ALTER TABLE posts
+    DROP COLUMN IF EXISTS slug;
@@ -0,0 +1,46 @@
+-- Add up migration script here
+ALTER TABLE posts
+    ADD COLUMN slug TEXT;
+
+-- Повторяет slugify из domain/slug.rs: кириллица транслитерируется, остальное — дефисы.
+-- Основа обрезается до 71 символа, чтобы с суффиксом id уложиться в 80
+CREATE FUNCTION backfill_slug(title TEXT) RETURNS TEXT
+    LANGUAGE sql
+    IMMUTABLE
+AS
+$$
+SELECT COALESCE(
+               NULLIF(trim(both '-' from left(ltrim(regexp_replace(
+                       translate(
+                               replace(replace(replace(replace(replace(replace(replace(replace(replace(
+                                       translate(lower(title),
+                                                 'АБВГДЕЁЖЗИЙКЛМНОПРСТУФХЦЧШЩЪЫЬЭЮЯ',
+                                                 'абвгдеёжзийклмнопрстуфхцчшщъыьэюя'),
+                                       'щ', 'shch'), 'ш', 'sh'), 'ч', 'ch'), 'ц', 'ts'), 'х', 'kh'),
+                                       'ж', 'zh'), 'ё', 'yo'), 'ю', 'yu'), 'я', 'ya'),
+                               'абвгдезийклмнопрстуфыэъь',
+                               'abvgdeziyklmnoprstufye'),
+                       '[^a-z0-9]+', '-', 'g'), '-'), 71)), ''),
+               'post'
+       )
+$$;
+
+-- Существующим постам — slug из заголовка и префикса id, чтобы не было коллизий
+UPDATE posts
+SET slug = backfill_slug(title) || '-' || left(id::text, 8);
+
+DROP FUNCTION backfill_slug(TEXT);
+
+ALTER TABLE posts
+    ALTER COLUMN slug SET NOT NULL,
+    ADD CONSTRAINT posts_slug_key UNIQUE (slug);
+
+-- Старые slug'и после смены заголовка, по ним отдаётся 301 на актуальный адрес
+CREATE TABLE post_slug_history
+(
+    slug       TEXT PRIMARY KEY,
+    post_id    UUID        NOT NULL REFERENCES posts (id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_post_slug_history_post_id ON post_slug_history (post_id);
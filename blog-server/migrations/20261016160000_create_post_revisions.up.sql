@@ -0,0 +1,10 @@
+-- Add up migration script here
+CREATE TABLE post_revisions
+(
+    post_id    UUID        NOT NULL REFERENCES posts (id) ON DELETE CASCADE,
+    revision   INTEGER     NOT NULL CHECK (revision > 0),
+    title      TEXT        NOT NULL,
+    content    TEXT        NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (post_id, revision)
+);
@@ -0,0 +1,21 @@
+-- Add up migration script here
+CREATE TABLE comments
+(
+    id         UUID PRIMARY KEY,
+    post_id    UUID        NOT NULL REFERENCES posts (id) ON DELETE CASCADE,
+    author_id  UUID        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    -- ответы удаляются вместе с родительским комментарием
+    parent_id  UUID REFERENCES comments (id) ON DELETE CASCADE,
+    content    TEXT        NOT NULL CHECK (trim(content) <> ''),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_comments_post_id ON comments (post_id, created_at);
+CREATE INDEX idx_comments_parent_id ON comments (parent_id);
+
+CREATE TRIGGER trg_comments_update_timestamp
+    BEFORE UPDATE
+    ON comments
+    FOR EACH ROW
+EXECUTE FUNCTION update_updated_at();
@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS post_reactions;
+DROP TYPE IF EXISTS reaction_kind;
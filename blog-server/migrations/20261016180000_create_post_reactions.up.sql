@@ -0,0 +1,14 @@
+-- Add up migration script here
+CREATE TYPE reaction_kind AS ENUM ('like', 'love', 'laugh', 'wow', 'sad', 'angry');
+
+CREATE TABLE post_reactions
+(
+    post_id    UUID          NOT NULL REFERENCES posts (id) ON DELETE CASCADE,
+    user_id    UUID          NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    kind       reaction_kind NOT NULL,
+    created_at TIMESTAMPTZ   NOT NULL DEFAULT NOW(),
+    CONSTRAINT post_reactions_user_post_kind_key UNIQUE (user_id, post_id, kind)
+);
+
+-- Агрегация счётчиков для страницы постов идёт по post_id
+CREATE INDEX idx_post_reactions_post_id ON post_reactions (post_id, kind);
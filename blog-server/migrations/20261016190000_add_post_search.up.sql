@@ -0,0 +1,12 @@
+-- Add up migration script here
+-- Авторы пишут и по-русски, и по-английски: латиница разбирается english_stem, кириллица — russian_stem.
+-- Одна конфигурация для индекса, запроса и сниппета, поэтому подсвечивается ровно то, по чему пост нашёлся
+CREATE TEXT SEARCH CONFIGURATION blog_search (COPY = russian);
+
+ALTER TABLE posts
+    ADD COLUMN search_vector tsvector GENERATED ALWAYS AS (
+        setweight(to_tsvector('blog_search', title), 'A') ||
+        setweight(to_tsvector('blog_search', content), 'B')
+        ) STORED;
+
+CREATE INDEX idx_posts_search_vector ON posts USING GIN (search_vector);
@@ -0,0 +1,3 @@
+-- Add up migration script here
+-- Ключ keyset-пагинации ленты: (created_at, id) по убыванию
+CREATE INDEX idx_posts_created_at_id ON posts (created_at DESC, id DESC);
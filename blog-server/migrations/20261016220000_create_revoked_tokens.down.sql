@@ -0,0 +1,5 @@
+-- Add down migration script here
+ALTER TABLE users
+    DROP COLUMN IF EXISTS tokens_valid_after;
+
+DROP TABLE IF EXISTS revoked_tokens;
@@ -0,0 +1,15 @@
+-- Add up migration script here
+-- Отозванные access-токены; строка нужна только до истечения самого токена
+CREATE TABLE revoked_tokens
+(
+    jti        UUID PRIMARY KEY,
+    user_id    UUID        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_revoked_tokens_expires_at ON revoked_tokens (expires_at);
+
+-- "Выйти везде": токены, выпущенные раньше этой отметки, недействительны
+ALTER TABLE users
+    ADD COLUMN tokens_valid_after TIMESTAMPTZ;
@@ -0,0 +1,19 @@
+-- Add up migration script here
+CREATE TABLE sessions
+(
+    id           UUID PRIMARY KEY,
+    user_id      UUID        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    user_agent   TEXT,
+    ip           TEXT,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_seen_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    revoked_at   TIMESTAMPTZ
+);
+
+CREATE INDEX idx_sessions_user_id ON sessions (user_id, last_seen_at DESC);
+
+-- Семья refresh-токенов — это и есть сессия; выданные до миграции токены перевыпускать не будем
+DELETE FROM refresh_tokens;
+ALTER TABLE refresh_tokens
+    ADD CONSTRAINT refresh_tokens_family_id_fkey
+        FOREIGN KEY (family_id) REFERENCES sessions (id) ON DELETE CASCADE;
@@ -0,0 +1,7 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS email_outbox;
+DROP TABLE IF EXISTS account_tokens;
+DROP TYPE IF EXISTS account_token_purpose;
+
+ALTER TABLE users
+    DROP COLUMN IF EXISTS email_verified_at;
@@ -0,0 +1,36 @@
+-- Add up migration script here
+ALTER TABLE users
+    ADD COLUMN email_verified_at TIMESTAMPTZ;
+
+CREATE TYPE account_token_purpose AS ENUM ('password_reset', 'email_verification');
+
+-- Одноразовые токены из писем; email — адрес, на который ушло письмо
+CREATE TABLE account_tokens
+(
+    id         UUID PRIMARY KEY,
+    user_id    UUID                  NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    purpose    account_token_purpose NOT NULL,
+    token_hash TEXT                  NOT NULL UNIQUE,
+    email      TEXT                  NOT NULL,
+    expires_at TIMESTAMPTZ           NOT NULL,
+    used_at    TIMESTAMPTZ,
+    created_at TIMESTAMPTZ           NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_account_tokens_user_id ON account_tokens (user_id, purpose);
+
+-- Письма пишутся в одной транзакции с изменением, к которому относятся, и отправляются воркером
+CREATE TABLE email_outbox
+(
+    id         UUID PRIMARY KEY,
+    recipient  TEXT        NOT NULL,
+    subject    TEXT        NOT NULL,
+    body       TEXT        NOT NULL,
+    attempts   INT         NOT NULL DEFAULT 0,
+    last_error TEXT,
+    send_after TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    sent_at    TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_email_outbox_pending ON email_outbox (send_after) WHERE sent_at IS NULL;
@@ -0,0 +1,11 @@
+-- Add up migration script here
+-- Неудачные попытки входа; ключ — 'user:<username>' или 'ip:<адрес>'
+CREATE TABLE login_throttles
+(
+    key             TEXT PRIMARY KEY,
+    failures        INT         NOT NULL DEFAULT 0,
+    last_failure_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    locked_until    TIMESTAMPTZ
+);
+
+CREATE INDEX idx_login_throttles_last_failure_at ON login_throttles (last_failure_at);
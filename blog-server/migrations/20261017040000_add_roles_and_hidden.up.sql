@@ -0,0 +1,12 @@
+-- Add up migration script here
+CREATE TYPE user_role AS ENUM ('user', 'moderator', 'admin');
+
+ALTER TABLE users
+    ADD COLUMN role user_role NOT NULL DEFAULT 'user';
+
+-- Скрытые модератором посты и комментарии видны только их авторам
+ALTER TABLE posts
+    ADD COLUMN hidden_at TIMESTAMPTZ;
+
+ALTER TABLE comments
+    ADD COLUMN hidden_at TIMESTAMPTZ;
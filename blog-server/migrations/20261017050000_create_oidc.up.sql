@@ -0,0 +1,22 @@
+-- Add up migration script here
+-- Учётная запись у внешнего провайдера; subject уникален только в пределах issuer
+CREATE TABLE user_identities
+(
+    issuer     TEXT        NOT NULL,
+    subject    TEXT        NOT NULL,
+    user_id    UUID        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (issuer, subject)
+);
+
+CREATE INDEX idx_user_identities_user_id ON user_identities (user_id);
+
+-- Начатые входы через OIDC: state, nonce и PKCE-verifier до возврата с провайдера
+CREATE TABLE oidc_logins
+(
+    state_hash    TEXT PRIMARY KEY,
+    nonce         TEXT        NOT NULL,
+    code_verifier TEXT        NOT NULL,
+    expires_at    TIMESTAMPTZ NOT NULL,
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
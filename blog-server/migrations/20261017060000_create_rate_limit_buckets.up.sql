@@ -0,0 +1,11 @@
+-- Add up migration script here
+-- Общие для всех инстансов вёдра ограничителя запросов (RATE_LIMIT_STORE=postgres);
+-- ключ — '<класс>:user:<id>' или '<класс>:ip:<адрес>'
+CREATE TABLE rate_limit_buckets
+(
+    key        TEXT PRIMARY KEY,
+    tokens     DOUBLE PRECISION NOT NULL,
+    updated_at TIMESTAMPTZ      NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_rate_limit_buckets_updated_at ON rate_limit_buckets (updated_at);
@@ -0,0 +1,35 @@
+-- Add up migration script here
+-- Журнал действий: кто, что, над чем и откуда. Без внешних ключей — записи
+-- должны пережить удаление и пользователя, и поста
+CREATE TABLE audit_log
+(
+    id          BIGSERIAL PRIMARY KEY,
+    occurred_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    actor_id    UUID,
+    action      TEXT        NOT NULL,
+    target_type TEXT        NOT NULL,
+    target_id   UUID,
+    request_id  TEXT,
+    ip          TEXT,
+    before      JSONB,
+    after       JSONB
+);
+
+-- Только добавление: изменить или удалить запись нельзя даже из приложения
+CREATE OR REPLACE FUNCTION audit_log_append_only()
+    RETURNS TRIGGER AS
+$$
+BEGIN
+    RAISE EXCEPTION 'audit_log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_audit_log_append_only
+    BEFORE UPDATE OR DELETE
+    ON audit_log
+    FOR EACH ROW
+EXECUTE FUNCTION audit_log_append_only();
+
+CREATE INDEX idx_audit_log_occurred_at ON audit_log (occurred_at DESC);
+CREATE INDEX idx_audit_log_actor ON audit_log (actor_id, occurred_at DESC);
+CREATE INDEX idx_audit_log_target ON audit_log (target_id, occurred_at DESC);
@@ -0,0 +1,6 @@
+-- Add up migration script here
+-- Удалённые посты лежат в корзине до окончательной очистки фоновой задачей
+ALTER TABLE posts
+    ADD COLUMN deleted_at TIMESTAMPTZ;
+
+CREATE INDEX idx_posts_deleted_at ON posts (deleted_at) WHERE deleted_at IS NOT NULL;
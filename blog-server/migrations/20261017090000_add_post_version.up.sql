@@ -0,0 +1,4 @@
+-- Add up migration script here
+-- Номер версии для оптимистичной блокировки: растёт при каждом изменении поста
+ALTER TABLE posts
+    ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,58 @@
+use std::sync::Arc;
+
+use tracing::error;
+
+use crate::data::audit_repository::AuditRepository;
+use crate::domain::audit::{AuditEvent, AuditFilter, AuditPage};
+use crate::domain::error::DomainError;
+use crate::domain::session::ClientInfo;
+
+const DEFAULT_AUDIT_PAGE_SIZE: i64 = 50;
+const MAX_AUDIT_PAGE_SIZE: i64 = 500;
+
+/// Журнал аудита, общий для сервисов; пишется после того, как действие удалось
+pub struct AuditLog {
+    repo: Arc<dyn AuditRepository>,
+}
+
+impl AuditLog {
+    pub fn new(repo: Arc<dyn AuditRepository>) -> Self {
+        Self { repo }
+    }
+
+    /// Действие к этому моменту уже совершено, поэтому сбой журнала не отменяет его,
+    /// а только попадает в лог вместе с самим событием
+    pub async fn record(&self, event: AuditEvent, client: &ClientInfo) {
+        if let Err(e) = self.repo.append(&event, client).await {
+            error!(
+                action = event.action.as_str(),
+                actor_id = ?event.actor_id,
+                target_id = ?event.target_id,
+                request_id = ?client.request_id,
+                "failed to write audit log: {}",
+                e
+            );
+        }
+    }
+
+    /// Права проверяет вызывающий: журнал доступен только администраторам
+    pub async fn search(
+        &self,
+        filter: &AuditFilter,
+        limit: Option<i64>,
+    ) -> Result<AuditPage, DomainError> {
+        let limit = limit
+            .unwrap_or(DEFAULT_AUDIT_PAGE_SIZE)
+            .clamp(1, MAX_AUDIT_PAGE_SIZE);
+        let entries = self.repo.find(filter, limit).await?;
+        let next_before_id = if (entries.len() as i64) < limit {
+            None
+        } else {
+            entries.last().map(|entry| entry.id)
+        };
+        Ok(AuditPage {
+            entries,
+            next_before_id,
+        })
+    }
+}
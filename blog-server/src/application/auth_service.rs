@@ -1,39 +1,293 @@
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
-use tracing::instrument;
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::jwk::JwkSet;
+use rand_core::{OsRng, RngCore};
+use serde_json::json;
+use tracing::{error, info, instrument, warn};
+use uuid::Uuid;
 
+use crate::application::audit_log::AuditLog;
+use crate::data::login_throttle_repository::LoginThrottleRepository;
+use crate::data::token_repository::TokenRepository;
 use crate::data::user_repository::UserRepository;
-use crate::domain::{error::DomainError, user::User};
-use crate::infrastructure::security::{JwtKeys, hash_password, verify_password};
-use crate::presentation::dto::{LoginRequest, RegisterRequest};
+use crate::domain::audit::{AuditAction, AuditEvent};
+use crate::domain::email::Email;
+use crate::domain::mfa::{LoginOutcome, MfaChallenge, TotpEnrollment};
+use crate::domain::oidc::{ExternalIdentity, OidcLogin};
+use crate::domain::personal_token::{Credential, PersonalAccessToken};
+use crate::domain::session::{ClientInfo, Session};
+use crate::domain::token::{AccountToken, AccountTokenPurpose, RefreshToken, TokenPair};
+use crate::domain::{
+    error::DomainError,
+    user::{Role, User},
+};
+use crate::infrastructure::oidc::OidcClient;
+use crate::infrastructure::security::{
+    Claims, JwtKeys, generate_opaque_token, hash_password, hash_token, verify_password,
+};
+use crate::infrastructure::totp;
+use crate::presentation::dto::{CreatePersonalTokenRequest, LoginRequest, RegisterRequest};
+
+/// По префиксу персональный токен отличается от JWT без похода в базу
+const PERSONAL_TOKEN_PREFIX: &str = "blog_pat_";
+const MAX_TOKEN_NAME_LENGTH: usize = 100;
+const MFA_CHALLENGE_TTL_SECS: i64 = 300;
+const MAX_MFA_ATTEMPTS: i32 = 5;
+const RECOVERY_CODE_COUNT: usize = 10;
+const MIN_PASSWORD_LENGTH: usize = 8;
+const MIN_USERNAME_LENGTH: usize = 6;
+const PASSWORD_RESET_TTL_MINS: i64 = 60;
+const EMAIL_VERIFICATION_TTL_HOURS: i64 = 48;
+/// Неудачные входы считаются подряд, пока между ними не больше этого окна
+const LOGIN_FAILURE_WINDOW_MINS: i64 = 15;
+/// После стольких ошибок вход блокируется; каждая следующая удваивает блокировку
+const MAX_USERNAME_FAILURES: i32 = 5;
+const MAX_IP_FAILURES: i32 = 20;
+const LOGIN_LOCKOUT_BASE_SECS: i64 = 30;
+const LOGIN_LOCKOUT_MAX_SECS: i64 = 900;
+/// Столько пользователь может провести на странице входа провайдера
+const OIDC_LOGIN_TTL_MINS: i64 = 10;
 
 #[derive(Clone)]
-pub struct AuthService<R: UserRepository + 'static> {
+pub struct AuthService<R: UserRepository + 'static, K: TokenRepository + 'static> {
     repo: Arc<R>,
+    tokens: Arc<K>,
+    /// Счётчики неудачных входов по имени пользователя и IP
+    throttles: Arc<dyn LoginThrottleRepository>,
     keys: JwtKeys,
+    refresh_ttl: Duration,
+    /// Адрес фронтенда для ссылок в письмах
+    app_url: String,
+    /// Вход через корпоративный SSO, если он настроен
+    oidc: Option<Arc<OidcClient>>,
+    audit: Arc<AuditLog>,
 }
 
-impl<R> AuthService<R>
+impl<R, K> AuthService<R, K>
 where
     R: UserRepository + 'static,
+    K: TokenRepository + 'static,
 {
-    pub fn new(repo: Arc<R>, keys: JwtKeys) -> Self {
-        Self { repo, keys }
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        repo: Arc<R>,
+        tokens: Arc<K>,
+        throttles: Arc<dyn LoginThrottleRepository>,
+        keys: JwtKeys,
+        refresh_ttl_secs: i64,
+        app_url: String,
+        oidc: Option<OidcClient>,
+        audit: Arc<AuditLog>,
+    ) -> Self {
+        Self {
+            repo,
+            tokens,
+            throttles,
+            keys,
+            refresh_ttl: Duration::seconds(refresh_ttl_secs),
+            app_url,
+            oidc: oidc.map(Arc::new),
+            audit,
+        }
     }
 
-    pub fn keys(&self) -> &JwtKeys {
-        &self.keys
+    pub fn jwks(&self) -> &JwkSet {
+        self.keys.jwks()
     }
 
-    pub async fn get_user(&self, id: uuid::Uuid) -> Result<User, DomainError> {
-        self.repo
-            .find_by_id(id)
+    /// Проверяет access-токен (подпись, срок, отзыв, сессию) или персональный токен
+    /// и возвращает пользователя вместе с тем, что этим токеном разрешено
+    pub async fn authenticate(&self, token: &str) -> Result<(User, Credential), DomainError> {
+        let (user_id, credential) = if token.starts_with(PERSONAL_TOKEN_PREFIX) {
+            let personal = self
+                .tokens
+                .find_personal_token(&hash_token(token))
+                .await?
+                .filter(PersonalAccessToken::is_active)
+                .ok_or(DomainError::Unauthorized)?;
+            if personal.needs_touch() {
+                self.tokens.touch_personal_token(personal.id).await?;
+            }
+            (personal.user_id, Credential::PersonalToken(personal.scopes))
+        } else {
+            (self.verify(token).await?.user_id, Credential::Session)
+        };
+
+        let user = self
+            .repo
+            .find_by_id(user_id)
+            .await?
+            .ok_or(DomainError::Unauthorized)?;
+        Ok((user, credential))
+    }
+
+    /// Закрывает текущую сессию и, если передан, сессию refresh-токена
+    #[instrument(skip_all)]
+    pub async fn logout(
+        &self,
+        access_token: &str,
+        refresh_token: Option<&str>,
+    ) -> Result<(), DomainError> {
+        let access = self.verify(access_token).await?;
+
+        if let Some(refresh_token) = refresh_token
+            && let Some(stored) = self
+                .tokens
+                .find_refresh_token(&hash_token(refresh_token))
+                .await?
+        {
+            self.tokens
+                .revoke_session(stored.family_id, access.user_id)
+                .await?;
+        }
+
+        self.tokens
+            .revoke_session(access.session_id, access.user_id)
+            .await?;
+        self.tokens
+            .revoke_access_token(access.jti, access.user_id, access.expires_at)
+            .await
+    }
+
+    /// Выход на всех устройствах: гасит все токены пользователя, включая текущий
+    #[instrument(skip_all)]
+    pub async fn logout_all(&self, access_token: &str) -> Result<(), DomainError> {
+        let access = self.verify(access_token).await?;
+
+        self.tokens.revoke_all_for_user(access.user_id).await?;
+        self.tokens
+            .revoke_access_token(access.jti, access.user_id, access.expires_at)
+            .await
+    }
+
+    /// Активные сессии владельца токена; текущая помечена `current`
+    pub async fn list_sessions(&self, access_token: &str) -> Result<Vec<Session>, DomainError> {
+        let access = self.verify(access_token).await?;
+
+        let mut sessions = self.tokens.list_sessions(access.user_id).await?;
+        for session in sessions.iter_mut() {
+            session.current = session.id == access.session_id;
+        }
+        Ok(sessions)
+    }
+
+    #[instrument(skip(self, access_token))]
+    pub async fn revoke_session(
+        &self,
+        access_token: &str,
+        session_id: Uuid,
+    ) -> Result<(), DomainError> {
+        let access = self.verify(access_token).await?;
+
+        if !self
+            .tokens
+            .revoke_session(session_id, access.user_id)
+            .await?
+        {
+            return Err(DomainError::SessionNotFound(session_id));
+        }
+        Ok(())
+    }
+
+    /// Выпускает персональный токен. Управлять токенами можно только из сессии,
+    /// поэтому токен не может выпустить другой токен с более широкими правами.
+    #[instrument(skip(self, access_token))]
+    pub async fn create_personal_token(
+        &self,
+        access_token: &str,
+        request: CreatePersonalTokenRequest,
+    ) -> Result<(PersonalAccessToken, String), DomainError> {
+        let access = self.verify(access_token).await?;
+
+        let name = request.name.trim().to_string();
+        if name.is_empty() || name.chars().count() > MAX_TOKEN_NAME_LENGTH {
+            return Err(DomainError::Validation(format!(
+                "token name must be 1 to {MAX_TOKEN_NAME_LENGTH} characters"
+            )));
+        }
+        let mut scopes = request.scopes;
+        scopes.sort();
+        scopes.dedup();
+        if scopes.is_empty() {
+            return Err(DomainError::Validation(
+                "at least one scope is required".to_string(),
+            ));
+        }
+        if request.expires_at.is_some_and(|at| at <= Utc::now()) {
+            return Err(DomainError::Validation(
+                "expires_at must be in the future".to_string(),
+            ));
+        }
+
+        let secret = format!("{PERSONAL_TOKEN_PREFIX}{}", generate_opaque_token());
+        let token = PersonalAccessToken::new(
+            access.user_id,
+            name,
+            hash_token(&secret),
+            scopes,
+            request.expires_at,
+        );
+        self.tokens.create_personal_token(&token).await?;
+        Ok((token, secret))
+    }
+
+    pub async fn list_personal_tokens(
+        &self,
+        access_token: &str,
+    ) -> Result<Vec<PersonalAccessToken>, DomainError> {
+        let access = self.verify(access_token).await?;
+        self.tokens.list_personal_tokens(access.user_id).await
+    }
+
+    #[instrument(skip(self, access_token))]
+    pub async fn revoke_personal_token(
+        &self,
+        access_token: &str,
+        token_id: Uuid,
+    ) -> Result<(), DomainError> {
+        let access = self.verify(access_token).await?;
+
+        if !self
+            .tokens
+            .revoke_personal_token(token_id, access.user_id)
             .await?
-            .ok_or(DomainError::UserNotFound(id))
+        {
+            return Err(DomainError::PersonalTokenNotFound(token_id));
+        }
+        Ok(())
+    }
+
+    async fn verify(&self, token: &str) -> Result<AccessToken, DomainError> {
+        let claims = self
+            .keys
+            .verify_token(token)
+            .map_err(|_| DomainError::Unauthorized)?;
+        let access = AccessToken::try_from(claims)?;
+
+        if self
+            .tokens
+            .is_access_token_revoked(
+                access.jti,
+                access.session_id,
+                access.user_id,
+                access.issued_at,
+            )
+            .await?
+        {
+            return Err(DomainError::Unauthorized);
+        }
+
+        self.tokens.touch_session(access.session_id, None).await?;
+        Ok(access)
     }
 
     #[instrument(skip(self))]
-    pub async fn register(&self, user: &RegisterRequest) -> Result<User, DomainError> {
+    pub async fn register(
+        &self,
+        user: &RegisterRequest,
+        client: &ClientInfo,
+    ) -> Result<User, DomainError> {
         let hash = hash_password(user.password.as_str())
             .map_err(|err| DomainError::Internal(err.to_string()))?;
         let user = User::new(
@@ -41,25 +295,896 @@ where
             user.email.as_str().to_lowercase(),
             hash,
         );
-        self.repo.create(user).await
+        let user = self.repo.create(user).await?;
+        self.send_verification_email(&user).await?;
+        self.audit
+            .record(
+                AuditEvent::new(AuditAction::UserRegister, Some(user.id), Some(user.id))
+                    .after(json!({ "username": user.username, "email": user.email })),
+                client,
+            )
+            .await;
+        Ok(user)
+    }
+
+    /// Письмо со ссылкой на сброс пароля. Неизвестный адрес не считается ошибкой,
+    /// иначе по ответу можно было бы перебирать зарегистрированные адреса.
+    #[instrument(skip(self))]
+    pub async fn request_password_reset(&self, email: &str) -> Result<(), DomainError> {
+        let Some(user) = self
+            .repo
+            .find_by_email(&email.trim().to_lowercase())
+            .await?
+        else {
+            info!("password reset requested for unknown email");
+            return Ok(());
+        };
+
+        let secret = generate_opaque_token();
+        let token = AccountToken::new(
+            user.id,
+            AccountTokenPurpose::PasswordReset,
+            hash_token(&secret),
+            user.email.clone(),
+            Duration::minutes(PASSWORD_RESET_TTL_MINS),
+        );
+        let email = Email {
+            recipient: user.email,
+            subject: "Reset your password".to_string(),
+            body: format!(
+                "Hi {},\n\nTo set a new password, open the link below. It is valid for {PASSWORD_RESET_TTL_MINS} minutes.\n\n{}/reset-password?token={secret}\n\nIf you did not ask for this, just ignore this email.\n",
+                user.username, self.app_url
+            ),
+        };
+        self.tokens.create_account_token(&token, &email).await
+    }
+
+    /// Новый пароль по ссылке из письма; все сессии пользователя при этом закрываются
+    #[instrument(skip_all)]
+    pub async fn reset_password(&self, token: &str, new_password: &str) -> Result<(), DomainError> {
+        validate_password(new_password)?;
+        let token = self
+            .tokens
+            .consume_account_token(&hash_token(token), AccountTokenPurpose::PasswordReset)
+            .await?
+            .ok_or_else(invalid_link)?;
+
+        let hash =
+            hash_password(new_password).map_err(|err| DomainError::Internal(err.to_string()))?;
+        self.repo.update_password(token.user_id, &hash).await?;
+        self.tokens.revoke_all_for_user(token.user_id).await
+    }
+
+    #[instrument(skip_all)]
+    pub async fn verify_email(&self, token: &str) -> Result<(), DomainError> {
+        let token = self
+            .tokens
+            .consume_account_token(&hash_token(token), AccountTokenPurpose::EmailVerification)
+            .await?
+            .ok_or_else(invalid_link)?;
+
+        // Адрес успели сменить — ссылка подтверждала уже не его
+        if !self
+            .repo
+            .mark_email_verified(token.user_id, &token.email)
+            .await?
+        {
+            return Err(invalid_link());
+        }
+        Ok(())
+    }
+
+    #[instrument(skip_all)]
+    pub async fn resend_verification_email(&self, access_token: &str) -> Result<(), DomainError> {
+        let user = self.current_user(access_token).await?;
+        if user.email_verified_at.is_some() {
+            return Err(DomainError::Validation(
+                "email is already verified".to_string(),
+            ));
+        }
+        self.send_verification_email(&user).await
+    }
+
+    async fn send_verification_email(&self, user: &User) -> Result<(), DomainError> {
+        let secret = generate_opaque_token();
+        let token = AccountToken::new(
+            user.id,
+            AccountTokenPurpose::EmailVerification,
+            hash_token(&secret),
+            user.email.clone(),
+            Duration::hours(EMAIL_VERIFICATION_TTL_HOURS),
+        );
+        let email = Email {
+            recipient: user.email.clone(),
+            subject: "Confirm your email".to_string(),
+            body: format!(
+                "Hi {},\n\nPlease confirm your email address by opening the link below.\n\n{}/verify-email?token={secret}\n",
+                user.username, self.app_url
+            ),
+        };
+        self.tokens.create_account_token(&token, &email).await
     }
 
+    /// С включённым TOTP вместо токенов возвращает challenge для второго шага
     #[instrument(skip(self))]
-    pub async fn login(&self, login: &LoginRequest) -> Result<String, DomainError> {
+    pub async fn login(
+        &self,
+        login: &LoginRequest,
+        client: &ClientInfo,
+    ) -> Result<LoginOutcome, DomainError> {
+        let username = login.username.as_str().to_lowercase();
+        let throttles = login_throttles(&username, client);
+        self.ensure_login_allowed(&throttles, &username, client)
+            .await?;
+
+        // Для несуществующего имени хеш всё равно проверяется, чтобы время ответа
+        // не выдавало, есть ли такой пользователь
+        let user = self.repo.find_by_username(&username).await?;
+        let valid = match &user {
+            Some(user) => verify_password(login.password.as_str(), &user.password_hash),
+            None => verify_password(login.password.as_str(), dummy_password_hash()),
+        }
+        .unwrap_or(false);
+        let target_id = user.as_ref().map(|user| user.id);
+        let Some(user) = user.filter(|_| valid) else {
+            self.audit
+                .record(
+                    AuditEvent::new(AuditAction::LoginFailed, None, target_id)
+                        .after(json!({ "username": username, "method": "password" })),
+                    client,
+                )
+                .await;
+            self.record_login_failure(&throttles, &username, client)
+                .await?;
+            return Err(DomainError::Unauthorized);
+        };
+
+        // Счётчики сбрасываются только после полного входа: с TOTP — после кода в verify_mfa
+        if user.totp_enabled() {
+            let mfa_token = generate_opaque_token();
+            self.tokens
+                .create_mfa_challenge(
+                    user.id,
+                    &hash_token(&mfa_token),
+                    Utc::now() + Duration::seconds(MFA_CHALLENGE_TTL_SECS),
+                )
+                .await?;
+            return Ok(LoginOutcome::MfaRequired(MfaChallenge {
+                mfa_token,
+                expires_in: MFA_CHALLENGE_TTL_SECS,
+            }));
+        }
+
+        self.throttles.clear_login_failures(&throttles[0].0).await?;
+        let tokens = self.issue_tokens(user.id, client).await?;
+        self.audit_login(&user, "password", client).await;
+        Ok(LoginOutcome::Tokens(tokens))
+    }
+
+    /// `TooManyRequests`, пока имя пользователя или IP заблокированы после неудачных входов
+    async fn ensure_login_allowed(
+        &self,
+        throttles: &[(String, i32)],
+        username: &str,
+        client: &ClientInfo,
+    ) -> Result<(), DomainError> {
+        let keys: Vec<String> = throttles.iter().map(|(key, _)| key.clone()).collect();
+        if let Some(locked_until) = self.throttles.login_locked_until(&keys).await? {
+            warn!(
+                event = "login_blocked",
+                username = %username,
+                ip = ?client.ip,
+                "login attempt during lockout"
+            );
+            let retry_after = (locked_until - Utc::now()).num_seconds().max(1);
+            return Err(DomainError::TooManyRequests(retry_after as u64));
+        }
+        Ok(())
+    }
+
+    async fn audit_login(&self, user: &User, method: &str, client: &ClientInfo) {
+        self.audit
+            .record(
+                AuditEvent::new(AuditAction::Login, Some(user.id), Some(user.id))
+                    .after(json!({ "username": user.username, "method": method })),
+                client,
+            )
+            .await;
+    }
+
+    async fn record_login_failure(
+        &self,
+        throttles: &[(String, i32)],
+        username: &str,
+        client: &ClientInfo,
+    ) -> Result<(), DomainError> {
+        for (key, max_failures) in throttles {
+            let failures = self
+                .throttles
+                .record_login_failure(key, Duration::minutes(LOGIN_FAILURE_WINDOW_MINS))
+                .await?;
+            warn!(
+                event = "login_failed",
+                username = %username,
+                ip = ?client.ip,
+                key = %key,
+                failures,
+                "failed login attempt"
+            );
+
+            if let Some(lockout) = login_lockout(failures, *max_failures) {
+                self.throttles.lock_login(key, Utc::now() + lockout).await?;
+                warn!(
+                    event = "login_locked",
+                    key = %key,
+                    failures,
+                    lockout_secs = lockout.num_seconds(),
+                    "login locked out"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Текущий пароль при смене настроек идёт в те же счётчики, что и вход:
+    /// иначе с украденным access-токеном его можно подбирать без ограничений
+    async fn check_current_password(
+        &self,
+        user: &User,
+        password: &str,
+        client: &ClientInfo,
+    ) -> Result<(), DomainError> {
+        let username = user.username.to_lowercase();
+        let throttles = login_throttles(&username, client);
+        self.ensure_login_allowed(&throttles, &username, client)
+            .await?;
+
+        if !verify_password(password, &user.password_hash).unwrap_or(false) {
+            self.record_login_failure(&throttles, &username, client)
+                .await?;
+            return Err(DomainError::Validation(
+                "current password is incorrect".to_string(),
+            ));
+        }
+        self.throttles.clear_login_failures(&throttles[0].0).await
+    }
+
+    /// Второй шаг входа: код из приложения или код восстановления.
+    /// Challenge одноразовый, на ввод кода даётся несколько попыток; неверные коды
+    /// идут в те же счётчики, что и неверные пароли, иначе код можно перебирать,
+    /// каждый раз заново вводя известный пароль.
+    #[instrument(skip_all)]
+    pub async fn verify_mfa(
+        &self,
+        mfa_token: &str,
+        code: &str,
+        client: &ClientInfo,
+    ) -> Result<TokenPair, DomainError> {
+        let token_hash = hash_token(mfa_token);
+        let user_id = self
+            .tokens
+            .attempt_mfa_challenge(&token_hash, MAX_MFA_ATTEMPTS)
+            .await?
+            .ok_or(DomainError::Unauthorized)?;
         let user = self
             .repo
-            .find_by_username(&login.username.as_str().to_lowercase())
+            .find_by_id(user_id)
             .await?
             .ok_or(DomainError::Unauthorized)?;
+        let username = user.username.to_lowercase();
+        let throttles = login_throttles(&username, client);
+        self.ensure_login_allowed(&throttles, &username, client)
+            .await?;
 
-        let valid = verify_password(login.password.as_str(), &user.password_hash)
-            .map_err(|_| DomainError::Unauthorized)?;
+        if !self.check_second_factor(&user, code).await? {
+            warn!(user_id = %user.id, "invalid two-factor code");
+            self.audit
+                .record(
+                    AuditEvent::new(AuditAction::LoginFailed, None, Some(user.id))
+                        .after(json!({ "username": user.username, "method": "mfa" })),
+                    client,
+                )
+                .await;
+            self.record_login_failure(&throttles, &username, client)
+                .await?;
+            return Err(DomainError::Unauthorized);
+        }
+        if !self.tokens.delete_mfa_challenge(&token_hash).await? {
+            return Err(DomainError::Unauthorized);
+        }
+        self.throttles.clear_login_failures(&throttles[0].0).await?;
+
+        let tokens = self.issue_tokens(user.id, client).await?;
+        self.audit_login(&user, "mfa", client).await;
+        Ok(tokens)
+    }
+
+    /// Начинает подключение TOTP; повторный вызов до подтверждения выдаёт новый секрет
+    #[instrument(skip_all)]
+    pub async fn enroll_totp(&self, access_token: &str) -> Result<TotpEnrollment, DomainError> {
+        let user = self.current_user(access_token).await?;
+        if user.totp_enabled() {
+            return Err(DomainError::Validation(
+                "two-factor authentication is already enabled".to_string(),
+            ));
+        }
+
+        let secret = totp::generate_secret();
+        self.repo.start_totp_enrollment(user.id, &secret).await?;
+
+        Ok(TotpEnrollment {
+            otpauth_uri: totp::otpauth_uri(&secret, &user.username),
+            secret,
+        })
+    }
+
+    /// Включает TOTP после проверки первого кода и выдаёт коды восстановления
+    #[instrument(skip_all)]
+    pub async fn confirm_totp(
+        &self,
+        access_token: &str,
+        code: &str,
+    ) -> Result<Vec<String>, DomainError> {
+        let user = self.current_user(access_token).await?;
+        let secret = match &user.totp_secret {
+            Some(secret) if !user.totp_enabled() => secret,
+            _ => {
+                return Err(DomainError::Validation(
+                    "no pending two-factor enrollment".to_string(),
+                ));
+            }
+        };
+
+        let valid = match totp::verify(secret, code, Utc::now()) {
+            Some(step) => self.repo.use_totp_step(user.id, step).await?,
+            None => false,
+        };
         if !valid {
+            return Err(invalid_code());
+        }
+
+        self.repo.enable_totp(user.id).await?;
+        self.new_recovery_codes(user.id).await
+    }
+
+    #[instrument(skip_all)]
+    pub async fn disable_totp(&self, access_token: &str, code: &str) -> Result<(), DomainError> {
+        let user = self.current_user(access_token).await?;
+        if !user.totp_enabled() {
+            return Err(DomainError::Validation(
+                "two-factor authentication is not enabled".to_string(),
+            ));
+        }
+        if !self.check_second_factor(&user, code).await? {
+            return Err(invalid_code());
+        }
+
+        self.repo.disable_totp(user.id).await?;
+        self.tokens.replace_recovery_codes(user.id, &[]).await
+    }
+
+    /// Новый набор кодов восстановления; старые перестают действовать
+    #[instrument(skip_all)]
+    pub async fn regenerate_recovery_codes(
+        &self,
+        access_token: &str,
+        code: &str,
+    ) -> Result<Vec<String>, DomainError> {
+        let user = self.current_user(access_token).await?;
+        if !self.check_second_factor(&user, code).await? {
+            return Err(invalid_code());
+        }
+        self.new_recovery_codes(user.id).await
+    }
+
+    /// Профиль владельца токена
+    pub async fn me(&self, access_token: &str) -> Result<User, DomainError> {
+        self.current_user(access_token).await
+    }
+
+    /// Смена пароля с проверкой текущего; все сессии, кроме текущей, закрываются
+    #[instrument(skip_all)]
+    pub async fn change_password(
+        &self,
+        access_token: &str,
+        current_password: &str,
+        new_password: &str,
+        client: &ClientInfo,
+    ) -> Result<(), DomainError> {
+        let access = self.verify(access_token).await?;
+        let user = self
+            .repo
+            .find_by_id(access.user_id)
+            .await?
+            .ok_or(DomainError::Unauthorized)?;
+        self.check_current_password(&user, current_password, client)
+            .await?;
+        validate_password(new_password)?;
+
+        let hash =
+            hash_password(new_password).map_err(|err| DomainError::Internal(err.to_string()))?;
+        self.repo.update_password(user.id, &hash).await?;
+        self.tokens
+            .revoke_other_sessions(user.id, access.session_id)
+            .await
+    }
+
+    /// Новый адрес требует пароля и считается неподтверждённым до перехода по ссылке из письма
+    #[instrument(skip(self, access_token, current_password))]
+    pub async fn change_email(
+        &self,
+        access_token: &str,
+        current_password: &str,
+        email: &str,
+        client: &ClientInfo,
+    ) -> Result<User, DomainError> {
+        let user = self.current_user(access_token).await?;
+        self.check_current_password(&user, current_password, client)
+            .await?;
+
+        let email = email.trim().to_lowercase();
+        if !email.contains('@') {
+            return Err(DomainError::Validation("invalid email".to_string()));
+        }
+        if email == user.email {
+            return Ok(user);
+        }
+
+        self.repo.update_email(user.id, &email).await?;
+        let user = User {
+            email,
+            email_verified_at: None,
+            ..user
+        };
+        self.send_verification_email(&user).await?;
+        Ok(user)
+    }
+
+    #[instrument(skip(self, access_token))]
+    pub async fn change_username(
+        &self,
+        access_token: &str,
+        username: &str,
+    ) -> Result<User, DomainError> {
+        let user = self.current_user(access_token).await?;
+
+        // Вход ищет пользователя по имени в нижнем регистре
+        let username = username.trim().to_lowercase();
+        if username.chars().count() < MIN_USERNAME_LENGTH {
+            return Err(DomainError::Validation(format!(
+                "username must be at least {MIN_USERNAME_LENGTH} characters"
+            )));
+        }
+        if username == user.username {
+            return Ok(user);
+        }
+
+        self.repo.update_username(user.id, &username).await?;
+        Ok(User { username, ..user })
+    }
+
+    /// Назначает роль другому пользователю; только для администраторов в обычной сессии
+    #[instrument(skip(self, access_token, client))]
+    pub async fn set_user_role(
+        &self,
+        access_token: &str,
+        user_id: Uuid,
+        role: Role,
+        client: &ClientInfo,
+    ) -> Result<User, DomainError> {
+        let admin = self.require_admin(access_token).await?;
+        // Иначе последний администратор может случайно остаться без прав
+        if admin.id == user_id {
+            return Err(DomainError::Validation(
+                "you cannot change your own role".to_string(),
+            ));
+        }
+
+        let user = self
+            .repo
+            .find_by_id(user_id)
+            .await?
+            .ok_or(DomainError::UserNotFound(user_id))?;
+        if user.role != role {
+            self.repo.update_role(user_id, role).await?;
+            info!(admin_id = %admin.id, user_id = %user_id, from = %user.role, to = %role, "user role changed");
+            self.audit
+                .record(
+                    AuditEvent::new(AuditAction::RoleChange, Some(admin.id), Some(user_id))
+                        .before(json!({ "role": user.role }))
+                        .after(json!({ "role": role })),
+                    client,
+                )
+                .await;
+        }
+        Ok(User { role, ..user })
+    }
+
+    /// Администратор в обычной сессии; персональным токеном администрировать нельзя
+    pub async fn require_admin(&self, access_token: &str) -> Result<User, DomainError> {
+        let user = self.current_user(access_token).await?;
+        if user.role != Role::Admin {
+            return Err(DomainError::Forbidden);
+        }
+        Ok(user)
+    }
+
+    async fn current_user(&self, access_token: &str) -> Result<User, DomainError> {
+        let access = self.verify(access_token).await?;
+        self.repo
+            .find_by_id(access.user_id)
+            .await?
+            .ok_or(DomainError::Unauthorized)
+    }
+
+    /// Код TOTP (каждый принимается один раз) или неиспользованный код восстановления
+    async fn check_second_factor(&self, user: &User, code: &str) -> Result<bool, DomainError> {
+        let Some(secret) = user.totp_secret.as_deref().filter(|_| user.totp_enabled()) else {
+            return Ok(false);
+        };
+        if let Some(step) = totp::verify(secret, code, Utc::now()) {
+            return self.repo.use_totp_step(user.id, step).await;
+        }
+
+        let recovery_code = totp::normalize_recovery_code(code);
+        if recovery_code.is_empty() {
+            return Ok(false);
+        }
+        self.tokens
+            .use_recovery_code(user.id, &hash_token(&recovery_code))
+            .await
+    }
+
+    async fn new_recovery_codes(&self, user_id: Uuid) -> Result<Vec<String>, DomainError> {
+        let codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+            .map(|_| totp::generate_recovery_code())
+            .collect();
+        let hashes: Vec<String> = codes
+            .iter()
+            .map(|code| hash_token(&totp::normalize_recovery_code(code)))
+            .collect();
+        self.tokens.replace_recovery_codes(user_id, &hashes).await?;
+        Ok(codes)
+    }
+
+    /// Открывает новую сессию; её id служит семьёй refresh-токенов
+    pub async fn issue_tokens(
+        &self,
+        user_id: Uuid,
+        client: &ClientInfo,
+    ) -> Result<TokenPair, DomainError> {
+        let session_id = Uuid::new_v4();
+        self.tokens
+            .create_session(session_id, user_id, client)
+            .await?;
+        self.token_pair(user_id, session_id, None).await
+    }
+
+    /// Начинает вход через провайдера: запоминает state, nonce и PKCE-verifier
+    /// и возвращает адрес страницы входа провайдера
+    #[instrument(skip_all)]
+    pub async fn oidc_authorization_url(&self) -> Result<String, DomainError> {
+        let oidc = self.oidc()?;
+
+        let state = generate_opaque_token();
+        let login = OidcLogin {
+            state_hash: hash_token(&state),
+            nonce: generate_opaque_token(),
+            code_verifier: generate_opaque_token(),
+            expires_at: Utc::now() + Duration::minutes(OIDC_LOGIN_TTL_MINS),
+        };
+        let url = oidc
+            .authorization_url(&state, &login.nonce, &login.code_verifier)
+            .await
+            .map_err(|e| {
+                error!("oidc discovery failed: {:#}", e);
+                DomainError::Internal("identity provider is unavailable".to_string())
+            })?;
+
+        self.tokens.create_oidc_login(&login).await?;
+        Ok(url)
+    }
+
+    /// Завершает вход через провайдера и выдаёт обычные токены блога.
+    /// Второй фактор блога здесь не спрашивается — за него отвечает провайдер
+    #[instrument(skip_all)]
+    pub async fn oidc_login(
+        &self,
+        code: &str,
+        state: &str,
+        client: &ClientInfo,
+    ) -> Result<TokenPair, DomainError> {
+        let oidc = self.oidc()?;
+
+        let login = self
+            .tokens
+            .take_oidc_login(&hash_token(state))
+            .await?
+            .ok_or_else(invalid_link)?;
+        let identity = oidc
+            .exchange_code(code, &login.code_verifier, &login.nonce)
+            .await
+            .map_err(|e| {
+                warn!(event = "oidc_login_failed", error = %format!("{:#}", e), "oidc login rejected");
+                DomainError::Unauthorized
+            })?;
+
+        let user = self.user_for_identity(&identity, client).await?;
+        info!(user_id = %user.id, issuer = %identity.issuer, "user logged in via oidc");
+
+        let tokens = self.issue_tokens(user.id, client).await?;
+        self.audit_login(&user, "oidc", client).await;
+        Ok(tokens)
+    }
+
+    fn oidc(&self) -> Result<&OidcClient, DomainError> {
+        self.oidc
+            .as_deref()
+            .ok_or_else(|| DomainError::Validation("single sign-on is not configured".to_string()))
+    }
+
+    /// Пользователь, уже привязанный к `subject`; иначе привязывается аккаунт с тем же
+    /// адресом или заводится новый
+    async fn user_for_identity(
+        &self,
+        identity: &ExternalIdentity,
+        client: &ClientInfo,
+    ) -> Result<User, DomainError> {
+        if let Some(user) = self
+            .repo
+            .find_by_identity(&identity.issuer, &identity.subject)
+            .await?
+        {
+            return Ok(user);
+        }
+
+        let email = identity
+            .email
+            .as_deref()
+            .map(|email| email.trim().to_lowercase())
+            .filter(|email| !email.is_empty())
+            .ok_or_else(|| {
+                DomainError::Validation(
+                    "identity provider did not share an email address".to_string(),
+                )
+            })?;
+
+        let user = match self.repo.find_by_email(&email).await? {
+            // Иначе по совпадению неподтверждённого адреса можно было бы войти в чужой аккаунт
+            Some(user) if identity.email_verified && user.email_verified_at.is_some() => user,
+            Some(_) => {
+                return Err(DomainError::UserAlreadyExists(
+                    "an account with this email already exists, verify the email to link it"
+                        .to_string(),
+                ));
+            }
+            None => self.provision_user(identity, &email, client).await?,
+        };
+
+        self.repo
+            .link_identity(user.id, &identity.issuer, &identity.subject)
+            .await?;
+        Ok(user)
+    }
+
+    async fn provision_user(
+        &self,
+        identity: &ExternalIdentity,
+        email: &str,
+        client: &ClientInfo,
+    ) -> Result<User, DomainError> {
+        let username = self.free_username(identity, email).await?;
+        // Пароля у такого пользователя нет; при желании его можно задать через сброс пароля
+        let hash = hash_password(&generate_opaque_token())
+            .map_err(|err| DomainError::Internal(err.to_string()))?;
+
+        let user = self
+            .repo
+            .create(User::new(username, email.to_string(), hash))
+            .await?;
+        if identity.email_verified {
+            self.repo.mark_email_verified(user.id, email).await?;
+        }
+
+        info!(user_id = %user.id, issuer = %identity.issuer, "user provisioned via oidc");
+        self.audit
+            .record(
+                AuditEvent::new(AuditAction::UserRegister, Some(user.id), Some(user.id)).after(
+                    json!({ "username": user.username, "email": user.email, "issuer": identity.issuer }),
+                ),
+                client,
+            )
+            .await;
+        Ok(user)
+    }
+
+    /// Имя из `preferred_username` или адреса; занятое или слишком короткое
+    /// дополняется случайным суффиксом
+    async fn free_username(
+        &self,
+        identity: &ExternalIdentity,
+        email: &str,
+    ) -> Result<String, DomainError> {
+        let base: String = identity
+            .preferred_username
+            .as_deref()
+            .unwrap_or_else(|| email.split('@').next().unwrap_or_default())
+            .to_lowercase()
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+            .collect();
+        let base = if base.is_empty() {
+            "user".to_string()
+        } else {
+            base
+        };
+
+        if base.chars().count() >= MIN_USERNAME_LENGTH
+            && self.repo.find_by_username(&base).await?.is_none()
+        {
+            return Ok(base);
+        }
+        for _ in 0..5 {
+            let candidate = format!("{base}-{:04x}", OsRng.next_u32() & 0xffff);
+            if self.repo.find_by_username(&candidate).await?.is_none() {
+                return Ok(candidate);
+            }
+        }
+        Err(DomainError::Internal(
+            "could not pick a free username".to_string(),
+        ))
+    }
+
+    /// Ротация: старый refresh-токен погашается, взамен выдаётся новый из той же семьи.
+    /// Повторное предъявление погашенного токена означает утечку — закрываем всю сессию.
+    #[instrument(skip_all)]
+    pub async fn refresh(
+        &self,
+        refresh_token: &str,
+        client: &ClientInfo,
+    ) -> Result<TokenPair, DomainError> {
+        let stored = self
+            .tokens
+            .find_refresh_token(&hash_token(refresh_token))
+            .await?
+            .ok_or(DomainError::Unauthorized)?;
+
+        if stored.revoked_at.is_some() || stored.is_expired() {
             return Err(DomainError::Unauthorized);
         }
+        if stored.used_at.is_some() {
+            return Err(self.reuse_detected(&stored).await);
+        }
+
+        let tokens = self
+            .token_pair(stored.user_id, stored.family_id, Some(&stored))
+            .await?;
+        self.tokens
+            .touch_session(stored.family_id, Some(client))
+            .await?;
+        Ok(tokens)
+    }
+
+    async fn reuse_detected(&self, stored: &RefreshToken) -> DomainError {
+        warn!(
+            user_id = %stored.user_id,
+            family_id = %stored.family_id,
+            "refresh token reuse detected, revoking session"
+        );
+        match self
+            .tokens
+            .revoke_session(stored.family_id, stored.user_id)
+            .await
+        {
+            Ok(_) => DomainError::Unauthorized,
+            Err(e) => e,
+        }
+    }
+
+    async fn token_pair(
+        &self,
+        user_id: Uuid,
+        family_id: Uuid,
+        rotated: Option<&RefreshToken>,
+    ) -> Result<TokenPair, DomainError> {
+        let user = self
+            .repo
+            .find_by_id(user_id)
+            .await?
+            .ok_or(DomainError::Unauthorized)?;
+        let access_token = self
+            .keys
+            .generate_token(user_id, user.role, family_id)
+            .map_err(|err| DomainError::Internal(err.to_string()))?;
+
+        let refresh_token = generate_opaque_token();
+        let next = RefreshToken::new(
+            user_id,
+            family_id,
+            hash_token(&refresh_token),
+            Utc::now() + self.refresh_ttl,
+        );
+        match rotated {
+            Some(used) => {
+                // Токен успели погасить параллельным запросом — тоже повтор
+                if !self.tokens.rotate_refresh_token(used.id, next).await? {
+                    return Err(self.reuse_detected(used).await);
+                }
+            }
+            None => self.tokens.create_refresh_token(next).await?,
+        }
+
+        Ok(TokenPair {
+            access_token,
+            refresh_token,
+            expires_in: self.keys.access_ttl(),
+        })
+    }
+}
+
+/// Счётчики неудачных входов: по имени пользователя и, если известен, по IP
+fn login_throttles(username: &str, client: &ClientInfo) -> Vec<(String, i32)> {
+    let mut throttles = vec![(format!("user:{username}"), MAX_USERNAME_FAILURES)];
+    if let Some(ip) = &client.ip {
+        throttles.push((format!("ip:{ip}"), MAX_IP_FAILURES));
+    }
+    throttles
+}
+
+/// 30 с на пороге, дальше вдвое больше за каждую ошибку, но не дольше 15 минут
+fn login_lockout(failures: i32, max_failures: i32) -> Option<Duration> {
+    if failures < max_failures {
+        return None;
+    }
+    let secs = LOGIN_LOCKOUT_BASE_SECS << (failures - max_failures).min(10);
+    Some(Duration::seconds(secs.min(LOGIN_LOCKOUT_MAX_SECS)))
+}
+
+fn dummy_password_hash() -> &'static str {
+    static HASH: OnceLock<String> = OnceLock::new();
+    HASH.get_or_init(|| hash_password("dummy password").expect("failed to hash dummy password"))
+}
+
+fn validate_password(password: &str) -> Result<(), DomainError> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(DomainError::Validation(format!(
+            "password must be at least {MIN_PASSWORD_LENGTH} characters"
+        )));
+    }
+    Ok(())
+}
+
+fn invalid_link() -> DomainError {
+    DomainError::Validation("invalid or expired link".to_string())
+}
+
+fn invalid_code() -> DomainError {
+    DomainError::Validation("invalid two-factor code".to_string())
+}
+
+/// Разобранные claims access-токена
+struct AccessToken {
+    user_id: Uuid,
+    session_id: Uuid,
+    jti: Uuid,
+    issued_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+}
+
+impl TryFrom<Claims> for AccessToken {
+    type Error = DomainError;
+
+    fn try_from(claims: Claims) -> Result<Self, Self::Error> {
+        let uuid = |value: &str| Uuid::parse_str(value).map_err(|_| DomainError::Unauthorized);
+        let timestamp =
+            |secs: usize| DateTime::from_timestamp(secs as i64, 0).ok_or(DomainError::Unauthorized);
 
-        self.keys
-            .generate_token(user.id)
-            .map_err(|err| DomainError::Internal(err.to_string()))
+        Ok(Self {
+            user_id: uuid(&claims.sub)?,
+            session_id: uuid(&claims.sid)?,
+            jti: uuid(&claims.jti)?,
+            issued_at: timestamp(claims.iat)?,
+            expires_at: timestamp(claims.exp)?,
+        })
     }
 }
@@ -0,0 +1,132 @@
+use std::sync::Arc;
+
+use crate::application::policy::Actor;
+use crate::data::comment_repository::CommentRepository;
+use crate::data::post_repository::PostRepository;
+use crate::domain::{
+    comment::{Comment, MAX_COMMENT_LENGTH},
+    error::DomainError,
+    post::Post,
+};
+use crate::presentation::dto::CreateCommentRequest;
+use tracing::instrument;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct CommentService<C: CommentRepository + 'static, P: PostRepository + 'static> {
+    comments: Arc<C>,
+    posts: Arc<P>,
+}
+
+impl<C, P> CommentService<C, P>
+where
+    C: CommentRepository + 'static,
+    P: PostRepository + 'static,
+{
+    pub fn new(comments: Arc<C>, posts: Arc<P>) -> Self {
+        Self { comments, posts }
+    }
+
+    /// Комментировать и читать комментарии можно только у видимых пользователю постов
+    async fn visible_post(&self, post_id: Uuid, viewer: Option<Uuid>) -> Result<Post, DomainError> {
+        self.posts
+            .find_by_id(post_id)
+            .await?
+            .filter(|post| post.is_visible_to(viewer))
+            .ok_or(DomainError::PostNotFound(post_id))
+    }
+
+    pub async fn get_comments(
+        &self,
+        post_id: Uuid,
+        viewer: Option<Uuid>,
+    ) -> Result<Vec<Comment>, DomainError> {
+        self.visible_post(post_id, viewer).await?;
+        let mut comments = self.comments.get_by_post(post_id).await?;
+
+        // Скрытый комментарий остаётся в дереве, чтобы не рвать ветку ответов, но без текста
+        for comment in comments.iter_mut() {
+            if comment.hidden_at.is_some() && viewer != Some(comment.author_id) {
+                comment.content.clear();
+            }
+        }
+        Ok(comments)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn create_comment(
+        &self,
+        author_id: Uuid,
+        post_id: Uuid,
+        create: CreateCommentRequest,
+    ) -> Result<Comment, DomainError> {
+        self.visible_post(post_id, Some(author_id)).await?;
+
+        let content = create.content.trim().to_string();
+        if content.is_empty() {
+            return Err(DomainError::Validation(
+                "comment must not be empty".to_string(),
+            ));
+        }
+        if content.chars().count() > MAX_COMMENT_LENGTH {
+            return Err(DomainError::Validation(format!(
+                "comment must be at most {MAX_COMMENT_LENGTH} characters"
+            )));
+        }
+
+        // Отвечать можно только на комментарии того же поста
+        if let Some(parent_id) = create.parent_id {
+            self.find_comment(post_id, parent_id).await?;
+        }
+
+        let comment = Comment::new(post_id, author_id, create.parent_id, content);
+        self.comments.create(comment).await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn delete_comment(
+        &self,
+        actor: Actor,
+        post_id: Uuid,
+        comment_id: Uuid,
+    ) -> Result<(), DomainError> {
+        let post = self.visible_post(post_id, Some(actor.id)).await?;
+        let comment = self.find_comment(post_id, comment_id).await?;
+
+        if !actor.can_delete_comment(&post, &comment) {
+            return Err(DomainError::Forbidden);
+        }
+
+        self.comments.delete(comment_id).await
+    }
+
+    /// Скрывает комментарий (или возвращает); доступно модераторам и администраторам
+    #[instrument(skip(self))]
+    pub async fn set_hidden(
+        &self,
+        actor: Actor,
+        post_id: Uuid,
+        comment_id: Uuid,
+        hidden: bool,
+    ) -> Result<Comment, DomainError> {
+        self.visible_post(post_id, Some(actor.id)).await?;
+        self.find_comment(post_id, comment_id).await?;
+
+        if !actor.can_moderate() {
+            return Err(DomainError::Forbidden);
+        }
+
+        self.comments
+            .set_hidden(comment_id, hidden)
+            .await?
+            .ok_or(DomainError::CommentNotFound(comment_id))
+    }
+
+    async fn find_comment(&self, post_id: Uuid, comment_id: Uuid) -> Result<Comment, DomainError> {
+        self.comments
+            .find_by_id(comment_id)
+            .await?
+            .filter(|comment| comment.post_id == post_id)
+            .ok_or(DomainError::CommentNotFound(comment_id))
+    }
+}
@@ -1,2 +1,7 @@
+pub mod audit_log;
 pub mod auth_service;
+pub mod comment_service;
+pub mod outbox_service;
+pub mod policy;
 pub mod post_service;
+pub mod rate_limiter;
@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+use tracing::warn;
+
+use crate::data::outbox_repository::OutboxRepository;
+use crate::domain::error::DomainError;
+use crate::infrastructure::mailer::Mailer;
+
+const DELIVERY_BATCH_SIZE: i64 = 50;
+/// После стольких неудач письмо остаётся в outbox с last_error и больше не отправляется
+const MAX_DELIVERY_ATTEMPTS: i32 = 10;
+const MAX_RETRY_DELAY_SECS: i64 = 3600;
+
+pub struct OutboxService<O: OutboxRepository + 'static> {
+    repo: Arc<O>,
+    mailer: Box<dyn Mailer>,
+}
+
+impl<O> OutboxService<O>
+where
+    O: OutboxRepository + 'static,
+{
+    pub fn new(repo: Arc<O>, mailer: Box<dyn Mailer>) -> Self {
+        Self { repo, mailer }
+    }
+
+    /// Отправляет накопившиеся письма. Возвращает число отправленных.
+    pub async fn deliver_pending(&self) -> Result<usize, DomainError> {
+        let mut delivered = 0;
+        loop {
+            let batch = self
+                .repo
+                .claim_pending(DELIVERY_BATCH_SIZE, MAX_DELIVERY_ATTEMPTS)
+                .await?;
+            let batch_len = batch.len();
+
+            for message in batch {
+                match self.mailer.send(&message.email).await {
+                    Ok(()) => {
+                        self.repo.mark_sent(message.id).await?;
+                        delivered += 1;
+                    }
+                    Err(e) => {
+                        warn!(
+                            message_id = %message.id,
+                            attempts = message.attempts,
+                            "email delivery failed: {}",
+                            e
+                        );
+                        let retry_at = Utc::now() + retry_delay(message.attempts);
+                        self.repo
+                            .mark_failed(message.id, &e.to_string(), retry_at)
+                            .await?;
+                    }
+                }
+            }
+
+            if (batch_len as i64) < DELIVERY_BATCH_SIZE {
+                return Ok(delivered);
+            }
+        }
+    }
+}
+
+/// Экспоненциальная пауза: 30 с, 1 мин, 2 мин… но не больше часа
+fn retry_delay(attempts: i32) -> Duration {
+    let secs = 30 * (1i64 << (attempts.clamp(1, 20) - 1));
+    Duration::seconds(secs.min(MAX_RETRY_DELAY_SECS))
+}
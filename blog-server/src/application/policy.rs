@@ -0,0 +1,36 @@
+use crate::domain::comment::Comment;
+use crate::domain::post::Post;
+use crate::domain::user::Role;
+use uuid::Uuid;
+
+/// Тот, кто выполняет действие; права определяются ролью и авторством
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Actor {
+    pub id: Uuid,
+    pub role: Role,
+}
+
+impl Actor {
+    pub fn new(id: Uuid, role: Role) -> Self {
+        Self { id, role }
+    }
+
+    pub fn is_admin(&self) -> bool {
+        self.role == Role::Admin
+    }
+
+    /// Править, публиковать и удалять пост может его автор или администратор
+    pub fn can_edit_post(&self, post: &Post) -> bool {
+        post.author_id == self.id || self.is_admin()
+    }
+
+    /// Скрывать посты и комментарии могут модераторы и администраторы
+    pub fn can_moderate(&self) -> bool {
+        matches!(self.role, Role::Moderator | Role::Admin)
+    }
+
+    /// Удалить комментарий может его автор, автор поста или администратор
+    pub fn can_delete_comment(&self, post: &Post, comment: &Comment) -> bool {
+        comment.author_id == self.id || post.author_id == self.id || self.is_admin()
+    }
+}
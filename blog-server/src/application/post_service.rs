@@ -1,39 +1,194 @@
 use std::sync::Arc;
 
-use crate::blog::DeletePostRequest;
+use crate::application::audit_log::AuditLog;
+use crate::application::policy::Actor;
 use crate::data::post_repository::PostRepository;
-use crate::domain::{error::DomainError, post::Post};
+use crate::domain::{
+    audit::{AuditAction, AuditEvent},
+    error::DomainError,
+    post::{Post, PostFilter, PostPage, PostStatus},
+    reaction::ReactionKind,
+    revision::{PostRevision, RevisionDiff, unified_diff},
+    search::{MAX_QUERY_LENGTH, SearchResult},
+    session::ClientInfo,
+    slug::SlugLookup,
+    tag::{TagCount, normalize_tags},
+};
+use crate::infrastructure::cursor::CursorCodec;
 use crate::presentation::dto::{CreatePostRequest, UpdatePostRequest};
+use chrono::{DateTime, Duration, Utc};
 use tracing::instrument;
 use uuid::Uuid;
 
+const PUBLISH_BATCH_SIZE: i64 = 100;
+const PURGE_BATCH_SIZE: i64 = 100;
+const DEFAULT_PAGE_SIZE: usize = 10;
+const MAX_PAGE_SIZE: usize = 100;
+
 #[derive(Clone)]
 pub struct PostService<R: PostRepository + 'static> {
     repo: Arc<R>,
+    cursors: CursorCodec,
+    audit: Arc<AuditLog>,
 }
 
 impl<R> PostService<R>
 where
     R: PostRepository + 'static,
 {
-    pub fn new(repo: Arc<R>) -> Self {
-        Self { repo }
+    pub fn new(repo: Arc<R>, cursors: CursorCodec, audit: Arc<AuditLog>) -> Self {
+        Self {
+            repo,
+            cursors,
+            audit,
+        }
     }
 
-    pub async fn get_post(&self, id: Uuid) -> Result<Post, DomainError> {
-        self.repo
+    pub async fn get_post(&self, id: Uuid, viewer: Option<Uuid>) -> Result<Post, DomainError> {
+        let mut post = self
+            .repo
             .find_by_id(id)
             .await?
-            .ok_or(DomainError::PostNotFound(id))
+            .filter(|post| post.is_visible_to(viewer))
+            .ok_or(DomainError::PostNotFound(id))?;
+        self.attach_reactions([&mut post], viewer).await?;
+        Ok(post)
+    }
+
+    pub async fn get_post_by_slug(
+        &self,
+        slug: &str,
+        viewer: Option<Uuid>,
+    ) -> Result<SlugLookup, DomainError> {
+        let mut post = self
+            .repo
+            .find_by_slug(slug)
+            .await?
+            .filter(|post| post.is_visible_to(viewer))
+            .ok_or_else(|| DomainError::SlugNotFound(slug.to_string()))?;
+        self.attach_reactions([&mut post], viewer).await?;
+
+        if post.slug == slug {
+            Ok(SlugLookup::Found(post))
+        } else {
+            Ok(SlugLookup::Moved(post))
+        }
     }
 
+    /// Лента постов: по `page_token` — keyset-пагинация, без него — прежний режим с offset
     pub async fn get_posts(
         &self,
+        viewer: Option<Uuid>,
+        mut filter: PostFilter,
         limit: Option<usize>,
         offset: Option<usize>,
-    ) -> Result<Vec<Post>, DomainError> {
-        let posts = self.repo.get_posts(limit, offset).await?;
-        Ok(posts)
+        page_token: Option<&str>,
+    ) -> Result<PostPage, DomainError> {
+        filter.tag = filter
+            .tag
+            .map(|tag| tag.trim().to_lowercase())
+            .filter(|tag| !tag.is_empty());
+        filter.title_contains = filter
+            .title_contains
+            .map(|title| title.trim().to_string())
+            .filter(|title| !title.is_empty());
+        if let (Some(after), Some(before)) = (filter.created_after, filter.created_before)
+            && after >= before
+        {
+            return Err(DomainError::Validation(
+                "created_after must be earlier than created_before".to_string(),
+            ));
+        }
+
+        let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+        let after = page_token
+            .filter(|token| !token.is_empty())
+            .map(|token| self.cursors.decode(token))
+            .transpose()?;
+
+        let mut posts = self
+            .repo
+            .get_posts(viewer, &filter, Some(limit), offset, after)
+            .await?;
+        let total = self.repo.count_posts(viewer, &filter).await?;
+        self.attach_reactions(posts.iter_mut(), viewer).await?;
+
+        // Полная страница — возможно, есть следующая
+        let next_page_token = match posts.last() {
+            Some(last) if posts.len() == limit => Some(self.cursors.encode(&last.cursor())),
+            _ => None,
+        };
+
+        Ok(PostPage {
+            posts,
+            next_page_token,
+            total,
+        })
+    }
+
+    pub async fn search_posts(
+        &self,
+        viewer: Option<Uuid>,
+        query: &str,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> Result<Vec<SearchResult>, DomainError> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Err(DomainError::Validation(
+                "search query must not be empty".to_string(),
+            ));
+        }
+        if query.chars().count() > MAX_QUERY_LENGTH {
+            return Err(DomainError::Validation(format!(
+                "search query must be at most {MAX_QUERY_LENGTH} characters"
+            )));
+        }
+
+        let mut results = self.repo.search(viewer, query, limit, offset).await?;
+        self.attach_reactions(results.iter_mut().map(|hit| &mut hit.post), viewer)
+            .await?;
+        Ok(results)
+    }
+
+    /// Проставляет счётчики реакций и реакции читателя одним запросом на все посты
+    async fn attach_reactions<'a>(
+        &self,
+        posts: impl IntoIterator<Item = &'a mut Post>,
+        viewer: Option<Uuid>,
+    ) -> Result<(), DomainError> {
+        let mut posts: Vec<&mut Post> = posts.into_iter().collect();
+        let ids: Vec<Uuid> = posts.iter().map(|post| post.id).collect();
+        let counts = self.repo.get_reaction_counts(&ids, viewer).await?;
+
+        for count in counts {
+            if let Some(post) = posts.iter_mut().find(|post| post.id == count.post_id) {
+                post.reactions.insert(count.kind, count.count);
+                if count.mine {
+                    post.my_reactions.push(count.kind);
+                }
+            }
+        }
+        for post in posts.iter_mut() {
+            post.my_reactions.sort();
+        }
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    pub async fn toggle_reaction(
+        &self,
+        user_id: Uuid,
+        post_id: Uuid,
+        kind: ReactionKind,
+    ) -> Result<Post, DomainError> {
+        self.get_post(post_id, Some(user_id)).await?;
+        self.repo.toggle_reaction(post_id, user_id, kind).await?;
+        self.get_post(post_id, Some(user_id)).await
+    }
+
+    pub async fn get_tags(&self) -> Result<Vec<TagCount>, DomainError> {
+        self.repo.get_tags().await
     }
 
     #[instrument(skip(self))]
@@ -41,31 +196,291 @@ where
         &self,
         author_id: Uuid,
         create: CreatePostRequest,
+        client: &ClientInfo,
     ) -> Result<Post, DomainError> {
-        let post = Post::new(author_id, create.title, create.content);
-        self.repo.create(post).await
+        let status = match create.publish_at {
+            Some(at) => {
+                ensure_future(at)?;
+                PostStatus::Scheduled
+            }
+            None if create.status == Some(PostStatus::Scheduled) => {
+                return Err(DomainError::Validation(
+                    "scheduled posts require publish_at".to_string(),
+                ));
+            }
+            None => create.status.unwrap_or(PostStatus::Draft),
+        };
+        let post = Post::new(
+            author_id,
+            create.title,
+            create.content,
+            status,
+            create.publish_at,
+            normalize_tags(create.tags)?,
+        );
+        let post = self.repo.create(post).await?;
+        self.audit
+            .record(
+                AuditEvent::new(AuditAction::PostCreate, Some(author_id), Some(post.id))
+                    .after(&post),
+                client,
+            )
+            .await;
+        Ok(post)
+    }
+
+    /// Пост, который `actor` вправе менять. Чужой видимый пост — `Forbidden`,
+    /// невидимый — `PostNotFound`, чтобы не раскрывать чужие черновики
+    async fn editable_post(&self, actor: Actor, post_id: Uuid) -> Result<Post, DomainError> {
+        let post = self
+            .repo
+            .find_by_id(post_id)
+            .await?
+            .ok_or(DomainError::PostNotFound(post_id))?;
+        if actor.can_edit_post(&post) {
+            Ok(post)
+        } else {
+            Err(denied(actor, &post))
+        }
     }
 
     #[instrument(skip(self))]
     pub async fn update_post(
         &self,
-        author_id: Uuid,
+        actor: Actor,
         post_id: Uuid,
-        update: UpdatePostRequest,
+        mut update: UpdatePostRequest,
+        client: &ClientInfo,
     ) -> Result<Post, DomainError> {
-        match self.repo.update_post(author_id, post_id, update).await {
-            Ok(Some(post)) => Ok(post),
-            Ok(None) => Err(DomainError::PostNotFound(post_id)),
-            Err(e) => Err(e),
+        if let Some(at) = update.publish_at {
+            ensure_future(at)?;
         }
+        update.tags = update.tags.map(normalize_tags).transpose()?;
+        let before = self.editable_post(actor, post_id).await?;
+        let post = self
+            .repo
+            .update_post(post_id, update)
+            .await?
+            .ok_or(DomainError::PostNotFound(post_id))?;
+        self.audit
+            .record(
+                AuditEvent::new(AuditAction::PostUpdate, Some(actor.id), Some(post_id))
+                    .before(&before)
+                    .after(&post),
+                client,
+            )
+            .await;
+        Ok(post)
     }
 
     #[instrument(skip(self))]
     pub async fn delete_post(
         &self,
-        author_id: Uuid,
-        req: DeletePostRequest,
+        actor: Actor,
+        post_id: Uuid,
+        client: &ClientInfo,
     ) -> Result<(), DomainError> {
-        self.repo.delete_post(author_id, req).await
+        let before = self.editable_post(actor, post_id).await?;
+        self.repo.delete_post(post_id).await?;
+        self.audit
+            .record(
+                AuditEvent::new(AuditAction::PostDelete, Some(actor.id), Some(post_id))
+                    .before(&before),
+                client,
+            )
+            .await;
+        Ok(())
+    }
+
+    /// Корзина: свои удалённые посты, которые ещё можно восстановить
+    pub async fn get_trash(&self, actor: Actor) -> Result<Vec<Post>, DomainError> {
+        self.repo.get_trash(actor.id).await
+    }
+
+    /// Возвращает пост из корзины; чужую корзину видит только администратор
+    #[instrument(skip(self, client))]
+    pub async fn restore_post(
+        &self,
+        actor: Actor,
+        post_id: Uuid,
+        client: &ClientInfo,
+    ) -> Result<Post, DomainError> {
+        let post = self
+            .repo
+            .find_deleted(post_id)
+            .await?
+            .filter(|post| actor.can_edit_post(post))
+            .ok_or(DomainError::PostNotFound(post_id))?;
+        let restored = self
+            .repo
+            .restore_post(post_id)
+            .await?
+            .ok_or(DomainError::PostNotFound(post_id))?;
+        self.audit
+            .record(
+                AuditEvent::new(AuditAction::PostRestore, Some(actor.id), Some(post_id))
+                    .before(&post)
+                    .after(&restored),
+                client,
+            )
+            .await;
+        Ok(restored)
+    }
+
+    /// Окончательно удаляет посты, пролежавшие в корзине дольше `retention`. Возвращает их число.
+    pub async fn purge_deleted_posts(&self, retention: Duration) -> Result<usize, DomainError> {
+        let mut total = 0;
+        loop {
+            let purged = self.repo.purge_deleted(retention, PURGE_BATCH_SIZE).await?;
+            total += purged.len();
+            if (purged.len() as i64) < PURGE_BATCH_SIZE {
+                return Ok(total);
+            }
+        }
+    }
+
+    /// Публикует отложенные посты, время которых наступило. Возвращает число опубликованных.
+    pub async fn publish_due_posts(&self) -> Result<usize, DomainError> {
+        let mut total = 0;
+        loop {
+            let published = self.repo.publish_due(PUBLISH_BATCH_SIZE).await?;
+            total += published.len();
+            if (published.len() as i64) < PUBLISH_BATCH_SIZE {
+                return Ok(total);
+            }
+        }
+    }
+
+    #[instrument(skip(self))]
+    pub async fn change_status(
+        &self,
+        actor: Actor,
+        post_id: Uuid,
+        status: PostStatus,
+    ) -> Result<Post, DomainError> {
+        self.editable_post(actor, post_id).await?;
+        self.repo
+            .set_status(post_id, status)
+            .await?
+            .ok_or(DomainError::PostNotFound(post_id))
+    }
+
+    /// Скрывает пост из выдачи (или возвращает); доступно модераторам и администраторам
+    #[instrument(skip(self))]
+    pub async fn set_hidden(
+        &self,
+        actor: Actor,
+        post_id: Uuid,
+        hidden: bool,
+    ) -> Result<Post, DomainError> {
+        let post = self
+            .repo
+            .find_by_id(post_id)
+            .await?
+            .ok_or(DomainError::PostNotFound(post_id))?;
+        if !actor.can_moderate() {
+            return Err(denied(actor, &post));
+        }
+        self.repo
+            .set_hidden(post_id, hidden)
+            .await?
+            .ok_or(DomainError::PostNotFound(post_id))
+    }
+
+    pub async fn get_revisions(
+        &self,
+        post_id: Uuid,
+        viewer: Option<Uuid>,
+    ) -> Result<Vec<PostRevision>, DomainError> {
+        self.get_post(post_id, viewer).await?;
+        self.repo.get_revisions(post_id).await
+    }
+
+    pub async fn get_revision(
+        &self,
+        post_id: Uuid,
+        revision: i32,
+        viewer: Option<Uuid>,
+    ) -> Result<PostRevision, DomainError> {
+        self.get_post(post_id, viewer).await?;
+        self.repo
+            .find_revision(post_id, revision)
+            .await?
+            .ok_or(DomainError::RevisionNotFound(post_id, revision))
+    }
+
+    /// Diff между ревизиями `from` и `to`; без `to` — с текущей версией поста
+    pub async fn diff_revisions(
+        &self,
+        post_id: Uuid,
+        from: i32,
+        to: Option<i32>,
+        viewer: Option<Uuid>,
+    ) -> Result<RevisionDiff, DomainError> {
+        let post = self.get_post(post_id, viewer).await?;
+        let old = self.get_revision(post_id, from, viewer).await?;
+
+        let diff = match to {
+            Some(to) => {
+                let new = self.get_revision(post_id, to, viewer).await?;
+                unified_diff(
+                    (&old.title, &old.content),
+                    (&new.title, &new.content),
+                    &format!("revision {from}"),
+                    &format!("revision {to}"),
+                )
+            }
+            None => unified_diff(
+                (&old.title, &old.content),
+                (&post.title, &post.content),
+                &format!("revision {from}"),
+                "current",
+            ),
+        };
+
+        Ok(RevisionDiff { from, to, diff })
+    }
+
+    /// Возвращает пост к ревизии; текущая версия при этом сама становится новой ревизией
+    #[instrument(skip(self))]
+    pub async fn restore_revision(
+        &self,
+        actor: Actor,
+        post_id: Uuid,
+        revision: i32,
+        client: &ClientInfo,
+    ) -> Result<Post, DomainError> {
+        self.editable_post(actor, post_id).await?;
+        let revision = self
+            .repo
+            .find_revision(post_id, revision)
+            .await?
+            .ok_or(DomainError::RevisionNotFound(post_id, revision))?;
+        let update = UpdatePostRequest {
+            title: Some(revision.title),
+            content: Some(revision.content),
+            publish_at: None,
+            tags: None,
+            expected_version: None,
+        };
+        self.update_post(actor, post_id, update, client).await
+    }
+}
+
+/// Отказ в действии над постом: существование чужого невидимого поста не раскрываем
+fn denied(actor: Actor, post: &Post) -> DomainError {
+    if post.is_visible_to(Some(actor.id)) {
+        DomainError::Forbidden
+    } else {
+        DomainError::PostNotFound(post.id)
+    }
+}
+
+fn ensure_future(at: DateTime<Utc>) -> Result<(), DomainError> {
+    if at <= Utc::now() {
+        return Err(DomainError::Validation(
+            "publish_at must be in the future".to_string(),
+        ));
     }
+    Ok(())
 }
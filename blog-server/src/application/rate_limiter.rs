@@ -0,0 +1,130 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::domain::error::DomainError;
+use crate::infrastructure::rate_limit::RateLimitStore;
+use crate::infrastructure::security::JwtKeys;
+
+/// Раз в столько проверок из хранилища убираются заброшенные вёдра
+const PURGE_EVERY_CHECKS: u64 = 10_000;
+/// Бюджет пополняется за минуту, так что через 10 минут простоя ведро точно полное
+const IDLE_BUCKET_TTL: Duration = Duration::from_secs(600);
+
+/// Группа запросов со своим бюджетом
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateClass {
+    /// Вход, регистрация, обновление токенов и прочее, что стоит подбирать перебором
+    Auth,
+    Write,
+    Read,
+}
+
+impl RateClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RateClass::Auth => "auth",
+            RateClass::Write => "write",
+            RateClass::Read => "read",
+        }
+    }
+}
+
+/// Чей бюджет расходует запрос
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RateSubject {
+    User(Uuid),
+    Ip(String),
+}
+
+impl fmt::Display for RateSubject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RateSubject::User(id) => write!(f, "user:{id}"),
+            RateSubject::Ip(ip) => write!(f, "ip:{ip}"),
+        }
+    }
+}
+
+pub struct RateLimiter {
+    store: Box<dyn RateLimitStore>,
+    keys: JwtKeys,
+    auth_per_min: u32,
+    write_per_min: u32,
+    read_per_min: u32,
+    checks: AtomicU64,
+}
+
+impl RateLimiter {
+    pub fn new(
+        store: Box<dyn RateLimitStore>,
+        keys: JwtKeys,
+        auth_per_min: u32,
+        write_per_min: u32,
+        read_per_min: u32,
+    ) -> Self {
+        Self {
+            store,
+            keys,
+            auth_per_min,
+            write_per_min,
+            read_per_min,
+            checks: AtomicU64::new(0),
+        }
+    }
+
+    /// Пользователь из access-токена, иначе адрес клиента. Проверяется только подпись и срок —
+    /// без похода в базу; с чужим или поддельным токеном запрос считается по IP.
+    /// Персональные токены без базы не проверить, поэтому они тоже идут по IP.
+    pub fn subject(&self, bearer: Option<&str>, ip: Option<String>) -> RateSubject {
+        bearer
+            .and_then(|token| self.keys.verify_token(token).ok())
+            .and_then(|claims| claims.sub.parse().ok())
+            .map(RateSubject::User)
+            .unwrap_or_else(|| RateSubject::Ip(ip.unwrap_or_else(|| "unknown".into())))
+    }
+
+    /// Списывает запрос из бюджета; `TooManyRequests` с временем ожидания, если он исчерпан.
+    /// Недоступное хранилище не должно класть сервис — тогда запрос пропускается.
+    pub async fn check(&self, class: RateClass, subject: &RateSubject) -> Result<(), DomainError> {
+        let per_minute = match class {
+            RateClass::Auth => self.auth_per_min,
+            RateClass::Write => self.write_per_min,
+            RateClass::Read => self.read_per_min,
+        };
+        if per_minute == 0 {
+            return Ok(());
+        }
+
+        if self
+            .checks
+            .fetch_add(1, Ordering::Relaxed)
+            .is_multiple_of(PURGE_EVERY_CHECKS)
+            && let Err(e) = self.store.purge_idle(IDLE_BUCKET_TTL).await
+        {
+            error!("rate limit purge failed: {}", e);
+        }
+
+        let key = format!("{}:{}", class.as_str(), subject);
+        match self.store.take(&key, per_minute).await {
+            Ok(None) => Ok(()),
+            Ok(Some(retry_after)) => {
+                warn!(
+                    event = "rate_limited",
+                    class = class.as_str(),
+                    subject = %subject,
+                    retry_after,
+                    "request rate limited"
+                );
+                Err(DomainError::TooManyRequests(retry_after))
+            }
+            Err(e) => {
+                error!("rate limit store unavailable: {}", e);
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use crate::domain::audit::{AuditEntry, AuditEvent, AuditFilter};
+use crate::domain::error::DomainError;
+use crate::domain::session::ClientInfo;
+use async_trait::async_trait;
+use sqlx::PgPool;
+use tracing::error;
+
+#[async_trait]
+pub trait AuditRepository: Send + Sync {
+    async fn append(&self, event: &AuditEvent, client: &ClientInfo) -> Result<(), DomainError>;
+    /// Новые записи первыми
+    async fn find(&self, filter: &AuditFilter, limit: i64) -> Result<Vec<AuditEntry>, DomainError>;
+}
+
+#[derive(Clone)]
+pub struct PostgresAuditRepository {
+    pool: PgPool,
+}
+
+impl PostgresAuditRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+fn db_error(e: sqlx::Error) -> DomainError {
+    error!("audit repository error: {}", e);
+    DomainError::Internal(format!("database error: {}", e))
+}
+
+#[async_trait]
+impl AuditRepository for PostgresAuditRepository {
+    async fn append(&self, event: &AuditEvent, client: &ClientInfo) -> Result<(), DomainError> {
+        sqlx::query(
+            r#"
+            INSERT INTO audit_log (actor_id, action, target_type, target_id, request_id, ip, before, after)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(event.actor_id)
+        .bind(event.action.as_str())
+        .bind(event.action.target_type())
+        .bind(event.target_id)
+        .bind(&client.request_id)
+        .bind(&client.ip)
+        .bind(&event.before)
+        .bind(&event.after)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    async fn find(&self, filter: &AuditFilter, limit: i64) -> Result<Vec<AuditEntry>, DomainError> {
+        sqlx::query_as::<_, AuditEntry>(
+            r#"
+            SELECT id, occurred_at, actor_id, action, target_type, target_id, request_id, ip, before, after
+            FROM audit_log
+            WHERE ($1::uuid IS NULL OR actor_id = $1)
+              AND ($2::uuid IS NULL OR target_id = $2)
+              AND ($3::timestamptz IS NULL OR occurred_at >= $3)
+              AND ($4::timestamptz IS NULL OR occurred_at < $4)
+              AND ($5::bigint IS NULL OR id < $5)
+            ORDER BY id DESC
+            LIMIT $6
+            "#,
+        )
+        .bind(filter.actor_id)
+        .bind(filter.target_id)
+        .bind(filter.from)
+        .bind(filter.to)
+        .bind(filter.before_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)
+    }
+}
@@ -0,0 +1,130 @@
+use crate::domain::comment::Comment;
+use crate::domain::error::DomainError;
+use async_trait::async_trait;
+use sqlx::PgPool;
+use tracing::{error, info};
+use uuid::Uuid;
+
+#[async_trait]
+pub trait CommentRepository: Send + Sync {
+    async fn create(&self, comment: Comment) -> Result<Comment, DomainError>;
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Comment>, DomainError>;
+    /// Все комментарии поста в хронологическом порядке; дерево строится по `parent_id`
+    async fn get_by_post(&self, post_id: Uuid) -> Result<Vec<Comment>, DomainError>;
+    async fn delete(&self, id: Uuid) -> Result<(), DomainError>;
+    /// Скрывает комментарий модератором или возвращает его; `None` — комментария нет
+    async fn set_hidden(&self, id: Uuid, hidden: bool) -> Result<Option<Comment>, DomainError>;
+}
+
+#[derive(Clone)]
+pub struct PostgresCommentRepository {
+    pool: PgPool,
+}
+
+impl PostgresCommentRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl CommentRepository for PostgresCommentRepository {
+    async fn create(&self, comment: Comment) -> Result<Comment, DomainError> {
+        sqlx::query(
+            r#"
+            INSERT INTO comments (id, post_id, author_id, parent_id, content, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $6)
+            "#,
+        )
+        .bind(comment.id)
+        .bind(comment.post_id)
+        .bind(comment.author_id)
+        .bind(comment.parent_id)
+        .bind(&comment.content)
+        .bind(comment.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("failed to create comment: {}", e);
+            DomainError::Internal(format!("database error: {}", e))
+        })?;
+
+        info!(comment_id = %comment.id, post_id = %comment.post_id, "comment created");
+        Ok(comment)
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Comment>, DomainError> {
+        sqlx::query_as::<_, Comment>(
+            r#"
+            SELECT id, post_id, author_id, parent_id, content, hidden_at, created_at, updated_at
+            FROM comments
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("db error find comment {}: {}", id, e);
+            DomainError::Internal(e.to_string())
+        })
+    }
+
+    async fn get_by_post(&self, post_id: Uuid) -> Result<Vec<Comment>, DomainError> {
+        sqlx::query_as::<_, Comment>(
+            r#"
+            SELECT id, post_id, author_id, parent_id, content, hidden_at, created_at, updated_at
+            FROM comments
+            WHERE post_id = $1
+            ORDER BY created_at, id
+            "#,
+        )
+        .bind(post_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!(
+                "db error while fetching comments of post {}: {}",
+                post_id, e
+            );
+            DomainError::Internal(e.to_string())
+        })
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), DomainError> {
+        // Ответы удаляются каскадом
+        sqlx::query("DELETE FROM comments WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                error!("failed to delete comment {}: {}", id, e);
+                DomainError::Internal(e.to_string())
+            })?;
+
+        info!(comment_id = %id, "comment deleted");
+        Ok(())
+    }
+
+    async fn set_hidden(&self, id: Uuid, hidden: bool) -> Result<Option<Comment>, DomainError> {
+        let comment = sqlx::query_as::<_, Comment>(
+            r#"
+            UPDATE comments
+            SET hidden_at = CASE WHEN $2 THEN COALESCE(hidden_at, NOW()) END
+            WHERE id = $1
+            RETURNING id, post_id, author_id, parent_id, content, hidden_at, created_at, updated_at
+            "#,
+        )
+        .bind(id)
+        .bind(hidden)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("failed to change visibility of comment {}: {}", id, e);
+            DomainError::Internal(e.to_string())
+        })?;
+
+        info!(comment_id = %id, hidden, "comment visibility changed");
+        Ok(comment)
+    }
+}
@@ -0,0 +1,119 @@
+use crate::domain::error::DomainError;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+use tracing::error;
+
+/// Счётчики неудачных входов и блокировки по ключам вида `user:<имя>` / `ip:<адрес>`
+#[async_trait]
+pub trait LoginThrottleRepository: Send + Sync {
+    /// Самая поздняя действующая блокировка входа среди ключей
+    async fn login_locked_until(
+        &self,
+        keys: &[String],
+    ) -> Result<Option<DateTime<Utc>>, DomainError>;
+    /// Засчитывает неудачный вход и возвращает число ошибок подряд. Счётчик начинается
+    /// заново, если с прошлой ошибки прошло больше `reset_after`
+    async fn record_login_failure(
+        &self,
+        key: &str,
+        reset_after: Duration,
+    ) -> Result<i32, DomainError>;
+    async fn lock_login(&self, key: &str, until: DateTime<Utc>) -> Result<(), DomainError>;
+    async fn clear_login_failures(&self, key: &str) -> Result<(), DomainError>;
+}
+
+#[derive(Clone)]
+pub struct PostgresLoginThrottleRepository {
+    pool: PgPool,
+}
+
+impl PostgresLoginThrottleRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+fn db_error(e: sqlx::Error) -> DomainError {
+    error!("login throttle repository error: {}", e);
+    DomainError::Internal(format!("database error: {}", e))
+}
+
+#[async_trait]
+impl LoginThrottleRepository for PostgresLoginThrottleRepository {
+    async fn login_locked_until(
+        &self,
+        keys: &[String],
+    ) -> Result<Option<DateTime<Utc>>, DomainError> {
+        sqlx::query_scalar::<_, Option<DateTime<Utc>>>(
+            r#"
+            SELECT MAX(locked_until)
+            FROM login_throttles
+            WHERE key = ANY($1) AND locked_until > NOW()
+            "#,
+        )
+        .bind(keys)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(db_error)
+    }
+
+    async fn record_login_failure(
+        &self,
+        key: &str,
+        reset_after: Duration,
+    ) -> Result<i32, DomainError> {
+        // Давно забытые счётчики ничего не значат — подчищаем их заодно
+        sqlx::query(
+            r#"
+            DELETE FROM login_throttles
+            WHERE last_failure_at < NOW() - INTERVAL '1 day'
+              AND (locked_until IS NULL OR locked_until < NOW())
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        sqlx::query_scalar::<_, i32>(
+            r#"
+            INSERT INTO login_throttles (key, failures, last_failure_at)
+            VALUES ($1, 1, NOW())
+            ON CONFLICT (key) DO UPDATE
+                SET failures        = CASE
+                                          WHEN login_throttles.last_failure_at < NOW() - $2
+                                              THEN 1
+                                          ELSE login_throttles.failures + 1
+                                      END,
+                    last_failure_at = NOW()
+            RETURNING failures
+            "#,
+        )
+        .bind(key)
+        .bind(reset_after)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(db_error)
+    }
+
+    async fn lock_login(&self, key: &str, until: DateTime<Utc>) -> Result<(), DomainError> {
+        sqlx::query("UPDATE login_throttles SET locked_until = $2 WHERE key = $1")
+            .bind(key)
+            .bind(until)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    async fn clear_login_failures(&self, key: &str) -> Result<(), DomainError> {
+        sqlx::query("DELETE FROM login_throttles WHERE key = $1")
+            .bind(key)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(())
+    }
+}
@@ -1,2 +1,7 @@
+pub mod audit_repository;
+pub mod comment_repository;
+pub mod login_throttle_repository;
+pub mod outbox_repository;
 pub mod post_repository;
+pub mod token_repository;
 pub mod user_repository;
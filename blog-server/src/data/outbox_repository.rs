@@ -0,0 +1,96 @@
+use crate::domain::email::OutboxMessage;
+use crate::domain::error::DomainError;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use tracing::error;
+use uuid::Uuid;
+
+#[async_trait]
+pub trait OutboxRepository: Send + Sync {
+    /// Забирает готовые к отправке письма и откладывает их на время отправки,
+    /// чтобы параллельный воркер не взял те же. Засчитывает попытку.
+    async fn claim_pending(
+        &self,
+        limit: i64,
+        max_attempts: i32,
+    ) -> Result<Vec<OutboxMessage>, DomainError>;
+    async fn mark_sent(&self, id: Uuid) -> Result<(), DomainError>;
+    async fn mark_failed(
+        &self,
+        id: Uuid,
+        error: &str,
+        retry_at: DateTime<Utc>,
+    ) -> Result<(), DomainError>;
+}
+
+#[derive(Clone)]
+pub struct PostgresOutboxRepository {
+    pool: PgPool,
+}
+
+impl PostgresOutboxRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+fn db_error(e: sqlx::Error) -> DomainError {
+    error!("outbox repository error: {}", e);
+    DomainError::Internal(format!("database error: {}", e))
+}
+
+#[async_trait]
+impl OutboxRepository for PostgresOutboxRepository {
+    async fn claim_pending(
+        &self,
+        limit: i64,
+        max_attempts: i32,
+    ) -> Result<Vec<OutboxMessage>, DomainError> {
+        sqlx::query_as::<_, OutboxMessage>(
+            r#"
+            UPDATE email_outbox
+            SET attempts   = attempts + 1,
+                send_after = NOW() + INTERVAL '5 minutes'
+            WHERE id IN (SELECT id
+                         FROM email_outbox
+                         WHERE sent_at IS NULL AND send_after <= NOW() AND attempts < $2
+                         ORDER BY send_after
+                         LIMIT $1 FOR UPDATE SKIP LOCKED)
+            RETURNING id, recipient, subject, body, attempts
+            "#,
+        )
+        .bind(limit)
+        .bind(max_attempts)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)
+    }
+
+    async fn mark_sent(&self, id: Uuid) -> Result<(), DomainError> {
+        sqlx::query("UPDATE email_outbox SET sent_at = NOW(), last_error = NULL WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    async fn mark_failed(
+        &self,
+        id: Uuid,
+        error: &str,
+        retry_at: DateTime<Utc>,
+    ) -> Result<(), DomainError> {
+        sqlx::query("UPDATE email_outbox SET last_error = $2, send_after = $3 WHERE id = $1")
+            .bind(id)
+            .bind(error)
+            .bind(retry_at)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(())
+    }
+}
@@ -1,30 +1,125 @@
-use crate::blog::DeletePostRequest;
 use crate::domain::error::DomainError;
-use crate::domain::post::Post;
+use crate::domain::post::{Post, PostCursor, PostFilter, PostStatus};
+use crate::domain::reaction::{ReactionCount, ReactionKind};
+use crate::domain::revision::PostRevision;
+use crate::domain::search::{self, MATCH_END, MATCH_START, SearchResult};
+use crate::domain::slug::{self, slugify};
+use crate::domain::tag::TagCount;
 use crate::presentation::dto::UpdatePostRequest;
 use async_trait::async_trait;
-use chrono::Utc;
-use sqlx::PgPool;
+use chrono::{Duration, Utc};
+use sqlx::{Connection, PgConnection, PgPool};
 use tracing::{error, info};
 use uuid::Uuid;
 
+/// Колонки поста вместе с его тегами; алиас таблицы — `p`
+const POST_COLUMNS: &str = r#"
+    p.id, p.author_id, p.title, p.slug, p.content, p.status, p.published_at, p.publish_at,
+    p.hidden_at, p.deleted_at, p.version, p.created_at, p.updated_at,
+    COALESCE(
+        (SELECT array_agg(t.name ORDER BY t.name)
+         FROM post_tags pt JOIN tags t ON t.id = pt.tag_id
+         WHERE pt.post_id = p.id),
+        '{}'
+    ) AS tags
+"#;
+
+/// Посты, видимые любому читателю (в том числе анонимному)
+const PUBLICLY_VISIBLE: &str = "(p.hidden_at IS NULL
+    AND (p.status = 'published' OR (p.status = 'scheduled' AND p.publish_at <= NOW())))";
+
+/// Условия `PostFilter`; параметры $2..$6 — поля фильтра в порядке объявления
+const FILTER_WHERE: &str = r#"
+    ($2::text IS NULL OR EXISTS (
+        SELECT 1 FROM post_tags pt JOIN tags t ON t.id = pt.tag_id
+        WHERE pt.post_id = p.id AND t.name = $2
+    ))
+    AND ($3::uuid IS NULL OR p.author_id = $3)
+    AND ($4::timestamptz IS NULL OR p.created_at >= $4)
+    AND ($5::timestamptz IS NULL OR p.created_at < $5)
+    AND ($6::text IS NULL OR p.title ILIKE '%' || $6 || '%')
+"#;
+
+/// Экранирует спецсимволы LIKE, чтобы `%` и `_` в запросе искались буквально
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
 #[async_trait]
 pub trait PostRepository: Send + Sync {
     async fn create(&self, user: Post) -> Result<Post, DomainError>;
     async fn find_by_id(&self, id: Uuid) -> Result<Option<Post>, DomainError>;
+    /// Ищет пост по актуальному slug или по одному из прежних
+    async fn find_by_slug(&self, slug: &str) -> Result<Option<Post>, DomainError>;
+    /// Права проверяет сервис; `None` — поста нет. С `expected_version` правка
+    /// применяется, только если пост всё ещё в этой версии, иначе `VersionConflict`
     async fn update_post(
         &self,
         id: Uuid,
-        author_id: Uuid,
         update: UpdatePostRequest,
     ) -> Result<Option<Post>, DomainError>;
-    async fn delete_post(&self, author_id: Uuid, req: DeletePostRequest)
-    -> Result<(), DomainError>;
+    /// Переносит пост в корзину; окончательно его удаляет `purge_deleted`
+    async fn delete_post(&self, id: Uuid) -> Result<(), DomainError>;
+    /// Пост из корзины; `find_by_id` и остальные выборки корзину не видят
+    async fn find_deleted(&self, id: Uuid) -> Result<Option<Post>, DomainError>;
+    /// Корзина автора, недавно удалённые первыми
+    async fn get_trash(&self, author_id: Uuid) -> Result<Vec<Post>, DomainError>;
+    /// `None` — в корзине такого поста нет
+    async fn restore_post(&self, id: Uuid) -> Result<Option<Post>, DomainError>;
+    /// Безвозвратно удаляет посты, пролежавшие в корзине дольше `retention`
+    async fn purge_deleted(
+        &self,
+        retention: Duration,
+        batch_size: i64,
+    ) -> Result<Vec<Uuid>, DomainError>;
+    /// С `after` выдача идёт по курсору (keyset), `offset` при этом игнорируется
     async fn get_posts(
         &self,
+        viewer: Option<Uuid>,
+        filter: &PostFilter,
         limit: Option<usize>,
         offset: Option<usize>,
+        after: Option<PostCursor>,
     ) -> Result<Vec<Post>, DomainError>;
+    async fn count_posts(
+        &self,
+        viewer: Option<Uuid>,
+        filter: &PostFilter,
+    ) -> Result<i64, DomainError>;
+    async fn set_status(&self, id: Uuid, status: PostStatus) -> Result<Option<Post>, DomainError>;
+    /// Скрывает пост модератором или возвращает его в выдачу
+    async fn set_hidden(&self, id: Uuid, hidden: bool) -> Result<Option<Post>, DomainError>;
+    async fn publish_due(&self, batch_size: i64) -> Result<Vec<Uuid>, DomainError>;
+    async fn get_tags(&self) -> Result<Vec<TagCount>, DomainError>;
+    async fn get_revisions(&self, post_id: Uuid) -> Result<Vec<PostRevision>, DomainError>;
+    /// Ставит реакцию или снимает уже поставленную. Возвращает `true`, если реакция добавлена
+    async fn toggle_reaction(
+        &self,
+        post_id: Uuid,
+        user_id: Uuid,
+        kind: ReactionKind,
+    ) -> Result<bool, DomainError>;
+    async fn search(
+        &self,
+        viewer: Option<Uuid>,
+        query: &str,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> Result<Vec<SearchResult>, DomainError>;
+    /// Счётчики реакций сразу для набора постов — один запрос на страницу
+    async fn get_reaction_counts(
+        &self,
+        post_ids: &[Uuid],
+        viewer: Option<Uuid>,
+    ) -> Result<Vec<ReactionCount>, DomainError>;
+    async fn find_revision(
+        &self,
+        post_id: Uuid,
+        revision: i32,
+    ) -> Result<Option<PostRevision>, DomainError>;
 }
 
 #[derive(Clone)]
@@ -38,44 +133,242 @@ impl PostgresPostRepository {
     }
 }
 
+async fn fetch_post(conn: &mut PgConnection, id: Uuid) -> Result<Option<Post>, sqlx::Error> {
+    sqlx::query_as::<_, Post>(&format!(
+        "SELECT {POST_COLUMNS} FROM posts p WHERE p.id = $1 AND p.deleted_at IS NULL"
+    ))
+    .bind(id)
+    .fetch_optional(conn)
+    .await
+}
+
+/// Сколько раз подбирать slug заново, если параллельный запрос занял его между проверкой и записью
+const SLUG_ATTEMPTS: usize = 5;
+
+/// Запись упала на уникальности `posts.slug` — slug занят параллельным запросом
+fn is_slug_taken(e: &sqlx::Error) -> bool {
+    e.as_database_error()
+        .is_some_and(|db| db.is_unique_violation() && db.constraint() == Some("posts_slug_key"))
+}
+
+/// Подбирает свободный slug на основе `base`, добавляя суффикс `-2`, `-3`, ... при коллизиях.
+/// Прежние slug'и других постов тоже заняты — по ним работают редиректы.
+async fn unique_slug(
+    conn: &mut PgConnection,
+    base: &str,
+    post_id: Uuid,
+) -> Result<String, sqlx::Error> {
+    // Параллельные подборы с той же основой ждут друг друга до конца транзакции:
+    // иначе все увидят один и тот же свободный суффикс
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+        .bind(base)
+        .execute(&mut *conn)
+        .await?;
+
+    // base состоит только из [a-z0-9-], поэтому экранировать шаблон LIKE не нужно
+    let taken: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT slug FROM posts WHERE (slug = $1 OR slug LIKE $2) AND id <> $3
+        UNION
+        SELECT slug FROM post_slug_history WHERE (slug = $1 OR slug LIKE $2) AND post_id <> $3
+        "#,
+    )
+    .bind(base)
+    .bind(format!("{base}-%"))
+    .bind(post_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    if !taken.iter().any(|s| s == base) {
+        return Ok(base.to_string());
+    }
+
+    let slug = (2..)
+        .map(|n| format!("{base}-{n}"))
+        .find(|candidate| !taken.contains(candidate))
+        .expect("infinite range");
+    Ok(slug)
+}
+
+/// Меняет slug поста после смены заголовка, сохраняя старый в истории
+async fn update_slug(
+    conn: &mut PgConnection,
+    post_id: Uuid,
+    title: &str,
+) -> Result<(), sqlx::Error> {
+    let current: String = sqlx::query_scalar("SELECT slug FROM posts WHERE id = $1")
+        .bind(post_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+    let base = slugify(title);
+    if slug::has_base(&current, &base) {
+        return Ok(());
+    }
+
+    sqlx::query("INSERT INTO post_slug_history (slug, post_id) VALUES ($1, $2) ON CONFLICT (slug) DO NOTHING")
+        .bind(&current)
+        .bind(post_id)
+        .execute(&mut *conn)
+        .await?;
+
+    // Подбор и запись идут в точке сохранения: если slug успели занять, откатываемся к ней и подбираем снова
+    let mut attempt = 1;
+    let new_slug = loop {
+        let mut savepoint = conn.begin().await?;
+        let new_slug = unique_slug(&mut savepoint, &base, post_id).await?;
+
+        // Возврат к прежнему заголовку: slug снова актуальный, из истории его убираем
+        sqlx::query("DELETE FROM post_slug_history WHERE slug = $1 AND post_id = $2")
+            .bind(&new_slug)
+            .bind(post_id)
+            .execute(&mut *savepoint)
+            .await?;
+
+        match sqlx::query("UPDATE posts SET slug = $1 WHERE id = $2")
+            .bind(&new_slug)
+            .bind(post_id)
+            .execute(&mut *savepoint)
+            .await
+        {
+            Ok(_) => {
+                savepoint.commit().await?;
+                break new_slug;
+            }
+            Err(e) if is_slug_taken(&e) && attempt < SLUG_ATTEMPTS => {
+                savepoint.rollback().await?;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    };
+
+    info!(post_id = %post_id, old_slug = %current, new_slug = %new_slug, "post slug changed");
+    Ok(())
+}
+
+async fn replace_tags(
+    conn: &mut PgConnection,
+    post_id: Uuid,
+    tags: &[String],
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM post_tags WHERE post_id = $1")
+        .bind(post_id)
+        .execute(&mut *conn)
+        .await?;
+
+    if tags.is_empty() {
+        return Ok(());
+    }
+
+    sqlx::query("INSERT INTO tags (name) SELECT unnest($1::text[]) ON CONFLICT (name) DO NOTHING")
+        .bind(tags)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO post_tags (post_id, tag_id)
+        SELECT $1, id FROM tags WHERE name = ANY($2)
+        "#,
+    )
+    .bind(post_id)
+    .bind(tags)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
 #[async_trait]
 impl PostRepository for PostgresPostRepository {
-    async fn create(&self, post: Post) -> Result<Post, DomainError> {
+    async fn create(&self, mut post: Post) -> Result<Post, DomainError> {
         let now = Utc::now();
-        sqlx::query(
-            r#"
-            INSERT INTO posts (id, author_id, title, content, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $5)
-            "#,
-        )
-        .bind(post.id)
-        .bind(post.author_id)
-        .bind(&post.title)
-        .bind(&post.content)
-        .bind(now)
-        .execute(&self.pool)
-        .await
-        .map_err(|e| {
+        let db_err = |e: sqlx::Error| {
             error!("failed to create post: {}", e);
             DomainError::Internal(format!("database error: {}", e))
-        })?;
+        };
+
+        let mut tx = self.pool.begin().await.map_err(db_err)?;
+
+        // Между подбором slug и вставкой его может занять параллельный запрос с тем же заголовком:
+        // тогда откатываемся к точке сохранения и подбираем следующий свободный
+        let base = std::mem::take(&mut post.slug);
+        let mut attempt = 1;
+        loop {
+            let mut savepoint = tx.begin().await.map_err(db_err)?;
+            post.slug = unique_slug(&mut savepoint, &base, post.id)
+                .await
+                .map_err(db_err)?;
+
+            let inserted = sqlx::query(
+                r#"
+                INSERT INTO posts (id, author_id, title, slug, content, status, published_at, publish_at, created_at, updated_at)
+                VALUES ($1, $2, $3, $9, $4, $5, $6, $7, $8, $8)
+                "#,
+            )
+            .bind(post.id)
+            .bind(post.author_id)
+            .bind(&post.title)
+            .bind(&post.content)
+            .bind(post.status)
+            .bind(post.published_at)
+            .bind(post.publish_at)
+            .bind(now)
+            .bind(&post.slug)
+            .execute(&mut *savepoint)
+            .await;
+
+            match inserted {
+                Ok(_) => {
+                    savepoint.commit().await.map_err(db_err)?;
+                    break;
+                }
+                Err(e) if is_slug_taken(&e) && attempt < SLUG_ATTEMPTS => {
+                    savepoint.rollback().await.map_err(db_err)?;
+                    attempt += 1;
+                }
+                Err(e) => return Err(db_err(e)),
+            }
+        }
+
+        replace_tags(&mut tx, post.id, &post.tags)
+            .await
+            .map_err(db_err)?;
+
+        tx.commit().await.map_err(db_err)?;
 
         info!(post_id = %post.id, author_id = %post.author_id, "post created");
         Ok(post)
     }
 
     async fn find_by_id(&self, id: Uuid) -> Result<Option<Post>, DomainError> {
-        sqlx::query_as::<_, Post>(
+        let mut conn = self
+            .pool
+            .acquire()
+            .await
+            .map_err(|e| DomainError::Internal(e.to_string()))?;
+
+        fetch_post(&mut conn, id).await.map_err(|e| {
+            error!("db error find_by_id {}: {}", id, e);
+            DomainError::Internal(e.to_string())
+        })
+    }
+
+    async fn find_by_slug(&self, slug: &str) -> Result<Option<Post>, DomainError> {
+        sqlx::query_as::<_, Post>(&format!(
             r#"
-            SELECT id, author_id, title, content, created_at, updated_at
-            FROM posts WHERE id = $1
-            "#,
-        )
-        .bind(id)
+            SELECT {POST_COLUMNS} FROM posts p
+            WHERE (p.slug = $1
+                   OR p.id = (SELECT post_id FROM post_slug_history WHERE slug = $1))
+              AND p.deleted_at IS NULL
+            "#
+        ))
+        .bind(slug)
         .fetch_optional(&self.pool)
         .await
         .map_err(|e| {
-            error!("db error find_by_id {}: {}", id, e);
+            error!("db error find_by_slug {}: {}", slug, e);
             DomainError::Internal(e.to_string())
         })
     }
@@ -83,91 +376,241 @@ impl PostRepository for PostgresPostRepository {
     async fn update_post(
         &self,
         id: Uuid,
-        author_id: Uuid,
         update: UpdatePostRequest,
     ) -> Result<Option<Post>, DomainError> {
         let now = Utc::now();
-        let post = sqlx::query_as::<_, Post>(
+        let db_err = |e: sqlx::Error| {
+            error!("failed to update post {}: {}", id, e);
+            DomainError::Internal(e.to_string())
+        };
+
+        let mut tx = self.pool.begin().await.map_err(db_err)?;
+
+        // Блокируем строку, чтобы параллельные правки не получили одинаковый номер ревизии
+        // и не проскочили проверку версии одновременно
+        let locked: Option<i32> = sqlx::query_scalar(
+            "SELECT version FROM posts WHERE id = $1 AND deleted_at IS NULL FOR UPDATE",
+        )
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(db_err)?;
+
+        let Some(version) = locked else {
+            return Ok(None);
+        };
+        if update
+            .expected_version
+            .is_some_and(|expected| expected != version)
+        {
+            return Err(DomainError::VersionConflict(id, version));
+        }
+
+        // Сохраняем прежнюю версию, только если заголовок или текст действительно меняются
+        sqlx::query(
+            r#"
+            INSERT INTO post_revisions (post_id, revision, title, content, created_at)
+            SELECT id,
+                   COALESCE((SELECT MAX(revision) FROM post_revisions WHERE post_id = $1), 0) + 1,
+                   title, content, updated_at
+            FROM posts
+            WHERE id = $1
+              AND (title IS DISTINCT FROM COALESCE($2, title)
+                   OR content IS DISTINCT FROM COALESCE($3, content))
+            "#,
+        )
+        .bind(id)
+        .bind(&update.title)
+        .bind(&update.content)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_err)?;
+
+        let updated: Option<Uuid> = sqlx::query_scalar(
             r#"
             UPDATE posts
             SET
                 title = COALESCE($1, title),
                 content = COALESCE($2, content),
-                updated_at = $3
-            WHERE id = $4 AND author_id = $5
-            RETURNING id, author_id, title, content, created_at, updated_at
+                updated_at = $3,
+                publish_at = COALESCE($5, publish_at),
+                status = CASE WHEN $5 IS NULL THEN status ELSE 'scheduled' END,
+                published_at = CASE WHEN $5 IS NULL THEN published_at END,
+                version = version + 1
+            WHERE id = $4
+            RETURNING id
             "#,
         )
-        .bind(update.title)
-        .bind(update.content)
+        .bind(&update.title)
+        .bind(&update.content)
         .bind(now)
         .bind(id)
-        .bind(author_id)
+        .bind(update.publish_at)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(db_err)?;
+
+        if updated.is_none() {
+            return Ok(None);
+        }
+
+        if let Some(title) = &update.title {
+            update_slug(&mut tx, id, title).await.map_err(db_err)?;
+        }
+
+        if let Some(tags) = &update.tags {
+            replace_tags(&mut tx, id, tags).await.map_err(db_err)?;
+        }
+
+        let post = fetch_post(&mut tx, id).await.map_err(db_err)?;
+        tx.commit().await.map_err(db_err)?;
+
+        info!(post_id = %id, "post updated");
+
+        Ok(post)
+    }
+
+    async fn delete_post(&self, id: Uuid) -> Result<(), DomainError> {
+        // Удаление меняет версию, чтобы старый ETag не прошёл If-Match после восстановления
+        let deleted = sqlx::query(
+            "UPDATE posts SET deleted_at = NOW(), version = version + 1 WHERE id = $1 AND deleted_at IS NULL",
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| DomainError::Internal(e.to_string()))?;
+
+        if deleted.rows_affected() == 0 {
+            return Err(DomainError::PostNotFound(id));
+        }
+
+        info!(post_id = %id, "post moved to trash");
+        Ok(())
+    }
+
+    async fn find_deleted(&self, id: Uuid) -> Result<Option<Post>, DomainError> {
+        sqlx::query_as::<_, Post>(&format!(
+            "SELECT {POST_COLUMNS} FROM posts p WHERE p.id = $1 AND p.deleted_at IS NOT NULL"
+        ))
+        .bind(id)
         .fetch_optional(&self.pool)
         .await
         .map_err(|e| {
-            error!("failed to update post {}: {}", id, e);
+            error!("db error find_deleted {}: {}", id, e);
             DomainError::Internal(e.to_string())
-        })?;
+        })
+    }
 
-        if post.is_some() {
-            info!(post_id = %id, "post updated");
+    async fn get_trash(&self, author_id: Uuid) -> Result<Vec<Post>, DomainError> {
+        sqlx::query_as::<_, Post>(&format!(
+            r#"
+            SELECT {POST_COLUMNS} FROM posts p
+            WHERE p.author_id = $1 AND p.deleted_at IS NOT NULL
+            ORDER BY p.deleted_at DESC, p.id DESC
+            "#
+        ))
+        .bind(author_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("db error while fetching trash of {}: {}", author_id, e);
+            DomainError::Internal(e.to_string())
+        })
+    }
+
+    async fn restore_post(&self, id: Uuid) -> Result<Option<Post>, DomainError> {
+        let db_err = |e: sqlx::Error| {
+            error!("failed to restore post {}: {}", id, e);
+            DomainError::Internal(e.to_string())
+        };
+
+        let mut conn = self.pool.acquire().await.map_err(db_err)?;
+
+        let restored: Option<Uuid> = sqlx::query_scalar(
+            "UPDATE posts SET deleted_at = NULL, version = version + 1 WHERE id = $1 AND deleted_at IS NOT NULL RETURNING id",
+        )
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(db_err)?;
+
+        if restored.is_none() {
+            return Ok(None);
         }
 
-        Ok(post)
+        info!(post_id = %id, "post restored from trash");
+
+        fetch_post(&mut conn, id).await.map_err(db_err)
     }
 
-    async fn delete_post(
+    async fn purge_deleted(
         &self,
-        author_id: Uuid,
-        req: DeletePostRequest,
-    ) -> Result<(), DomainError> {
-        let id = Uuid::parse_str(req.post_id.as_str())
-            .map_err(|e| DomainError::Internal(e.to_string()))?;
-
-        let deleted = sqlx::query("DELETE FROM posts WHERE id = $1 AND author_id = $2")
-            .bind(id)
-            .bind(author_id)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| DomainError::Internal(e.to_string()))?;
+        retention: Duration,
+        batch_size: i64,
+    ) -> Result<Vec<Uuid>, DomainError> {
+        // Комментарии, реакции, ревизии и теги уходят вместе с постом по ON DELETE CASCADE
+        let purged: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            DELETE FROM posts
+            WHERE id IN (
+                SELECT id FROM posts
+                WHERE deleted_at < $1
+                ORDER BY deleted_at
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id
+            "#,
+        )
+        .bind(Utc::now() - retention)
+        .bind(batch_size)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("failed to purge deleted posts: {}", e);
+            DomainError::Internal(e.to_string())
+        })?;
 
-        if deleted.rows_affected() == 0 {
-            // Проверяем существование
-            let exists: bool =
-                sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM posts WHERE id = $1)")
-                    .bind(id)
-                    .fetch_one(&self.pool)
-                    .await
-                    .map_err(|e| DomainError::Internal(e.to_string()))?;
-
-            return if exists {
-                Err(DomainError::Forbidden)
-            } else {
-                Err(DomainError::PostNotFound(id))
-            };
+        for id in &purged {
+            info!(post_id = %id, "deleted post purged");
         }
 
-        info!(post_id = %id, "post deleted");
-        Ok(())
+        Ok(purged)
     }
 
     async fn get_posts(
         &self,
+        viewer: Option<Uuid>,
+        filter: &PostFilter,
         limit: Option<usize>,
         offset: Option<usize>,
+        after: Option<PostCursor>,
     ) -> Result<Vec<Post>, DomainError> {
         let limit = limit.unwrap_or(10).min(100) as i64;
-        let offset = offset.unwrap_or(0) as i64;
+        let offset = match after {
+            Some(_) => 0,
+            None => offset.unwrap_or(0) as i64,
+        };
 
-        sqlx::query_as::<_, Post>(
+        sqlx::query_as::<_, Post>(&format!(
             r#"
-        SELECT id, author_id, title, content, created_at, updated_at
-        FROM posts
-        ORDER BY created_at DESC
-        LIMIT $1 OFFSET $2
-        "#,
-        )
+        SELECT {POST_COLUMNS} FROM posts p
+        WHERE p.deleted_at IS NULL
+          AND ({PUBLICLY_VISIBLE} OR p.author_id = $1)
+          AND {FILTER_WHERE}
+          AND ($7::timestamptz IS NULL OR (p.created_at, p.id) < ($7, $8))
+        ORDER BY p.created_at DESC, p.id DESC
+        LIMIT $9 OFFSET $10
+        "#
+        ))
+        .bind(viewer)
+        .bind(&filter.tag)
+        .bind(filter.author_id)
+        .bind(filter.created_after)
+        .bind(filter.created_before)
+        .bind(filter.title_contains.as_deref().map(escape_like))
+        .bind(after.map(|cursor| cursor.created_at))
+        .bind(after.map(|cursor| cursor.id))
         .bind(limit)
         .bind(offset)
         .fetch_all(&self.pool) // ← fetch_all, not fetch_optional!
@@ -177,4 +620,327 @@ impl PostRepository for PostgresPostRepository {
             DomainError::Internal(e.to_string())
         })
     }
+
+    async fn count_posts(
+        &self,
+        viewer: Option<Uuid>,
+        filter: &PostFilter,
+    ) -> Result<i64, DomainError> {
+        sqlx::query_scalar(&format!(
+            r#"
+        SELECT COUNT(*) FROM posts p
+        WHERE p.deleted_at IS NULL
+          AND ({PUBLICLY_VISIBLE} OR p.author_id = $1)
+          AND {FILTER_WHERE}
+        "#
+        ))
+        .bind(viewer)
+        .bind(&filter.tag)
+        .bind(filter.author_id)
+        .bind(filter.created_after)
+        .bind(filter.created_before)
+        .bind(filter.title_contains.as_deref().map(escape_like))
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("db error while counting posts: {}", e);
+            DomainError::Internal(e.to_string())
+        })
+    }
+
+    async fn set_status(&self, id: Uuid, status: PostStatus) -> Result<Option<Post>, DomainError> {
+        let db_err = |e: sqlx::Error| {
+            error!("failed to change status of post {}: {}", id, e);
+            DomainError::Internal(e.to_string())
+        };
+
+        let mut conn = self.pool.acquire().await.map_err(db_err)?;
+
+        // published_at фиксирует первую публикацию, при снятии с публикации сбрасывается.
+        // Явная смена статуса отменяет отложенную публикацию.
+        let updated: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            UPDATE posts
+            SET
+                status = $1,
+                published_at = CASE
+                    WHEN $1 = 'published' THEN COALESCE(published_at, NOW())
+                    WHEN $1 = 'draft' THEN NULL
+                    ELSE published_at
+                END,
+                publish_at = NULL,
+                version = version + 1
+            WHERE id = $2 AND deleted_at IS NULL
+            RETURNING id
+            "#,
+        )
+        .bind(status)
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(db_err)?;
+
+        if updated.is_none() {
+            return Ok(None);
+        }
+
+        info!(post_id = %id, status = ?status, "post status changed");
+
+        fetch_post(&mut conn, id).await.map_err(db_err)
+    }
+
+    async fn set_hidden(&self, id: Uuid, hidden: bool) -> Result<Option<Post>, DomainError> {
+        let db_err = |e: sqlx::Error| {
+            error!("failed to change visibility of post {}: {}", id, e);
+            DomainError::Internal(e.to_string())
+        };
+
+        let mut conn = self.pool.acquire().await.map_err(db_err)?;
+
+        // Повторное скрытие не сдвигает время первого
+        let updated: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            UPDATE posts
+            SET hidden_at = CASE WHEN $2 THEN COALESCE(hidden_at, NOW()) END,
+                version = version + 1
+            WHERE id = $1 AND deleted_at IS NULL
+            RETURNING id
+            "#,
+        )
+        .bind(id)
+        .bind(hidden)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(db_err)?;
+
+        if updated.is_none() {
+            return Ok(None);
+        }
+
+        info!(post_id = %id, hidden, "post visibility changed");
+
+        fetch_post(&mut conn, id).await.map_err(db_err)
+    }
+
+    async fn publish_due(&self, batch_size: i64) -> Result<Vec<Uuid>, DomainError> {
+        // SKIP LOCKED позволяет нескольким инстансам сервера публиковать параллельно,
+        // не забирая одни и те же строки
+        let published: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            UPDATE posts
+            SET
+                status = 'published',
+                published_at = publish_at,
+                publish_at = NULL,
+                version = version + 1
+            WHERE id IN (
+                SELECT id FROM posts
+                WHERE status = 'scheduled' AND publish_at <= NOW() AND deleted_at IS NULL
+                ORDER BY publish_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id
+            "#,
+        )
+        .bind(batch_size)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("failed to publish scheduled posts: {}", e);
+            DomainError::Internal(e.to_string())
+        })?;
+
+        for id in &published {
+            info!(post_id = %id, "scheduled post published");
+        }
+
+        Ok(published)
+    }
+
+    async fn get_tags(&self) -> Result<Vec<TagCount>, DomainError> {
+        sqlx::query_as::<_, TagCount>(&format!(
+            r#"
+            SELECT t.name, COUNT(*) AS post_count
+            FROM tags t
+            JOIN post_tags pt ON pt.tag_id = t.id
+            JOIN posts p ON p.id = pt.post_id
+            WHERE {PUBLICLY_VISIBLE} AND p.deleted_at IS NULL
+            GROUP BY t.name
+            ORDER BY post_count DESC, t.name
+            "#
+        ))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("db error while fetching tags: {}", e);
+            DomainError::Internal(e.to_string())
+        })
+    }
+
+    async fn get_revisions(&self, post_id: Uuid) -> Result<Vec<PostRevision>, DomainError> {
+        sqlx::query_as::<_, PostRevision>(
+            r#"
+            SELECT post_id, revision, title, content, created_at
+            FROM post_revisions
+            WHERE post_id = $1
+            ORDER BY revision DESC
+            "#,
+        )
+        .bind(post_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!(
+                "db error while fetching revisions of post {}: {}",
+                post_id, e
+            );
+            DomainError::Internal(e.to_string())
+        })
+    }
+
+    async fn find_revision(
+        &self,
+        post_id: Uuid,
+        revision: i32,
+    ) -> Result<Option<PostRevision>, DomainError> {
+        sqlx::query_as::<_, PostRevision>(
+            r#"
+            SELECT post_id, revision, title, content, created_at
+            FROM post_revisions
+            WHERE post_id = $1 AND revision = $2
+            "#,
+        )
+        .bind(post_id)
+        .bind(revision)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!(
+                "db error find_revision {} of post {}: {}",
+                revision, post_id, e
+            );
+            DomainError::Internal(e.to_string())
+        })
+    }
+
+    async fn toggle_reaction(
+        &self,
+        post_id: Uuid,
+        user_id: Uuid,
+        kind: ReactionKind,
+    ) -> Result<bool, DomainError> {
+        let added: Option<i32> = sqlx::query_scalar(
+            r#"
+            WITH removed AS (
+                DELETE FROM post_reactions
+                WHERE post_id = $1 AND user_id = $2 AND kind = $3
+                RETURNING 1
+            )
+            INSERT INTO post_reactions (post_id, user_id, kind)
+            SELECT $1, $2, $3
+            WHERE NOT EXISTS (SELECT 1 FROM removed)
+            ON CONFLICT ON CONSTRAINT post_reactions_user_post_kind_key DO NOTHING
+            RETURNING 1
+            "#,
+        )
+        .bind(post_id)
+        .bind(user_id)
+        .bind(kind)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("failed to toggle reaction on post {}: {}", post_id, e);
+            DomainError::Internal(e.to_string())
+        })?;
+
+        info!(post_id = %post_id, user_id = %user_id, kind = %kind, added = added.is_some(), "reaction toggled");
+        Ok(added.is_some())
+    }
+
+    async fn get_reaction_counts(
+        &self,
+        post_ids: &[Uuid],
+        viewer: Option<Uuid>,
+    ) -> Result<Vec<ReactionCount>, DomainError> {
+        if post_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        sqlx::query_as::<_, ReactionCount>(
+            r#"
+            SELECT post_id, kind, COUNT(*) AS count, COALESCE(BOOL_OR(user_id = $2), FALSE) AS mine
+            FROM post_reactions
+            WHERE post_id = ANY($1)
+            GROUP BY post_id, kind
+            "#,
+        )
+        .bind(post_ids)
+        .bind(viewer)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("db error while counting reactions: {}", e);
+            DomainError::Internal(e.to_string())
+        })
+    }
+
+    async fn search(
+        &self,
+        viewer: Option<Uuid>,
+        query: &str,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> Result<Vec<SearchResult>, DomainError> {
+        let limit = limit.unwrap_or(10).min(100) as i64;
+        let offset = offset.unwrap_or(0) as i64;
+
+        // Индекс, запрос и сниппет строятся в одной конфигурации blog_search (латиница —
+        // английский стеммер, кириллица — русский). Сниппет размечается управляющими символами,
+        // а HTML из него собирает snippet_html: так текст поста не попадает в ответ неэкранированным
+        let hits = sqlx::query_as::<_, SearchResult>(&format!(
+            r#"
+            WITH q AS (
+                SELECT websearch_to_tsquery('blog_search', $1) AS query
+            ),
+            matched AS (
+                SELECT p.id, ts_rank(p.search_vector, q.query) AS rank
+                FROM posts p, q
+                WHERE p.search_vector @@ q.query
+                  AND p.deleted_at IS NULL
+                  AND ({PUBLICLY_VISIBLE} OR p.author_id = $2)
+                ORDER BY rank DESC, p.created_at DESC
+                LIMIT $3 OFFSET $4
+            )
+            SELECT {POST_COLUMNS}, m.rank,
+                   ts_headline('blog_search', translate(p.content, $5, ''), q.query,
+                               'StartSel=' || chr(2) || ', StopSel=' || chr(3)
+                                   || ', MaxFragments=2, MaxWords=30, MinWords=10')
+                       AS snippet
+            FROM matched m
+            JOIN posts p ON p.id = m.id
+            CROSS JOIN q
+            ORDER BY m.rank DESC, p.created_at DESC
+            "#
+        ))
+        .bind(query)
+        .bind(viewer)
+        .bind(limit)
+        .bind(offset)
+        .bind(format!("{MATCH_START}{MATCH_END}"))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("db error while searching posts: {}", e);
+            DomainError::Internal(e.to_string())
+        })?;
+
+        Ok(hits
+            .into_iter()
+            .map(|mut hit| {
+                hit.snippet = search::snippet_html(&hit.snippet);
+                hit
+            })
+            .collect())
+    }
 }
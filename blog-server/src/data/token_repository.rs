@@ -0,0 +1,804 @@
+use crate::domain::email::Email;
+use crate::domain::error::DomainError;
+use crate::domain::oidc::OidcLogin;
+use crate::domain::personal_token::{PersonalAccessToken, Scope};
+use crate::domain::session::{ClientInfo, Session};
+use crate::domain::token::{AccountToken, AccountTokenPurpose, RefreshToken};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, PgPool};
+use tracing::{error, info};
+use uuid::Uuid;
+
+#[async_trait]
+pub trait TokenRepository: Send + Sync {
+    async fn create_refresh_token(&self, token: RefreshToken) -> Result<(), DomainError>;
+    async fn find_refresh_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<RefreshToken>, DomainError>;
+    /// Помечает `used_id` использованным и сохраняет `next` в одной транзакции.
+    /// `false` — токен уже успели использовать или отозвать (гонка или повтор).
+    async fn rotate_refresh_token(
+        &self,
+        used_id: Uuid,
+        next: RefreshToken,
+    ) -> Result<bool, DomainError>;
+    async fn create_session(
+        &self,
+        session_id: Uuid,
+        user_id: Uuid,
+        client: &ClientInfo,
+    ) -> Result<(), DomainError>;
+    /// Обновляет время последней активности (не чаще раза в минуту), а с `client` — и адрес
+    async fn touch_session(
+        &self,
+        session_id: Uuid,
+        client: Option<&ClientInfo>,
+    ) -> Result<(), DomainError>;
+    async fn list_sessions(&self, user_id: Uuid) -> Result<Vec<Session>, DomainError>;
+    /// Закрывает сессию вместе с её refresh-токенами. `false` — активной сессии нет
+    async fn revoke_session(&self, session_id: Uuid, user_id: Uuid) -> Result<bool, DomainError>;
+    async fn revoke_access_token(
+        &self,
+        jti: Uuid,
+        user_id: Uuid,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), DomainError>;
+    /// Отозван сам токен, закрыта его сессия или пользователь вышел везде после `issued_at`
+    async fn is_access_token_revoked(
+        &self,
+        jti: Uuid,
+        session_id: Uuid,
+        user_id: Uuid,
+        issued_at: DateTime<Utc>,
+    ) -> Result<bool, DomainError>;
+    /// Гасит все выданные пользователю access- и refresh-токены
+    async fn revoke_all_for_user(&self, user_id: Uuid) -> Result<(), DomainError>;
+    /// Закрывает все сессии пользователя, кроме `keep_session_id`
+    async fn revoke_other_sessions(
+        &self,
+        user_id: Uuid,
+        keep_session_id: Uuid,
+    ) -> Result<(), DomainError>;
+    async fn create_personal_token(&self, token: &PersonalAccessToken) -> Result<(), DomainError>;
+    async fn find_personal_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<PersonalAccessToken>, DomainError>;
+    async fn list_personal_tokens(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<PersonalAccessToken>, DomainError>;
+    /// `false` — активного токена с таким id у пользователя нет
+    async fn revoke_personal_token(&self, id: Uuid, user_id: Uuid) -> Result<bool, DomainError>;
+    /// Отмечает использование токена, не чаще раза в минуту
+    async fn touch_personal_token(&self, id: Uuid) -> Result<(), DomainError>;
+    /// Заменяет все коды восстановления пользователя новыми (пустой список — просто удаляет)
+    async fn replace_recovery_codes(
+        &self,
+        user_id: Uuid,
+        code_hashes: &[String],
+    ) -> Result<(), DomainError>;
+    /// Гасит неиспользованный код. `false` — такого кода нет
+    async fn use_recovery_code(&self, user_id: Uuid, code_hash: &str) -> Result<bool, DomainError>;
+    async fn create_mfa_challenge(
+        &self,
+        user_id: Uuid,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), DomainError>;
+    /// Засчитывает попытку ввода кода и возвращает пользователя,
+    /// если challenge жив и попытки не исчерпаны
+    async fn attempt_mfa_challenge(
+        &self,
+        token_hash: &str,
+        max_attempts: i32,
+    ) -> Result<Option<Uuid>, DomainError>;
+    /// Сохраняет токен и письмо со ссылкой в одной транзакции; прежние неиспользованные
+    /// токены того же назначения при этом гасятся
+    async fn create_account_token(
+        &self,
+        token: &AccountToken,
+        email: &Email,
+    ) -> Result<(), DomainError>;
+    /// Гасит действующий токен и возвращает его. `None` — неизвестен, истёк или уже использован
+    async fn consume_account_token(
+        &self,
+        token_hash: &str,
+        purpose: AccountTokenPurpose,
+    ) -> Result<Option<AccountToken>, DomainError>;
+    /// `false` — challenge уже использован параллельным запросом
+    async fn delete_mfa_challenge(&self, token_hash: &str) -> Result<bool, DomainError>;
+    async fn create_oidc_login(&self, login: &OidcLogin) -> Result<(), DomainError>;
+    /// Забирает начатый вход: каждый `state` принимается один раз и только до истечения
+    async fn take_oidc_login(&self, state_hash: &str) -> Result<Option<OidcLogin>, DomainError>;
+}
+
+#[derive(Clone)]
+pub struct PostgresTokenRepository {
+    pool: PgPool,
+}
+
+impl PostgresTokenRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(FromRow)]
+struct PersonalTokenRow {
+    id: Uuid,
+    user_id: Uuid,
+    name: String,
+    token_hash: String,
+    scopes: Vec<String>,
+    expires_at: Option<DateTime<Utc>>,
+    last_used_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    revoked_at: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<PersonalTokenRow> for PersonalAccessToken {
+    type Error = DomainError;
+
+    fn try_from(row: PersonalTokenRow) -> Result<Self, Self::Error> {
+        let scopes = row
+            .scopes
+            .iter()
+            .map(|scope| scope.parse::<Scope>())
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            id: row.id,
+            user_id: row.user_id,
+            name: row.name,
+            token_hash: row.token_hash,
+            scopes,
+            expires_at: row.expires_at,
+            last_used_at: row.last_used_at,
+            created_at: row.created_at,
+            revoked_at: row.revoked_at,
+        })
+    }
+}
+
+fn db_error(e: sqlx::Error) -> DomainError {
+    error!("token repository error: {}", e);
+    DomainError::Internal(format!("database error: {}", e))
+}
+
+#[async_trait]
+impl TokenRepository for PostgresTokenRepository {
+    async fn create_refresh_token(&self, token: RefreshToken) -> Result<(), DomainError> {
+        sqlx::query(
+            r#"
+            INSERT INTO refresh_tokens (id, user_id, family_id, token_hash, expires_at, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(token.id)
+        .bind(token.user_id)
+        .bind(token.family_id)
+        .bind(&token.token_hash)
+        .bind(token.expires_at)
+        .bind(token.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    async fn find_refresh_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<RefreshToken>, DomainError> {
+        sqlx::query_as::<_, RefreshToken>(
+            r#"
+            SELECT id, user_id, family_id, token_hash, expires_at, used_at, revoked_at, created_at
+            FROM refresh_tokens
+            WHERE token_hash = $1
+            "#,
+        )
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)
+    }
+
+    async fn rotate_refresh_token(
+        &self,
+        used_id: Uuid,
+        next: RefreshToken,
+    ) -> Result<bool, DomainError> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+
+        let updated = sqlx::query(
+            r#"
+            UPDATE refresh_tokens
+            SET used_at = NOW()
+            WHERE id = $1 AND used_at IS NULL AND revoked_at IS NULL
+            "#,
+        )
+        .bind(used_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        if updated.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO refresh_tokens (id, user_id, family_id, token_hash, expires_at, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(next.id)
+        .bind(next.user_id)
+        .bind(next.family_id)
+        .bind(&next.token_hash)
+        .bind(next.expires_at)
+        .bind(next.created_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        tx.commit().await.map_err(db_error)?;
+        Ok(true)
+    }
+
+    async fn create_session(
+        &self,
+        session_id: Uuid,
+        user_id: Uuid,
+        client: &ClientInfo,
+    ) -> Result<(), DomainError> {
+        sqlx::query(
+            r#"
+            INSERT INTO sessions (id, user_id, user_agent, ip)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(session_id)
+        .bind(user_id)
+        .bind(&client.user_agent)
+        .bind(&client.ip)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        info!(user_id = %user_id, session_id = %session_id, "session created");
+        Ok(())
+    }
+
+    async fn touch_session(
+        &self,
+        session_id: Uuid,
+        client: Option<&ClientInfo>,
+    ) -> Result<(), DomainError> {
+        let query = match client {
+            Some(client) => sqlx::query(
+                r#"
+                UPDATE sessions
+                SET last_seen_at = NOW(),
+                    user_agent   = COALESCE($2, user_agent),
+                    ip           = COALESCE($3, ip)
+                WHERE id = $1
+                "#,
+            )
+            .bind(session_id)
+            .bind(&client.user_agent)
+            .bind(&client.ip),
+            // На каждый запрос писать в базу незачем
+            None => sqlx::query(
+                r#"
+                UPDATE sessions
+                SET last_seen_at = NOW()
+                WHERE id = $1 AND last_seen_at < NOW() - INTERVAL '1 minute'
+                "#,
+            )
+            .bind(session_id),
+        };
+
+        query.execute(&self.pool).await.map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn list_sessions(&self, user_id: Uuid) -> Result<Vec<Session>, DomainError> {
+        sqlx::query_as::<_, Session>(
+            r#"
+            SELECT id, user_id, user_agent, ip, created_at, last_seen_at
+            FROM sessions
+            WHERE user_id = $1 AND revoked_at IS NULL
+            ORDER BY last_seen_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)
+    }
+
+    async fn revoke_session(&self, session_id: Uuid, user_id: Uuid) -> Result<bool, DomainError> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+
+        let revoked = sqlx::query(
+            r#"
+            UPDATE sessions
+            SET revoked_at = NOW()
+            WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(session_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        sqlx::query(
+            r#"
+            UPDATE refresh_tokens
+            SET revoked_at = NOW()
+            WHERE family_id = $1 AND user_id = $2 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(session_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        tx.commit().await.map_err(db_error)?;
+
+        info!(user_id = %user_id, session_id = %session_id, "session revoked");
+        Ok(revoked.rows_affected() > 0)
+    }
+
+    async fn revoke_access_token(
+        &self,
+        jti: Uuid,
+        user_id: Uuid,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), DomainError> {
+        // Истёкшие записи больше ничего не блокируют — подчищаем их заодно
+        sqlx::query("DELETE FROM revoked_tokens WHERE expires_at < NOW()")
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO revoked_tokens (jti, user_id, expires_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (jti) DO NOTHING
+            "#,
+        )
+        .bind(jti)
+        .bind(user_id)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        info!(user_id = %user_id, jti = %jti, "access token revoked");
+        Ok(())
+    }
+
+    async fn is_access_token_revoked(
+        &self,
+        jti: Uuid,
+        session_id: Uuid,
+        user_id: Uuid,
+        issued_at: DateTime<Utc>,
+    ) -> Result<bool, DomainError> {
+        sqlx::query_scalar::<_, bool>(
+            r#"
+            SELECT EXISTS (SELECT 1 FROM revoked_tokens WHERE jti = $1)
+                OR EXISTS (SELECT 1 FROM users WHERE id = $3 AND tokens_valid_after > $4)
+                OR NOT EXISTS (SELECT 1
+                               FROM sessions
+                               WHERE id = $2 AND user_id = $3 AND revoked_at IS NULL)
+            "#,
+        )
+        .bind(jti)
+        .bind(session_id)
+        .bind(user_id)
+        .bind(issued_at)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(db_error)
+    }
+
+    async fn revoke_all_for_user(&self, user_id: Uuid) -> Result<(), DomainError> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+
+        // iat в JWT с точностью до секунды, поэтому отметку округляем вниз
+        sqlx::query(
+            "UPDATE users SET tokens_valid_after = date_trunc('second', NOW()) WHERE id = $1",
+        )
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        for table in ["sessions", "refresh_tokens"] {
+            sqlx::query(&format!(
+                "UPDATE {table} SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL"
+            ))
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        }
+
+        tx.commit().await.map_err(db_error)?;
+
+        info!(user_id = %user_id, "all tokens revoked");
+        Ok(())
+    }
+
+    async fn revoke_other_sessions(
+        &self,
+        user_id: Uuid,
+        keep_session_id: Uuid,
+    ) -> Result<(), DomainError> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+
+        // Access-токены закрытых сессий отсекает проверка сессии в is_access_token_revoked
+        sqlx::query(
+            r#"
+            UPDATE sessions
+            SET revoked_at = NOW()
+            WHERE user_id = $1 AND id <> $2 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(user_id)
+        .bind(keep_session_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        sqlx::query(
+            r#"
+            UPDATE refresh_tokens
+            SET revoked_at = NOW()
+            WHERE user_id = $1 AND family_id <> $2 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(user_id)
+        .bind(keep_session_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        tx.commit().await.map_err(db_error)?;
+
+        info!(user_id = %user_id, session_id = %keep_session_id, "other sessions revoked");
+        Ok(())
+    }
+
+    async fn create_personal_token(&self, token: &PersonalAccessToken) -> Result<(), DomainError> {
+        let scopes: Vec<&str> = token.scopes.iter().map(Scope::as_str).collect();
+        sqlx::query(
+            r#"
+            INSERT INTO personal_access_tokens (id, user_id, name, token_hash, scopes, expires_at, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(token.id)
+        .bind(token.user_id)
+        .bind(&token.name)
+        .bind(&token.token_hash)
+        .bind(&scopes)
+        .bind(token.expires_at)
+        .bind(token.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        info!(user_id = %token.user_id, token_id = %token.id, "personal access token created");
+        Ok(())
+    }
+
+    async fn find_personal_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<PersonalAccessToken>, DomainError> {
+        sqlx::query_as::<_, PersonalTokenRow>(
+            r#"
+            SELECT id, user_id, name, token_hash, scopes, expires_at, last_used_at, created_at, revoked_at
+            FROM personal_access_tokens
+            WHERE token_hash = $1
+            "#,
+        )
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?
+        .map(PersonalAccessToken::try_from)
+        .transpose()
+    }
+
+    async fn list_personal_tokens(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<PersonalAccessToken>, DomainError> {
+        sqlx::query_as::<_, PersonalTokenRow>(
+            r#"
+            SELECT id, user_id, name, token_hash, scopes, expires_at, last_used_at, created_at, revoked_at
+            FROM personal_access_tokens
+            WHERE user_id = $1 AND revoked_at IS NULL
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(PersonalAccessToken::try_from)
+        .collect()
+    }
+
+    async fn revoke_personal_token(&self, id: Uuid, user_id: Uuid) -> Result<bool, DomainError> {
+        let revoked = sqlx::query(
+            r#"
+            UPDATE personal_access_tokens
+            SET revoked_at = NOW()
+            WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        info!(user_id = %user_id, token_id = %id, "personal access token revoked");
+        Ok(revoked.rows_affected() > 0)
+    }
+
+    async fn touch_personal_token(&self, id: Uuid) -> Result<(), DomainError> {
+        sqlx::query(
+            r#"
+            UPDATE personal_access_tokens
+            SET last_used_at = NOW()
+            WHERE id = $1 AND (last_used_at IS NULL OR last_used_at < NOW() - INTERVAL '1 minute')
+            "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    async fn replace_recovery_codes(
+        &self,
+        user_id: Uuid,
+        code_hashes: &[String],
+    ) -> Result<(), DomainError> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+
+        sqlx::query("DELETE FROM recovery_codes WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+
+        for code_hash in code_hashes {
+            sqlx::query(
+                r#"
+                INSERT INTO recovery_codes (id, user_id, code_hash)
+                VALUES ($1, $2, $3)
+                "#,
+            )
+            .bind(Uuid::new_v4())
+            .bind(user_id)
+            .bind(code_hash)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        }
+
+        tx.commit().await.map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn use_recovery_code(&self, user_id: Uuid, code_hash: &str) -> Result<bool, DomainError> {
+        let used = sqlx::query(
+            r#"
+            UPDATE recovery_codes
+            SET used_at = NOW()
+            WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL
+            "#,
+        )
+        .bind(user_id)
+        .bind(code_hash)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        if used.rows_affected() > 0 {
+            info!(user_id = %user_id, "recovery code used");
+        }
+        Ok(used.rows_affected() > 0)
+    }
+
+    async fn create_mfa_challenge(
+        &self,
+        user_id: Uuid,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), DomainError> {
+        sqlx::query("DELETE FROM mfa_challenges WHERE expires_at < NOW()")
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO mfa_challenges (id, user_id, token_hash, expires_at)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(token_hash)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    async fn attempt_mfa_challenge(
+        &self,
+        token_hash: &str,
+        max_attempts: i32,
+    ) -> Result<Option<Uuid>, DomainError> {
+        sqlx::query_scalar::<_, Uuid>(
+            r#"
+            UPDATE mfa_challenges
+            SET attempts = attempts + 1
+            WHERE token_hash = $1 AND expires_at > NOW() AND attempts < $2
+            RETURNING user_id
+            "#,
+        )
+        .bind(token_hash)
+        .bind(max_attempts)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)
+    }
+
+    async fn delete_mfa_challenge(&self, token_hash: &str) -> Result<bool, DomainError> {
+        let deleted = sqlx::query("DELETE FROM mfa_challenges WHERE token_hash = $1")
+            .bind(token_hash)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(deleted.rows_affected() > 0)
+    }
+
+    async fn create_account_token(
+        &self,
+        token: &AccountToken,
+        email: &Email,
+    ) -> Result<(), DomainError> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+
+        sqlx::query(
+            r#"
+            UPDATE account_tokens
+            SET used_at = NOW()
+            WHERE user_id = $1 AND purpose = $2 AND used_at IS NULL
+            "#,
+        )
+        .bind(token.user_id)
+        .bind(token.purpose)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO account_tokens (id, user_id, purpose, token_hash, email, expires_at, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(token.id)
+        .bind(token.user_id)
+        .bind(token.purpose)
+        .bind(&token.token_hash)
+        .bind(&token.email)
+        .bind(token.expires_at)
+        .bind(token.created_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO email_outbox (id, recipient, subject, body)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(&email.recipient)
+        .bind(&email.subject)
+        .bind(&email.body)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        tx.commit().await.map_err(db_error)?;
+
+        info!(user_id = %token.user_id, purpose = ?token.purpose, "account token created");
+        Ok(())
+    }
+
+    async fn consume_account_token(
+        &self,
+        token_hash: &str,
+        purpose: AccountTokenPurpose,
+    ) -> Result<Option<AccountToken>, DomainError> {
+        sqlx::query_as::<_, AccountToken>(
+            r#"
+            UPDATE account_tokens
+            SET used_at = NOW()
+            WHERE token_hash = $1 AND purpose = $2 AND used_at IS NULL AND expires_at > NOW()
+            RETURNING id, user_id, purpose, token_hash, email, expires_at, used_at, created_at
+            "#,
+        )
+        .bind(token_hash)
+        .bind(purpose)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)
+    }
+
+    async fn create_oidc_login(&self, login: &OidcLogin) -> Result<(), DomainError> {
+        // Брошенные на странице провайдера входы больше не нужны
+        sqlx::query("DELETE FROM oidc_logins WHERE expires_at < NOW()")
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO oidc_logins (state_hash, nonce, code_verifier, expires_at)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(&login.state_hash)
+        .bind(&login.nonce)
+        .bind(&login.code_verifier)
+        .bind(login.expires_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    async fn take_oidc_login(&self, state_hash: &str) -> Result<Option<OidcLogin>, DomainError> {
+        sqlx::query_as::<_, OidcLogin>(
+            r#"
+            DELETE FROM oidc_logins
+            WHERE state_hash = $1
+            RETURNING state_hash, nonce, code_verifier, expires_at
+            "#,
+        )
+        .bind(state_hash)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)
+        .map(|login| login.filter(|login| login.expires_at > Utc::now()))
+    }
+}
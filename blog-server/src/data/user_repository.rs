@@ -1,5 +1,5 @@
 use crate::domain::error::DomainError;
-use crate::domain::user::User;
+use crate::domain::user::{Role, User};
 use async_trait::async_trait;
 use sqlx::PgPool;
 use tracing::{error, info};
@@ -11,6 +11,31 @@ pub trait UserRepository: Send + Sync {
     async fn find_by_email(&self, email: &str) -> Result<Option<User>, DomainError>;
     async fn find_by_username(&self, username: &str) -> Result<Option<User>, DomainError>;
     async fn find_by_id(&self, id: Uuid) -> Result<Option<User>, DomainError>;
+    async fn update_password(&self, user_id: Uuid, password_hash: &str) -> Result<(), DomainError>;
+    /// Меняет адрес и сбрасывает его подтверждение
+    async fn update_email(&self, user_id: Uuid, email: &str) -> Result<(), DomainError>;
+    async fn update_username(&self, user_id: Uuid, username: &str) -> Result<(), DomainError>;
+    async fn update_role(&self, user_id: Uuid, role: Role) -> Result<(), DomainError>;
+    /// Пользователь, привязанный к учётной записи внешнего провайдера
+    async fn find_by_identity(
+        &self,
+        issuer: &str,
+        subject: &str,
+    ) -> Result<Option<User>, DomainError>;
+    async fn link_identity(
+        &self,
+        user_id: Uuid,
+        issuer: &str,
+        subject: &str,
+    ) -> Result<(), DomainError>;
+    /// Подтверждает адрес, если он не менялся с момента отправки письма
+    async fn mark_email_verified(&self, user_id: Uuid, email: &str) -> Result<bool, DomainError>;
+    /// Сохраняет ещё не подтверждённый TOTP-секрет
+    async fn start_totp_enrollment(&self, user_id: Uuid, secret: &str) -> Result<(), DomainError>;
+    async fn enable_totp(&self, user_id: Uuid) -> Result<(), DomainError>;
+    async fn disable_totp(&self, user_id: Uuid) -> Result<(), DomainError>;
+    /// Запоминает шаг принятого кода. `false` — код этого или более позднего шага уже был
+    async fn use_totp_step(&self, user_id: Uuid, step: i64) -> Result<bool, DomainError>;
 }
 
 #[derive(Clone)]
@@ -24,6 +49,19 @@ impl PostgresUserRepository {
     }
 }
 
+/// Занятые username/email превращаются в `UserAlreadyExists`, остальное — во внутреннюю ошибку
+fn unique_violation(e: sqlx::Error, message: &str) -> DomainError {
+    if e.as_database_error()
+        .and_then(|db| db.constraint())
+        .map(|c| c.contains("users_email") || c.contains("users_username"))
+        == Some(true)
+    {
+        DomainError::UserAlreadyExists(message.to_string())
+    } else {
+        DomainError::Internal(format!("database error: {}", e))
+    }
+}
+
 #[async_trait]
 impl UserRepository for PostgresUserRepository {
     async fn create(&self, user: User) -> Result<User, DomainError> {
@@ -41,15 +79,7 @@ impl UserRepository for PostgresUserRepository {
         .await
         .map_err(|e| {
             error!("failed to create user: {}", e);
-            if e.as_database_error()
-                .and_then(|db| db.constraint())
-                .map(|c| c.contains("users_email") || c.contains("users_username"))
-                == Some(true)
-            {
-                DomainError::UserAlreadyExists("username or email already registered".to_string())
-            } else {
-                DomainError::Internal(format!("database error: {}", e))
-            }
+            unique_violation(e, "username or email already registered")
         })?;
 
         info!(user_id = %user.id, email = %user.email, "user created");
@@ -59,7 +89,7 @@ impl UserRepository for PostgresUserRepository {
     async fn find_by_email(&self, email: &str) -> Result<Option<User>, DomainError> {
         sqlx::query_as::<_, User>(
             r#"
-            SELECT id, username, email, password_hash, created_at
+            SELECT id, username, email, password_hash, role, created_at, email_verified_at, totp_secret, totp_enabled_at
             FROM users
             WHERE email = $1
             "#,
@@ -76,7 +106,7 @@ impl UserRepository for PostgresUserRepository {
     async fn find_by_username(&self, username: &str) -> Result<Option<User>, DomainError> {
         sqlx::query_as::<_, User>(
             r#"
-            SELECT id, email, username, password_hash, created_at
+            SELECT id, email, username, password_hash, role, created_at, email_verified_at, totp_secret, totp_enabled_at
             FROM users
             WHERE username = $1
             "#,
@@ -93,7 +123,7 @@ impl UserRepository for PostgresUserRepository {
     async fn find_by_id(&self, id: Uuid) -> Result<Option<User>, DomainError> {
         sqlx::query_as::<_, User>(
             r#"
-            SELECT id, username, email, password_hash, created_at
+            SELECT id, username, email, password_hash, role, created_at, email_verified_at, totp_secret, totp_enabled_at
             FROM users
             WHERE id = $1
             "#,
@@ -106,4 +136,203 @@ impl UserRepository for PostgresUserRepository {
             DomainError::Internal(format!("database error: {}", e))
         })
     }
+
+    async fn start_totp_enrollment(&self, user_id: Uuid, secret: &str) -> Result<(), DomainError> {
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET totp_secret = $2, totp_enabled_at = NULL, totp_last_step = NULL
+            WHERE id = $1
+            "#,
+        )
+        .bind(user_id)
+        .bind(secret)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("failed to store totp secret for {}: {}", user_id, e);
+            DomainError::Internal(format!("database error: {}", e))
+        })?;
+
+        Ok(())
+    }
+
+    async fn enable_totp(&self, user_id: Uuid) -> Result<(), DomainError> {
+        sqlx::query("UPDATE users SET totp_enabled_at = NOW() WHERE id = $1")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                error!("failed to enable totp for {}: {}", user_id, e);
+                DomainError::Internal(format!("database error: {}", e))
+            })?;
+
+        info!(user_id = %user_id, "totp enabled");
+        Ok(())
+    }
+
+    async fn disable_totp(&self, user_id: Uuid) -> Result<(), DomainError> {
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET totp_secret = NULL, totp_enabled_at = NULL, totp_last_step = NULL
+            WHERE id = $1
+            "#,
+        )
+        .bind(user_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("failed to disable totp for {}: {}", user_id, e);
+            DomainError::Internal(format!("database error: {}", e))
+        })?;
+
+        info!(user_id = %user_id, "totp disabled");
+        Ok(())
+    }
+
+    async fn use_totp_step(&self, user_id: Uuid, step: i64) -> Result<bool, DomainError> {
+        let updated = sqlx::query(
+            r#"
+            UPDATE users
+            SET totp_last_step = $2
+            WHERE id = $1 AND (totp_last_step IS NULL OR totp_last_step < $2)
+            "#,
+        )
+        .bind(user_id)
+        .bind(step)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("failed to store totp step for {}: {}", user_id, e);
+            DomainError::Internal(format!("database error: {}", e))
+        })?;
+
+        Ok(updated.rows_affected() > 0)
+    }
+
+    async fn update_password(&self, user_id: Uuid, password_hash: &str) -> Result<(), DomainError> {
+        sqlx::query("UPDATE users SET password_hash = $2 WHERE id = $1")
+            .bind(user_id)
+            .bind(password_hash)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                error!("failed to update password for {}: {}", user_id, e);
+                DomainError::Internal(format!("database error: {}", e))
+            })?;
+
+        info!(user_id = %user_id, "password updated");
+        Ok(())
+    }
+
+    async fn mark_email_verified(&self, user_id: Uuid, email: &str) -> Result<bool, DomainError> {
+        let updated = sqlx::query(
+            r#"
+            UPDATE users
+            SET email_verified_at = COALESCE(email_verified_at, NOW())
+            WHERE id = $1 AND email = $2
+            "#,
+        )
+        .bind(user_id)
+        .bind(email)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("failed to verify email for {}: {}", user_id, e);
+            DomainError::Internal(format!("database error: {}", e))
+        })?;
+
+        Ok(updated.rows_affected() > 0)
+    }
+
+    async fn update_email(&self, user_id: Uuid, email: &str) -> Result<(), DomainError> {
+        sqlx::query("UPDATE users SET email = $2, email_verified_at = NULL WHERE id = $1")
+            .bind(user_id)
+            .bind(email)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                error!("failed to update email for {}: {}", user_id, e);
+                unique_violation(e, "email already registered")
+            })?;
+
+        info!(user_id = %user_id, email = %email, "email updated");
+        Ok(())
+    }
+
+    async fn update_username(&self, user_id: Uuid, username: &str) -> Result<(), DomainError> {
+        sqlx::query("UPDATE users SET username = $2 WHERE id = $1")
+            .bind(user_id)
+            .bind(username)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                error!("failed to update username for {}: {}", user_id, e);
+                unique_violation(e, "username already taken")
+            })?;
+
+        info!(user_id = %user_id, username = %username, "username updated");
+        Ok(())
+    }
+
+    async fn update_role(&self, user_id: Uuid, role: Role) -> Result<(), DomainError> {
+        sqlx::query("UPDATE users SET role = $2 WHERE id = $1")
+            .bind(user_id)
+            .bind(role)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                error!("failed to update role for {}: {}", user_id, e);
+                DomainError::Internal(format!("database error: {}", e))
+            })?;
+
+        info!(user_id = %user_id, role = %role, "role updated");
+        Ok(())
+    }
+
+    async fn find_by_identity(
+        &self,
+        issuer: &str,
+        subject: &str,
+    ) -> Result<Option<User>, DomainError> {
+        sqlx::query_as::<_, User>(
+            r#"
+            SELECT u.id, u.username, u.email, u.password_hash, u.role, u.created_at,
+                   u.email_verified_at, u.totp_secret, u.totp_enabled_at
+            FROM user_identities i
+            JOIN users u ON u.id = i.user_id
+            WHERE i.issuer = $1 AND i.subject = $2
+            "#,
+        )
+        .bind(issuer)
+        .bind(subject)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("failed to find user by identity {}: {}", subject, e);
+            DomainError::Internal(format!("database error: {}", e))
+        })
+    }
+
+    async fn link_identity(
+        &self,
+        user_id: Uuid,
+        issuer: &str,
+        subject: &str,
+    ) -> Result<(), DomainError> {
+        sqlx::query("INSERT INTO user_identities (issuer, subject, user_id) VALUES ($1, $2, $3)")
+            .bind(issuer)
+            .bind(subject)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                error!("failed to link identity for {}: {}", user_id, e);
+                DomainError::Internal(format!("database error: {}", e))
+            })?;
+
+        info!(user_id = %user_id, issuer = %issuer, "external identity linked");
+        Ok(())
+    }
 }
@@ -0,0 +1,113 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Действие из журнала аудита; в базе хранится строкой вида `post.delete`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    UserRegister,
+    Login,
+    LoginFailed,
+    PostCreate,
+    PostUpdate,
+    PostDelete,
+    PostRestore,
+    RoleChange,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::UserRegister => "user.register",
+            AuditAction::Login => "auth.login",
+            AuditAction::LoginFailed => "auth.login_failed",
+            AuditAction::PostCreate => "post.create",
+            AuditAction::PostUpdate => "post.update",
+            AuditAction::PostDelete => "post.delete",
+            AuditAction::PostRestore => "post.restore",
+            AuditAction::RoleChange => "user.role_change",
+        }
+    }
+
+    /// Над чем совершается действие
+    pub fn target_type(&self) -> &'static str {
+        match self {
+            AuditAction::PostCreate
+            | AuditAction::PostUpdate
+            | AuditAction::PostDelete
+            | AuditAction::PostRestore => "post",
+            AuditAction::UserRegister
+            | AuditAction::Login
+            | AuditAction::LoginFailed
+            | AuditAction::RoleChange => "user",
+        }
+    }
+}
+
+/// Событие для записи в журнал: `actor_id` сделал `action` над `target_id`.
+/// Снимки `before`/`after` — состояние цели до и после; для входа — подробности попытки
+#[derive(Debug, Clone)]
+pub struct AuditEvent {
+    pub action: AuditAction,
+    pub actor_id: Option<Uuid>,
+    pub target_id: Option<Uuid>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+impl AuditEvent {
+    pub fn new(action: AuditAction, actor_id: Option<Uuid>, target_id: Option<Uuid>) -> Self {
+        Self {
+            action,
+            actor_id,
+            target_id,
+            before: None,
+            after: None,
+        }
+    }
+
+    pub fn before(mut self, snapshot: impl Serialize) -> Self {
+        self.before = serde_json::to_value(snapshot).ok();
+        self
+    }
+
+    pub fn after(mut self, snapshot: impl Serialize) -> Self {
+        self.after = serde_json::to_value(snapshot).ok();
+        self
+    }
+}
+
+/// Запись журнала в том виде, в каком её видит администратор
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub occurred_at: DateTime<Utc>,
+    pub actor_id: Option<Uuid>,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: Option<Uuid>,
+    pub request_id: Option<String>,
+    pub ip: Option<String>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+/// Страница журнала; `next_before_id` — курсор следующей, `None` на последней
+#[derive(Debug, Serialize)]
+pub struct AuditPage {
+    pub entries: Vec<AuditEntry>,
+    pub next_before_id: Option<i64>,
+}
+
+/// Отбор записей: все условия необязательны и объединяются через AND
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    pub actor_id: Option<Uuid>,
+    pub target_id: Option<Uuid>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    /// Курсор: записи старше этой, чтобы листать дальше последней полученной
+    pub before_id: Option<i64>,
+}
@@ -0,0 +1,36 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+pub const MAX_COMMENT_LENGTH: usize = 10_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Comment {
+    pub id: Uuid,
+    pub post_id: Uuid,
+    pub author_id: Uuid,
+    /// Комментарий, на который это ответ; `None` — комментарий верхнего уровня
+    pub parent_id: Option<Uuid>,
+    pub content: String,
+    /// Скрыт модератором; текст видит только автор комментария
+    pub hidden_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Comment {
+    pub fn new(post_id: Uuid, author_id: Uuid, parent_id: Option<Uuid>, content: String) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            post_id,
+            author_id,
+            parent_id,
+            content,
+            hidden_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct Email {
+    pub recipient: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Письмо из outbox, взятое воркером на отправку
+#[derive(Debug, Clone, FromRow)]
+pub struct OutboxMessage {
+    pub id: Uuid,
+    #[sqlx(flatten)]
+    pub email: Email,
+    /// С учётом текущей попытки
+    pub attempts: i32,
+}
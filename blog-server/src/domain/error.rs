@@ -1,4 +1,5 @@
-use actix_web::{HttpResponse, ResponseError, http::StatusCode};
+use actix_web::http::{StatusCode, header};
+use actix_web::{HttpResponse, ResponseError};
 use serde::Serialize;
 use serde_json::json;
 use thiserror::Error;
@@ -12,10 +13,28 @@ pub enum DomainError {
     UserAlreadyExists(String),
     #[error("post not found: {0}")]
     PostNotFound(Uuid),
+    #[error("post not found: {0}")]
+    SlugNotFound(String),
+    #[error("comment not found: {0}")]
+    CommentNotFound(Uuid),
+    #[error("session not found: {0}")]
+    SessionNotFound(Uuid),
+    #[error("token not found: {0}")]
+    PersonalTokenNotFound(Uuid),
+    #[error("revision {1} of post {0} not found")]
+    RevisionNotFound(Uuid, i32),
+    /// Пост успел измениться: клиент правил версию, отличную от текущей
+    #[error("post {0} was modified concurrently, current version is {1}")]
+    VersionConflict(Uuid, i32),
     #[error("forbidden")]
     Forbidden,
     #[error("unauthorized")]
     Unauthorized,
+    /// Повторить можно через указанное число секунд
+    #[error("too many requests, try again in {0} seconds")]
+    TooManyRequests(u64),
+    #[error("validation error: {0}")]
+    Validation(String),
     #[error("internal error: {0}")]
     Internal(String),
 }
@@ -30,10 +49,19 @@ struct ErrorBody<'a> {
 impl ResponseError for DomainError {
     fn status_code(&self) -> StatusCode {
         match self {
-            DomainError::UserNotFound(_) | DomainError::PostNotFound(_) => StatusCode::NOT_FOUND,
+            DomainError::UserNotFound(_)
+            | DomainError::PostNotFound(_)
+            | DomainError::SlugNotFound(_)
+            | DomainError::CommentNotFound(_)
+            | DomainError::SessionNotFound(_)
+            | DomainError::PersonalTokenNotFound(_)
+            | DomainError::RevisionNotFound(..) => StatusCode::NOT_FOUND,
             DomainError::Unauthorized => StatusCode::UNAUTHORIZED,
             DomainError::Forbidden => StatusCode::FORBIDDEN,
+            DomainError::Validation(_) => StatusCode::BAD_REQUEST,
+            DomainError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
             DomainError::UserAlreadyExists(_) => StatusCode::CONFLICT,
+            DomainError::VersionConflict(..) => StatusCode::PRECONDITION_FAILED,
             DomainError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -41,11 +69,23 @@ impl ResponseError for DomainError {
     fn error_response(&self) -> HttpResponse {
         let message = self.to_string();
         let details = match self {
-            DomainError::PostNotFound(resource) | DomainError::UserNotFound(resource) => {
-                Some(json!({ "resource": resource }))
+            DomainError::PostNotFound(resource)
+            | DomainError::UserNotFound(resource)
+            | DomainError::CommentNotFound(resource)
+            | DomainError::SessionNotFound(resource)
+            | DomainError::PersonalTokenNotFound(resource) => Some(json!({ "resource": resource })),
+            DomainError::SlugNotFound(slug) => Some(json!({ "resource": slug })),
+            DomainError::RevisionNotFound(post_id, revision) => {
+                Some(json!({ "resource": post_id, "revision": revision }))
+            }
+            DomainError::VersionConflict(post_id, version) => {
+                Some(json!({ "resource": post_id, "version": version }))
             }
             DomainError::Forbidden => {
-                Some(json!({ "message:": "you do not have permission to delete this post"}))
+                Some(json!({ "message:": "you do not have permission to perform this action"}))
+            }
+            DomainError::TooManyRequests(retry_after) => {
+                Some(json!({ "retry_after": retry_after }))
             }
             _ => None,
         };
@@ -53,6 +93,10 @@ impl ResponseError for DomainError {
             error: message.as_str(),
             details,
         };
-        HttpResponse::build(self.status_code()).json(body)
+        let mut response = HttpResponse::build(self.status_code());
+        if let DomainError::TooManyRequests(retry_after) = self {
+            response.insert_header((header::RETRY_AFTER, retry_after.to_string()));
+        }
+        response.json(body)
     }
 }
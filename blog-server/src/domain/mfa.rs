@@ -0,0 +1,24 @@
+use serde::Serialize;
+
+use crate::domain::token::TokenPair;
+
+/// Итог проверки пароля: либо сразу токены, либо нужен второй фактор
+#[derive(Debug, Clone)]
+pub enum LoginOutcome {
+    Tokens(TokenPair),
+    MfaRequired(MfaChallenge),
+}
+
+/// Короткоживущий токен между шагами входа; сам по себе доступа не даёт
+#[derive(Debug, Clone)]
+pub struct MfaChallenge {
+    pub mfa_token: String,
+    pub expires_in: i64,
+}
+
+/// Данные для приложения-аутентификатора; TOTP включится после подтверждения кодом
+#[derive(Debug, Clone, Serialize)]
+pub struct TotpEnrollment {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
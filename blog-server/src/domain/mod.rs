@@ -1,3 +1,16 @@
+pub mod audit;
+pub mod comment;
+pub mod email;
 pub mod error;
+pub mod mfa;
+pub mod oidc;
+pub mod personal_token;
 pub mod post;
+pub mod reaction;
+pub mod revision;
+pub mod search;
+pub mod session;
+pub mod slug;
+pub mod tag;
+pub mod token;
 pub mod user;
@@ -0,0 +1,22 @@
+use chrono::{DateTime, Utc};
+use sqlx::FromRow;
+
+/// Начатый вход через провайдера; секреты живут до возврата пользователя с `code`
+#[derive(Debug, Clone, FromRow)]
+pub struct OidcLogin {
+    pub state_hash: String,
+    pub nonce: String,
+    /// PKCE: провайдер отдаст токены только тому, кто знает исходное значение
+    pub code_verifier: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Пользователь со слов провайдера — из проверенного ID-токена
+#[derive(Debug, Clone)]
+pub struct ExternalIdentity {
+    pub issuer: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub preferred_username: Option<String>,
+}
@@ -0,0 +1,113 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::domain::error::DomainError;
+
+/// `last_used_at` нужен с точностью до минуты, чаще писать его в базу незачем
+const LAST_USED_PRECISION_SECS: i64 = 60;
+
+/// Права персонального токена
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Scope {
+    /// Чтение постов, включая собственные черновики
+    #[serde(rename = "posts:read")]
+    PostsRead,
+    /// Создание, правка, публикация и удаление постов
+    #[serde(rename = "posts:write")]
+    PostsWrite,
+}
+
+impl Scope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::PostsRead => "posts:read",
+            Scope::PostsWrite => "posts:write",
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Scope {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "posts:read" => Ok(Scope::PostsRead),
+            "posts:write" => Ok(Scope::PostsWrite),
+            _ => Err(DomainError::Validation(format!("unknown scope: {s}"))),
+        }
+    }
+}
+
+/// Именованный токен для автоматизации; сам секрет показывается один раз, храним sha256-хэш
+#[derive(Debug, Clone, Serialize)]
+pub struct PersonalAccessToken {
+    pub id: Uuid,
+    #[serde(skip)]
+    pub user_id: Uuid,
+    pub name: String,
+    #[serde(skip)]
+    pub token_hash: String,
+    pub scopes: Vec<Scope>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip)]
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl PersonalAccessToken {
+    pub fn new(
+        user_id: Uuid,
+        name: String,
+        token_hash: String,
+        scopes: Vec<Scope>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            user_id,
+            name,
+            token_hash,
+            scopes,
+            expires_at,
+            last_used_at: None,
+            created_at: Utc::now(),
+            revoked_at: None,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.revoked_at.is_none() && self.expires_at.is_none_or(|at| at > Utc::now())
+    }
+
+    /// Отметка об использовании устарела и её пора обновить
+    pub fn needs_touch(&self) -> bool {
+        self.last_used_at
+            .is_none_or(|at| at < Utc::now() - Duration::seconds(LAST_USED_PRECISION_SECS))
+    }
+}
+
+/// Чем подписан запрос: сессия даёт полный доступ, персональный токен — только свои scope
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Credential {
+    Session,
+    PersonalToken(Vec<Scope>),
+}
+
+impl Credential {
+    pub fn allows(&self, scope: Scope) -> bool {
+        match self {
+            Credential::Session => true,
+            Credential::PersonalToken(scopes) => scopes.contains(&scope),
+        }
+    }
+}
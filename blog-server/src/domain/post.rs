@@ -1,27 +1,126 @@
+use crate::domain::reaction::ReactionKind;
+use crate::domain::slug::slugify;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "post_status", rename_all = "lowercase")]
+pub enum PostStatus {
+    Draft,
+    Published,
+    Archived,
+    Scheduled,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Post {
     pub id: Uuid,
     pub author_id: Uuid,
     pub title: String,
+    pub slug: String,
     pub content: String,
+    pub status: PostStatus,
+    pub published_at: Option<DateTime<Utc>>,
+    pub publish_at: Option<DateTime<Utc>>,
+    pub tags: Vec<String>,
+    /// Скрыт модератором; такой пост видит только автор
+    pub hidden_at: Option<DateTime<Utc>>,
+    /// Пост в корзине; из выдачи исключён, автор может его восстановить
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Растёт на единицу при каждом изменении; в REST отдаётся как ETag
+    pub version: i32,
+    /// Заполняется сервисом отдельным запросом сразу для всей страницы
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub reactions: BTreeMap<ReactionKind, i64>,
+    /// Реакции текущего читателя; пусто для анонимных
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub my_reactions: Vec<ReactionKind>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// Позиция в ленте для keyset-пагинации: посты идут по `(created_at, id)` по убыванию
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PostCursor {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+/// Условия выборки ленты; незаданные поля не ограничивают выдачу
+#[derive(Debug, Clone, Default)]
+pub struct PostFilter {
+    pub author_id: Option<Uuid>,
+    pub tag: Option<String>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    /// Подстрока заголовка без учёта регистра
+    pub title_contains: Option<String>,
+}
+
+/// Страница ленты; `next_page_token` пуст, если дальше постов нет
+#[derive(Debug, Clone)]
+pub struct PostPage {
+    pub posts: Vec<Post>,
+    pub next_page_token: Option<String>,
+    /// Сколько всего постов подходит под фильтр
+    pub total: i64,
+}
+
 impl Post {
-    pub fn new(author_id: Uuid, title: String, content: String) -> Self {
+    pub fn new(
+        author_id: Uuid,
+        title: String,
+        content: String,
+        status: PostStatus,
+        publish_at: Option<DateTime<Utc>>,
+        tags: Vec<String>,
+    ) -> Self {
+        let now = Utc::now();
         Self {
             id: Uuid::new_v4(),
             author_id,
+            slug: slugify(&title),
             title,
-            created_at: Utc::now(),
             content,
-            updated_at: Utc::now(),
+            status,
+            published_at: (status == PostStatus::Published).then_some(now),
+            publish_at,
+            tags,
+            hidden_at: None,
+            deleted_at: None,
+            version: 1,
+            reactions: BTreeMap::new(),
+            my_reactions: Vec::new(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn cursor(&self) -> PostCursor {
+        PostCursor {
+            created_at: self.created_at,
+            id: self.id,
+        }
+    }
+
+    /// Черновики, архив, скрытые и ещё не наступившие отложенные публикации видны только автору
+    pub fn is_visible_to(&self, viewer: Option<Uuid>) -> bool {
+        if viewer == Some(self.author_id) {
+            return true;
+        }
+        if self.hidden_at.is_some() {
+            return false;
+        }
+        match self.status {
+            PostStatus::Published => true,
+            PostStatus::Scheduled if self.publish_at.is_some_and(|at| at <= Utc::now()) => true,
+            _ => false,
         }
     }
 }
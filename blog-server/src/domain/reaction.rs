@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::domain::error::DomainError;
+
+/// Фиксированный набор реакций: лайк и несколько эмодзи
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, sqlx::Type,
+)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "reaction_kind", rename_all = "lowercase")]
+pub enum ReactionKind {
+    /// 👍
+    Like,
+    /// ❤️
+    Love,
+    /// 😂
+    Laugh,
+    /// 😮
+    Wow,
+    /// 😢
+    Sad,
+    /// 😡
+    Angry,
+}
+
+impl ReactionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReactionKind::Like => "like",
+            ReactionKind::Love => "love",
+            ReactionKind::Laugh => "laugh",
+            ReactionKind::Wow => "wow",
+            ReactionKind::Sad => "sad",
+            ReactionKind::Angry => "angry",
+        }
+    }
+}
+
+impl fmt::Display for ReactionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ReactionKind {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "like" => Ok(ReactionKind::Like),
+            "love" => Ok(ReactionKind::Love),
+            "laugh" => Ok(ReactionKind::Laugh),
+            "wow" => Ok(ReactionKind::Wow),
+            "sad" => Ok(ReactionKind::Sad),
+            "angry" => Ok(ReactionKind::Angry),
+            _ => Err(DomainError::Validation(format!("unknown reaction: {s}"))),
+        }
+    }
+}
+
+/// Число реакций одного вида на пост; `mine` — есть ли среди них реакция читателя
+#[derive(Debug, Clone, FromRow)]
+pub struct ReactionCount {
+    pub post_id: Uuid,
+    pub kind: ReactionKind,
+    pub count: i64,
+    pub mine: bool,
+}
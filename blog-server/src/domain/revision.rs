@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use similar::TextDiff;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Предыдущая версия поста; номера идут с 1 в порядке изменений
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PostRevision {
+    pub post_id: Uuid,
+    pub revision: i32,
+    pub title: String,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RevisionDiff {
+    pub from: i32,
+    /// `None` — сравнение с текущей версией поста
+    pub to: Option<i32>,
+    pub diff: String,
+}
+
+/// Unified diff по строкам; заголовок сравнивается как первая строка документа
+pub fn unified_diff(
+    from: (&str, &str),
+    to: (&str, &str),
+    from_label: &str,
+    to_label: &str,
+) -> String {
+    let old = format!("{}\n\n{}", from.0, from.1);
+    let new = format!("{}\n\n{}", to.0, to.1);
+
+    TextDiff::from_lines(&old, &new)
+        .unified_diff()
+        .context_radius(3)
+        .header(from_label, to_label)
+        .to_string()
+}
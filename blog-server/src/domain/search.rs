@@ -0,0 +1,41 @@
+use serde::Serialize;
+use sqlx::FromRow;
+
+use crate::domain::post::Post;
+
+pub const MAX_QUERY_LENGTH: usize = 256;
+
+/// Границы совпадений, которые ставит `ts_headline`. Управляющие символы не встречаются
+/// в тексте постов (база их вычищает перед подсветкой) и не экранируются в HTML
+pub const MATCH_START: char = '\u{2}';
+pub const MATCH_END: char = '\u{3}';
+
+/// Найденный пост с релевантностью и фрагментом текста. `snippet` — экранированный HTML,
+/// единственная разметка в нём — `<mark>` вокруг совпадений
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct SearchResult {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub post: Post,
+    pub rank: f32,
+    pub snippet: String,
+}
+
+/// Превращает сниппет из базы в безопасный HTML: текст поста экранируется целиком,
+/// и только после этого границы совпадений становятся тегами `<mark>`
+pub fn snippet_html(raw: &str) -> String {
+    let mut html = String::with_capacity(raw.len() + 16);
+    for c in raw.chars() {
+        match c {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            MATCH_START => html.push_str("<mark>"),
+            MATCH_END => html.push_str("</mark>"),
+            c => html.push(c),
+        }
+    }
+    html
+}
@@ -0,0 +1,46 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Вход пользователя с конкретного устройства; живёт, пока не отозван
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Session {
+    pub id: Uuid,
+    #[serde(skip)]
+    pub user_id: Uuid,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    /// Сессия, с которой сделан запрос
+    #[sqlx(skip)]
+    pub current: bool,
+}
+
+/// Откуда пришёл запрос: для сессий и журнала аудита
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub request_id: Option<String>,
+}
+
+const MAX_USER_AGENT_LENGTH: usize = 512;
+
+impl ClientInfo {
+    pub fn new(user_agent: Option<&str>, ip: Option<&str>) -> Self {
+        Self {
+            user_agent: user_agent
+                .map(|ua| ua.chars().take(MAX_USER_AGENT_LENGTH).collect())
+                .filter(|ua: &String| !ua.is_empty()),
+            ip: ip.map(str::to_string),
+            request_id: None,
+        }
+    }
+
+    pub fn with_request_id(mut self, request_id: String) -> Self {
+        self.request_id = Some(request_id);
+        self
+    }
+}
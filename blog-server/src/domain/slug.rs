@@ -0,0 +1,83 @@
+use crate::domain::post::Post;
+
+pub const MAX_SLUG_LENGTH: usize = 80;
+const FALLBACK_SLUG: &str = "post";
+
+/// Результат поиска по slug: актуальный адрес или старый, сменившийся после переименования
+#[derive(Debug)]
+pub enum SlugLookup {
+    Found(Post),
+    Moved(Post),
+}
+
+/// Строит slug из заголовка: латиница и цифры, кириллица транслитерируется, остальное — дефисы
+pub fn slugify(title: &str) -> String {
+    let mut slug = String::with_capacity(title.len());
+    for ch in title.to_lowercase().chars() {
+        if ch.is_ascii_alphanumeric() {
+            slug.push(ch);
+        } else if let Some(latin) = transliterate(ch) {
+            slug.push_str(latin);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    slug.truncate(MAX_SLUG_LENGTH);
+    let slug = slug.trim_matches('-');
+    if slug.is_empty() {
+        FALLBACK_SLUG.to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// `slug` построен из `base`: совпадает с ним или отличается только числовым суффиксом (`base-2`)
+pub fn has_base(slug: &str, base: &str) -> bool {
+    match slug.strip_prefix(base) {
+        Some("") => true,
+        Some(rest) => rest
+            .strip_prefix('-')
+            .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit())),
+        None => false,
+    }
+}
+
+fn transliterate(ch: char) -> Option<&'static str> {
+    let latin = match ch {
+        'а' => "a",
+        'б' => "b",
+        'в' => "v",
+        'г' => "g",
+        'д' => "d",
+        'е' => "e",
+        'ё' => "yo",
+        'ж' => "zh",
+        'з' => "z",
+        'и' => "i",
+        'й' => "y",
+        'к' => "k",
+        'л' => "l",
+        'м' => "m",
+        'н' => "n",
+        'о' => "o",
+        'п' => "p",
+        'р' => "r",
+        'с' => "s",
+        'т' => "t",
+        'у' => "u",
+        'ф' => "f",
+        'х' => "kh",
+        'ц' => "ts",
+        'ч' => "ch",
+        'ш' => "sh",
+        'щ' => "shch",
+        'ъ' | 'ь' => "",
+        'ы' => "y",
+        'э' => "e",
+        'ю' => "yu",
+        'я' => "ya",
+        _ => return None,
+    };
+    Some(latin)
+}
@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use crate::domain::error::DomainError;
+
+pub const MAX_TAGS_PER_POST: usize = 10;
+pub const MAX_TAG_LENGTH: usize = 32;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TagCount {
+    pub name: String,
+    pub post_count: i64,
+}
+
+/// Приводит теги к единому виду: без пробелов по краям, в нижнем регистре, без дублей
+pub fn normalize_tags(tags: Vec<String>) -> Result<Vec<String>, DomainError> {
+    let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if tag.is_empty() || normalized.contains(&tag) {
+            continue;
+        }
+        if tag.chars().count() > MAX_TAG_LENGTH {
+            return Err(DomainError::Validation(format!(
+                "tag must be at most {MAX_TAG_LENGTH} characters: {tag}"
+            )));
+        }
+        normalized.push(tag);
+    }
+
+    if normalized.len() > MAX_TAGS_PER_POST {
+        return Err(DomainError::Validation(format!(
+            "a post can have at most {MAX_TAGS_PER_POST} tags"
+        )));
+    }
+
+    normalized.sort();
+    Ok(normalized)
+}
@@ -0,0 +1,92 @@
+use chrono::{DateTime, Duration, Utc};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Refresh-токен храним только в виде sha256-хэша; семья — цепочка ротаций от одного логина
+#[derive(Debug, Clone, FromRow)]
+pub struct RefreshToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub family_id: Uuid,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl RefreshToken {
+    pub fn new(
+        user_id: Uuid,
+        family_id: Uuid,
+        token_hash: String,
+        expires_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            user_id,
+            family_id,
+            token_hash,
+            expires_at,
+            used_at: None,
+            revoked_at: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
+}
+
+/// Результат логина или обновления: короткий access JWT и непрозрачный refresh-токен
+#[derive(Debug, Clone)]
+pub struct TokenPair {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub expires_in: i64,
+}
+
+/// Назначение токена из письма
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "account_token_purpose", rename_all = "snake_case")]
+pub enum AccountTokenPurpose {
+    PasswordReset,
+    EmailVerification,
+}
+
+/// Одноразовый токен для ссылки из письма; как и refresh-токен, хранится только хэш
+#[derive(Debug, Clone, FromRow)]
+pub struct AccountToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub purpose: AccountTokenPurpose,
+    pub token_hash: String,
+    /// Адрес, на который ушло письмо
+    pub email: String,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl AccountToken {
+    pub fn new(
+        user_id: Uuid,
+        purpose: AccountTokenPurpose,
+        token_hash: String,
+        email: String,
+        ttl: Duration,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            user_id,
+            purpose,
+            token_hash,
+            email,
+            expires_at: now + ttl,
+            used_at: None,
+            created_at: now,
+        }
+    }
+}
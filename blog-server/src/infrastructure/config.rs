@@ -6,8 +6,61 @@ pub struct AppConfig {
     pub port: u16,
     pub database_url: String,
     pub jwt_secret: String,
+    /// Каталог с PEM-ключами для RS256/EdDSA; без него токены подписываются JWT_SECRET
+    pub jwt_keys_dir: Option<String>,
+    pub jwt_signing_kid: Option<String>,
+    pub access_token_ttl_secs: i64,
+    pub refresh_token_ttl_secs: i64,
+    /// Ключ подписи курсоров пагинации; по умолчанию совпадает с JWT_SECRET
+    pub page_token_secret: String,
     #[serde(default)]
     pub cors_origins: Vec<String>,
+    pub publisher_interval_secs: u64,
+    /// Сколько дней удалённый пост лежит в корзине до окончательного удаления
+    pub trash_retention_days: i64,
+    pub trash_purge_interval_secs: u64,
+    pub mailer: MailerKind,
+    pub smtp_url: Option<String>,
+    pub mail_from: String,
+    pub mail_dir: String,
+    /// Адрес фронтенда для ссылок в письмах
+    pub app_url: String,
+    pub outbox_interval_secs: u64,
+    /// Вход через корпоративный SSO; без OIDC_ISSUER выключен
+    pub oidc: Option<OidcConfig>,
+    /// Запросов в минуту на пользователя или IP для каждого класса; 0 — без ограничения
+    pub rate_limit_auth_per_min: u32,
+    pub rate_limit_write_per_min: u32,
+    pub rate_limit_read_per_min: u32,
+    pub rate_limit_store: RateLimitStoreKind,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct OidcConfig {
+    /// Без завершающего `/`, как в поле `iss` ID-токенов
+    pub issuer: String,
+    pub client_id: String,
+    /// Для публичных клиентов не нужен: их защищает PKCE
+    pub client_secret: Option<String>,
+    pub redirect_url: String,
+    pub scopes: String,
+}
+
+/// Куда уходят письма: SMTP, `.eml`-файлы в MAIL_DIR или только в лог
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MailerKind {
+    Smtp,
+    File,
+    Memory,
+}
+
+/// Где считать запросы: в памяти процесса или в Postgres, общем для всех инстансов
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitStoreKind {
+    Memory,
+    Postgres,
 }
 
 impl AppConfig {
@@ -23,19 +76,118 @@ impl AppConfig {
             .map_err(|_| anyhow::anyhow!("DATABASE_URL must be set"))?;
         let jwt_secret =
             std::env::var("JWT_SECRET").map_err(|_| anyhow::anyhow!("JWT_SECRET must be set"))?;
+        let jwt_keys_dir = std::env::var("JWT_KEYS_DIR").ok();
+        let jwt_signing_kid = std::env::var("JWT_SIGNING_KID").ok();
+        if jwt_keys_dir.is_some() && jwt_signing_kid.is_none() {
+            anyhow::bail!("JWT_SIGNING_KID must be set together with JWT_KEYS_DIR");
+        }
+        let access_token_ttl_secs = std::env::var("ACCESS_TOKEN_EXPIRATION_SECS")
+            .unwrap_or_else(|_| "900".into())
+            .parse()
+            .map_err(|e| anyhow::anyhow!("invalid ACCESS_TOKEN_EXPIRATION_SECS: {}", e))?;
+        let refresh_token_ttl_secs = std::env::var("REFRESH_TOKEN_EXPIRATION_SECS")
+            .unwrap_or_else(|_| "2592000".into())
+            .parse()
+            .map_err(|e| anyhow::anyhow!("invalid REFRESH_TOKEN_EXPIRATION_SECS: {}", e))?;
+        let page_token_secret =
+            std::env::var("PAGE_TOKEN_SECRET").unwrap_or_else(|_| jwt_secret.clone());
         let cors_origins = std::env::var("CORS_ORIGINS")
             .unwrap_or_else(|_| "*".into())
             .split(',')
             .map(|s| s.trim().to_string())
             .filter(|s| !s.is_empty())
             .collect();
+        let publisher_interval_secs = std::env::var("PUBLISHER_INTERVAL_SECS")
+            .unwrap_or_else(|_| "30".into())
+            .parse()
+            .map_err(|e| anyhow::anyhow!("invalid PUBLISHER_INTERVAL_SECS: {}", e))?;
+        let trash_retention_days = std::env::var("TRASH_RETENTION_DAYS")
+            .unwrap_or_else(|_| "30".into())
+            .parse()
+            .map_err(|e| anyhow::anyhow!("invalid TRASH_RETENTION_DAYS: {}", e))?;
+        let trash_purge_interval_secs = std::env::var("TRASH_PURGE_INTERVAL_SECS")
+            .unwrap_or_else(|_| "3600".into())
+            .parse()
+            .map_err(|e| anyhow::anyhow!("invalid TRASH_PURGE_INTERVAL_SECS: {}", e))?;
+
+        let mailer = match std::env::var("MAILER").as_deref() {
+            Ok("smtp") => MailerKind::Smtp,
+            Ok("file") => MailerKind::File,
+            Ok("memory") | Err(_) => MailerKind::Memory,
+            Ok(other) => anyhow::bail!("invalid MAILER: {other} (expected smtp, file or memory)"),
+        };
+        let smtp_url = std::env::var("SMTP_URL").ok();
+        let mail_from =
+            std::env::var("MAIL_FROM").unwrap_or_else(|_| "rust-blog <no-reply@localhost>".into());
+        let mail_dir = std::env::var("MAIL_DIR").unwrap_or_else(|_| "./mail".into());
+        let app_url = std::env::var("APP_URL")
+            .unwrap_or_else(|_| "http://127.0.0.1:8080".into())
+            .trim_end_matches('/')
+            .to_string();
+        let outbox_interval_secs = std::env::var("OUTBOX_INTERVAL_SECS")
+            .unwrap_or_else(|_| "10".into())
+            .parse()
+            .map_err(|e| anyhow::anyhow!("invalid OUTBOX_INTERVAL_SECS: {}", e))?;
+
+        let oidc = match std::env::var("OIDC_ISSUER") {
+            Ok(issuer) => Some(OidcConfig {
+                issuer: issuer.trim_end_matches('/').to_string(),
+                client_id: std::env::var("OIDC_CLIENT_ID")
+                    .map_err(|_| anyhow::anyhow!("OIDC_CLIENT_ID must be set with OIDC_ISSUER"))?,
+                client_secret: std::env::var("OIDC_CLIENT_SECRET").ok(),
+                redirect_url: std::env::var("OIDC_REDIRECT_URL")
+                    .unwrap_or_else(|_| format!("{app_url}/api/auth/oidc/callback")),
+                scopes: std::env::var("OIDC_SCOPES")
+                    .unwrap_or_else(|_| "openid email profile".into()),
+            }),
+            Err(_) => None,
+        };
+
+        let rate_limit_auth_per_min = std::env::var("RATE_LIMIT_AUTH_PER_MIN")
+            .unwrap_or_else(|_| "20".into())
+            .parse()
+            .map_err(|e| anyhow::anyhow!("invalid RATE_LIMIT_AUTH_PER_MIN: {}", e))?;
+        let rate_limit_write_per_min = std::env::var("RATE_LIMIT_WRITE_PER_MIN")
+            .unwrap_or_else(|_| "60".into())
+            .parse()
+            .map_err(|e| anyhow::anyhow!("invalid RATE_LIMIT_WRITE_PER_MIN: {}", e))?;
+        let rate_limit_read_per_min = std::env::var("RATE_LIMIT_READ_PER_MIN")
+            .unwrap_or_else(|_| "600".into())
+            .parse()
+            .map_err(|e| anyhow::anyhow!("invalid RATE_LIMIT_READ_PER_MIN: {}", e))?;
+        let rate_limit_store = match std::env::var("RATE_LIMIT_STORE").as_deref() {
+            Ok("memory") | Err(_) => RateLimitStoreKind::Memory,
+            Ok("postgres") => RateLimitStoreKind::Postgres,
+            Ok(other) => {
+                anyhow::bail!("invalid RATE_LIMIT_STORE: {other} (expected memory or postgres)")
+            }
+        };
 
         Ok(Self {
             host,
             port,
             database_url,
             jwt_secret,
+            jwt_keys_dir,
+            jwt_signing_kid,
+            access_token_ttl_secs,
+            refresh_token_ttl_secs,
+            page_token_secret,
             cors_origins,
+            publisher_interval_secs,
+            trash_retention_days,
+            trash_purge_interval_secs,
+            mailer,
+            smtp_url,
+            mail_from,
+            mail_dir,
+            app_url,
+            outbox_interval_secs,
+            oidc,
+            rate_limit_auth_per_min,
+            rate_limit_write_per_min,
+            rate_limit_read_per_min,
+            rate_limit_store,
         })
     }
 }
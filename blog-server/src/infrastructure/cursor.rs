@@ -0,0 +1,69 @@
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::DateTime;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::domain::error::DomainError;
+use crate::domain::post::PostCursor;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Кодирует позицию keyset-пагинации в непрозрачный токен `payload.signature`.
+/// Подпись HMAC не даёт клиенту подделать или подправить курсор.
+#[derive(Clone)]
+pub struct CursorCodec {
+    secret: Vec<u8>,
+}
+
+impl CursorCodec {
+    pub fn new(secret: impl Into<Vec<u8>>) -> Self {
+        Self {
+            secret: secret.into(),
+        }
+    }
+
+    pub fn encode(&self, cursor: &PostCursor) -> String {
+        let payload = format!("{}:{}", cursor.created_at.timestamp_micros(), cursor.id);
+        let signature = self.sign(payload.as_bytes()).finalize().into_bytes();
+
+        format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(payload),
+            URL_SAFE_NO_PAD.encode(signature)
+        )
+    }
+
+    pub fn decode(&self, token: &str) -> Result<PostCursor, DomainError> {
+        let invalid = || DomainError::Validation("invalid page_token".to_string());
+
+        let (payload, signature) = token.split_once('.').ok_or_else(invalid)?;
+        let payload = URL_SAFE_NO_PAD.decode(payload).map_err(|_| invalid())?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).map_err(|_| invalid())?;
+
+        // verify_slice сравнивает за постоянное время
+        self.sign(&payload)
+            .verify_slice(&signature)
+            .map_err(|_| invalid())?;
+
+        let payload = String::from_utf8(payload).map_err(|_| invalid())?;
+        let (micros, id) = payload.split_once(':').ok_or_else(invalid)?;
+        let created_at = micros
+            .parse()
+            .ok()
+            .and_then(DateTime::from_timestamp_micros)
+            .ok_or_else(invalid)?;
+        let id = Uuid::parse_str(id).map_err(|_| invalid())?;
+
+        Ok(PostCursor { created_at, id })
+    }
+
+    fn sign(&self, payload: &[u8]) -> HmacSha256 {
+        let mut mac =
+            HmacSha256::new_from_slice(&self.secret).expect("HMAC accepts keys of any length");
+        mac.update(b"post-cursor:");
+        mac.update(payload);
+        mac
+    }
+}
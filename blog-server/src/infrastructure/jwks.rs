@@ -0,0 +1,81 @@
+use anyhow::{Context, anyhow, bail};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use jsonwebtoken::Algorithm;
+use jsonwebtoken::jwk::{
+    AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, KeyAlgorithm,
+    OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+};
+use simple_asn1::{ASN1Block, from_der};
+
+const RSA_ENCRYPTION_OID: [u64; 7] = [1, 2, 840, 113549, 1, 1, 1];
+const ED25519_OID: [u64; 4] = [1, 3, 101, 112];
+
+/// Открытый ключ из PEM (SubjectPublicKeyInfo) в виде JWK.
+/// Алгоритм подписи определяется по типу ключа: RSA — RS256, Ed25519 — EdDSA.
+pub fn public_key_to_jwk(kid: &str, public_pem: &[u8]) -> anyhow::Result<(Algorithm, Jwk)> {
+    let pem = pem::parse(public_pem).context("invalid PEM")?;
+    if pem.tag() != "PUBLIC KEY" {
+        bail!("expected a PUBLIC KEY PEM, got {}", pem.tag());
+    }
+
+    let (oid, key) = parse_spki(pem.contents())?;
+    let (algorithm, key_algorithm, parameters) = if oid == RSA_ENCRYPTION_OID {
+        let (n, e) = parse_rsa_public_key(&key)?;
+        let parameters = AlgorithmParameters::RSA(RSAKeyParameters {
+            key_type: RSAKeyType::RSA,
+            n: URL_SAFE_NO_PAD.encode(n),
+            e: URL_SAFE_NO_PAD.encode(e),
+        });
+        (Algorithm::RS256, KeyAlgorithm::RS256, parameters)
+    } else if oid == ED25519_OID {
+        let parameters = AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+            key_type: OctetKeyPairType::OctetKeyPair,
+            curve: EllipticCurve::Ed25519,
+            x: URL_SAFE_NO_PAD.encode(key),
+        });
+        (Algorithm::EdDSA, KeyAlgorithm::EdDSA, parameters)
+    } else {
+        bail!("unsupported key type {:?}, expected RSA or Ed25519", oid);
+    };
+
+    let jwk = Jwk {
+        common: CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            key_algorithm: Some(key_algorithm),
+            key_id: Some(kid.to_string()),
+            ..Default::default()
+        },
+        algorithm: parameters,
+    };
+    Ok((algorithm, jwk))
+}
+
+/// SEQUENCE { SEQUENCE { OID, params }, BIT STRING } -> (OID, содержимое BIT STRING)
+fn parse_spki(der: &[u8]) -> anyhow::Result<(Vec<u64>, Vec<u8>)> {
+    let blocks = from_der(der).map_err(|e| anyhow!("invalid public key DER: {e}"))?;
+    if let Some(ASN1Block::Sequence(_, spki)) = blocks.first()
+        && let [
+            ASN1Block::Sequence(_, algorithm),
+            ASN1Block::BitString(_, _, key),
+        ] = spki.as_slice()
+        && let Some(ASN1Block::ObjectIdentifier(_, oid)) = algorithm.first()
+    {
+        let oid = oid
+            .as_vec::<u64>()
+            .map_err(|e| anyhow!("invalid key OID: {e}"))?;
+        return Ok((oid, key.clone()));
+    }
+    bail!("public key is not a SubjectPublicKeyInfo")
+}
+
+/// RSAPublicKey ::= SEQUENCE { modulus INTEGER, publicExponent INTEGER }
+fn parse_rsa_public_key(der: &[u8]) -> anyhow::Result<(Vec<u8>, Vec<u8>)> {
+    let blocks = from_der(der).map_err(|e| anyhow!("invalid RSA key DER: {e}"))?;
+    if let Some(ASN1Block::Sequence(_, fields)) = blocks.first()
+        && let [ASN1Block::Integer(_, n), ASN1Block::Integer(_, e)] = fields.as_slice()
+    {
+        return Ok((n.to_bytes_be().1, e.to_bytes_be().1));
+    }
+    bail!("invalid RSA public key")
+}
@@ -1,4 +1,10 @@
 pub mod config;
+pub mod cursor;
 pub mod database;
+pub mod jwks;
 pub mod logging;
+pub mod mailer;
+pub mod oidc;
+pub mod rate_limit;
 pub mod security;
+pub mod totp;
@@ -0,0 +1,232 @@
+use anyhow::{Context, bail};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use jsonwebtoken::jwk::{Jwk, JwkSet};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header};
+use reqwest::Url;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::domain::oidc::ExternalIdentity;
+use crate::infrastructure::config::OidcConfig;
+
+const HTTP_TIMEOUT_SECS: u64 = 10;
+
+/// Нужная нам часть `/.well-known/openid-configuration`
+#[derive(Debug, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IdTokenClaims {
+    sub: String,
+    nonce: Option<String>,
+    email: Option<String>,
+    #[serde(default)]
+    email_verified: bool,
+    preferred_username: Option<String>,
+}
+
+/// Клиент провайдера: discovery и ключи запрашиваются при первом входе и кешируются
+pub struct OidcClient {
+    config: OidcConfig,
+    http: reqwest::Client,
+    metadata: RwLock<Option<Arc<ProviderMetadata>>>,
+    jwks: RwLock<Option<Arc<JwkSet>>>,
+}
+
+impl OidcClient {
+    pub fn new(config: OidcConfig) -> anyhow::Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(HTTP_TIMEOUT_SECS))
+            .redirect(reqwest::redirect::Policy::none())
+            .build()?;
+        Ok(Self {
+            config,
+            http,
+            metadata: RwLock::new(None),
+            jwks: RwLock::new(None),
+        })
+    }
+
+    /// Адрес страницы входа провайдера; `code_verifier` уходит туда только как S256-хеш
+    pub async fn authorization_url(
+        &self,
+        state: &str,
+        nonce: &str,
+        code_verifier: &str,
+    ) -> anyhow::Result<String> {
+        let metadata = self.metadata().await?;
+        let code_challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()));
+        let url = Url::parse_with_params(
+            &metadata.authorization_endpoint,
+            [
+                ("response_type", "code"),
+                ("client_id", self.config.client_id.as_str()),
+                ("redirect_uri", self.config.redirect_url.as_str()),
+                ("scope", self.config.scopes.as_str()),
+                ("state", state),
+                ("nonce", nonce),
+                ("code_challenge", code_challenge.as_str()),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .context("invalid authorization_endpoint")?;
+        Ok(url.into())
+    }
+
+    /// Обменивает `code` на ID-токен и возвращает пользователя из проверенного токена
+    pub async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> anyhow::Result<ExternalIdentity> {
+        let metadata = self.metadata().await?;
+
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.config.redirect_url.as_str()),
+            ("client_id", self.config.client_id.as_str()),
+            ("code_verifier", code_verifier),
+        ];
+        if let Some(secret) = &self.config.client_secret {
+            form.push(("client_secret", secret.as_str()));
+        }
+
+        let response: TokenResponse = self
+            .http
+            .post(&metadata.token_endpoint)
+            .form(&form)
+            .send()
+            .await?
+            .error_for_status()
+            .context("token endpoint rejected the code")?
+            .json()
+            .await
+            .context("invalid token response")?;
+        let id_token = response
+            .id_token
+            .context("token response without id_token")?;
+
+        self.validate_id_token(&id_token, nonce).await
+    }
+
+    async fn validate_id_token(
+        &self,
+        id_token: &str,
+        nonce: &str,
+    ) -> anyhow::Result<ExternalIdentity> {
+        let header = decode_header(id_token)?;
+        // Только асимметричная подпись ключом из JWKS: HS* подделал бы любой, кто знает client_secret
+        if !matches!(
+            header.alg,
+            Algorithm::RS256
+                | Algorithm::RS384
+                | Algorithm::RS512
+                | Algorithm::PS256
+                | Algorithm::PS384
+                | Algorithm::PS512
+                | Algorithm::ES256
+                | Algorithm::ES384
+                | Algorithm::EdDSA
+        ) {
+            bail!("unsupported id_token algorithm {:?}", header.alg);
+        }
+
+        let jwk = self.signing_key(header.kid.as_deref()).await?;
+        let key = DecodingKey::from_jwk(&jwk)?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&self.config.issuer]);
+        validation.set_audience(&[&self.config.client_id]);
+        validation.set_required_spec_claims(&["exp", "iat", "iss", "aud", "sub"]);
+        let claims = decode::<IdTokenClaims>(id_token, &key, &validation)?.claims;
+
+        // nonce связывает токен с этим входом: чужой перехваченный токен не подойдёт
+        if claims.nonce.as_deref() != Some(nonce) {
+            bail!("id_token nonce mismatch");
+        }
+
+        Ok(ExternalIdentity {
+            issuer: self.config.issuer.clone(),
+            subject: claims.sub,
+            email: claims.email,
+            email_verified: claims.email_verified,
+            preferred_username: claims.preferred_username,
+        })
+    }
+
+    async fn metadata(&self) -> anyhow::Result<Arc<ProviderMetadata>> {
+        if let Some(metadata) = self.metadata.read().unwrap().clone() {
+            return Ok(metadata);
+        }
+
+        let url = format!("{}/.well-known/openid-configuration", self.config.issuer);
+        let metadata: ProviderMetadata = self
+            .http
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .context("invalid discovery document")?;
+        // Провайдер обязан объявить ровно тот issuer, по которому его нашли
+        if metadata.issuer.trim_end_matches('/') != self.config.issuer {
+            bail!("discovery document is for issuer {}", metadata.issuer);
+        }
+
+        let metadata = Arc::new(metadata);
+        *self.metadata.write().unwrap() = Some(Arc::clone(&metadata));
+        Ok(metadata)
+    }
+
+    /// Ключ не нашёлся в кеше — возможно, провайдер их сменил: перечитываем JWKS один раз
+    async fn signing_key(&self, kid: Option<&str>) -> anyhow::Result<Jwk> {
+        for reload in [false, true] {
+            let jwks = self.jwks(reload).await?;
+            let key = match kid {
+                Some(kid) => jwks.find(kid),
+                None => jwks.keys.first(),
+            };
+            if let Some(key) = key {
+                return Ok(key.clone());
+            }
+        }
+        bail!("unknown id_token signing key {:?}", kid)
+    }
+
+    async fn jwks(&self, reload: bool) -> anyhow::Result<Arc<JwkSet>> {
+        if !reload && let Some(jwks) = self.jwks.read().unwrap().clone() {
+            return Ok(jwks);
+        }
+
+        let metadata = self.metadata().await?;
+        let jwks: JwkSet = self
+            .http
+            .get(&metadata.jwks_uri)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .context("invalid jwks document")?;
+
+        let jwks = Arc::new(jwks);
+        *self.jwks.write().unwrap() = Some(Arc::clone(&jwks));
+        Ok(jwks)
+    }
+}
@@ -0,0 +1,143 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::infrastructure::config::{AppConfig, RateLimitStoreKind};
+
+/// Хранилище вёдер токенов. Ведро вмещает `per_minute` запросов и пополняется
+/// с той же скоростью, так что всплеск допустим, но средний темп ограничен.
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Забирает токен из ведра `key`; `Some(секунды)` — ведро пустое, повторить можно через столько
+    async fn take(&self, key: &str, per_minute: u32) -> anyhow::Result<Option<u64>>;
+    /// Удаляет вёдра, к которым давно не обращались: они всё равно уже полные
+    async fn purge_idle(&self, idle: Duration) -> anyhow::Result<()>;
+}
+
+pub fn rate_limit_store_from_config(config: &AppConfig, pool: PgPool) -> Box<dyn RateLimitStore> {
+    match config.rate_limit_store {
+        RateLimitStoreKind::Memory => Box::new(InMemoryRateLimitStore::default()),
+        RateLimitStoreKind::Postgres => Box::new(PostgresRateLimitStore::new(pool)),
+    }
+}
+
+fn refill_per_sec(per_minute: u32) -> f64 {
+    f64::from(per_minute) / 60.0
+}
+
+/// Сколько ждать, пока в ведре с `tokens` наберётся целый токен
+fn retry_after(tokens: f64, per_minute: u32) -> u64 {
+    ((1.0 - tokens) / refill_per_sec(per_minute))
+        .ceil()
+        .max(1.0) as u64
+}
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+/// Вёдра в памяти процесса: при нескольких инстансах у каждого свой бюджет
+#[derive(Default)]
+pub struct InMemoryRateLimitStore {
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+#[async_trait]
+impl RateLimitStore for InMemoryRateLimitStore {
+    async fn take(&self, key: &str, per_minute: u32) -> anyhow::Result<Option<u64>> {
+        let capacity = f64::from(per_minute);
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            updated_at: now,
+        });
+
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * refill_per_sec(per_minute)).min(capacity);
+        bucket.updated_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(None)
+        } else {
+            Ok(Some(retry_after(bucket.tokens, per_minute)))
+        }
+    }
+
+    async fn purge_idle(&self, idle: Duration) -> anyhow::Result<()> {
+        self.buckets
+            .lock()
+            .unwrap()
+            .retain(|_, bucket| bucket.updated_at.elapsed() < idle);
+        Ok(())
+    }
+}
+
+/// Вёдра в таблице `rate_limit_buckets`, общие для всех инстансов
+pub struct PostgresRateLimitStore {
+    pool: PgPool,
+}
+
+impl PostgresRateLimitStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for PostgresRateLimitStore {
+    async fn take(&self, key: &str, per_minute: u32) -> anyhow::Result<Option<u64>> {
+        let capacity = f64::from(per_minute);
+        let rate = refill_per_sec(per_minute);
+
+        // Пополнение и списание одним запросом: строка блокируется на время UPDATE,
+        // поэтому параллельные инстансы не спишут один и тот же токен дважды.
+        // Пустое ведро не трогаем — WHERE не пропустит, и строка не вернётся.
+        let taken = sqlx::query_scalar::<_, f64>(
+            r#"
+            INSERT INTO rate_limit_buckets AS b (key, tokens, updated_at)
+            VALUES ($1, $2 - 1, NOW())
+            ON CONFLICT (key) DO UPDATE
+                SET tokens     = LEAST($2, b.tokens + EXTRACT(EPOCH FROM NOW() - b.updated_at)::float8 * $3) - 1,
+                    updated_at = NOW()
+            WHERE LEAST($2, b.tokens + EXTRACT(EPOCH FROM NOW() - b.updated_at)::float8 * $3) >= 1
+            RETURNING tokens
+            "#,
+        )
+        .bind(key)
+        .bind(capacity)
+        .bind(rate)
+        .fetch_optional(&self.pool)
+        .await?;
+        if taken.is_some() {
+            return Ok(None);
+        }
+
+        let tokens = sqlx::query_scalar::<_, f64>(
+            r#"
+            SELECT LEAST($2, tokens + EXTRACT(EPOCH FROM NOW() - updated_at)::float8 * $3)
+            FROM rate_limit_buckets
+            WHERE key = $1
+            "#,
+        )
+        .bind(key)
+        .bind(capacity)
+        .bind(rate)
+        .fetch_optional(&self.pool)
+        .await?
+        .unwrap_or(0.0);
+        Ok(Some(retry_after(tokens, per_minute)))
+    }
+
+    async fn purge_idle(&self, idle: Duration) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM rate_limit_buckets WHERE updated_at < NOW() - $1")
+            .bind(chrono::Duration::from_std(idle)?)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
@@ -1,50 +1,180 @@
+use crate::domain::user::Role;
+use crate::infrastructure::jwks::public_key_to_jwk;
+use anyhow::{Context, bail};
 use argon2::{
     Argon2,
     password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
 };
-use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
-use rand_core::OsRng;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, decode_header, encode,
+};
+use rand_core::{OsRng, RngCore};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::Arc;
 use uuid::Uuid;
 
+const OPAQUE_TOKEN_BYTES: usize = 32;
+
+#[derive(Clone)]
+struct SigningKey {
+    kid: Option<String>,
+    algorithm: Algorithm,
+    key: EncodingKey,
+}
+
+#[derive(Clone)]
+struct VerifyingKey {
+    kid: Option<String>,
+    algorithm: Algorithm,
+    key: DecodingKey,
+}
+
+/// Подпись и проверка JWT. Либо общий HMAC-секрет, либо асимметричные ключи из каталога:
+/// токены подписываются активным ключом, а проверяются любым из опубликованных по `kid`.
 #[derive(Clone)]
 pub struct JwtKeys {
-    secret: String,
+    signing: SigningKey,
+    verifying: Arc<Vec<VerifyingKey>>,
+    jwks: Arc<JwkSet>,
+    access_ttl_secs: i64,
 }
 
 impl JwtKeys {
-    pub fn new(secret: String) -> Self {
-        Self { secret }
+    /// HS256 с общим секретом; JWKS при этом пустой
+    pub fn new(secret: String, access_ttl_secs: i64) -> Self {
+        Self {
+            signing: SigningKey {
+                kid: None,
+                algorithm: Algorithm::HS256,
+                key: EncodingKey::from_secret(secret.as_bytes()),
+            },
+            verifying: Arc::new(vec![VerifyingKey {
+                kid: None,
+                algorithm: Algorithm::HS256,
+                key: DecodingKey::from_secret(secret.as_bytes()),
+            }]),
+            jwks: Arc::new(JwkSet { keys: Vec::new() }),
+            access_ttl_secs,
+        }
+    }
+
+    /// Каталог ключей: `<kid>.pem` — открытые ключи (RSA или Ed25519), все принимаются
+    /// при проверке и публикуются в JWKS; `<signing_kid>.key` — закрытый ключ для подписи.
+    pub fn from_pem_dir(
+        dir: &Path,
+        signing_kid: &str,
+        access_ttl_secs: i64,
+    ) -> anyhow::Result<Self> {
+        let mut verifying = Vec::new();
+        let mut jwks = Vec::new();
+
+        for entry in std::fs::read_dir(dir).with_context(|| format!("reading {}", dir.display()))? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("pem") {
+                continue;
+            }
+            let Some(kid) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+
+            let pem =
+                std::fs::read(&path).with_context(|| format!("reading {}", path.display()))?;
+            let (algorithm, jwk) = public_key_to_jwk(kid, &pem)
+                .with_context(|| format!("loading {}", path.display()))?;
+            verifying.push(VerifyingKey {
+                kid: Some(kid.to_string()),
+                algorithm,
+                key: DecodingKey::from_jwk(&jwk)?,
+            });
+            jwks.push(jwk);
+        }
+        jwks.sort_by(|a, b| a.common.key_id.cmp(&b.common.key_id));
+
+        let Some(algorithm) = verifying
+            .iter()
+            .find(|key| key.kid.as_deref() == Some(signing_kid))
+            .map(|key| key.algorithm)
+        else {
+            bail!(
+                "public key {signing_kid}.pem not found in {}",
+                dir.display()
+            );
+        };
+
+        let private_path = dir.join(format!("{signing_kid}.key"));
+        let private_pem = std::fs::read(&private_path)
+            .with_context(|| format!("reading {}", private_path.display()))?;
+        let key = match algorithm {
+            Algorithm::EdDSA => EncodingKey::from_ed_pem(&private_pem)?,
+            _ => EncodingKey::from_rsa_pem(&private_pem)?,
+        };
+
+        let keys = Self {
+            signing: SigningKey {
+                kid: Some(signing_kid.to_string()),
+                algorithm,
+                key,
+            },
+            verifying: Arc::new(verifying),
+            jwks: Arc::new(JwkSet { keys: jwks }),
+            access_ttl_secs,
+        };
+
+        // Пара ключей должна сходиться, иначе сервер выдавал бы непроверяемые токены
+        let probe = keys.generate_token(Uuid::nil(), Role::User, Uuid::nil())?;
+        keys.verify_token(&probe)
+            .with_context(|| format!("{signing_kid}.key does not match {signing_kid}.pem"))?;
+
+        Ok(keys)
+    }
+
+    /// Время жизни access-токена в секундах
+    pub fn access_ttl(&self) -> i64 {
+        self.access_ttl_secs
     }
 
-    pub fn generate_token(&self, user_id: Uuid) -> Result<String, jsonwebtoken::errors::Error> {
-        dotenvy::dotenv().ok();
-        let expiration_time: i64 = std::env::var("ACCESS_TOKEN_EXPIRATION_SECS")
-            .expect("Missing access token expiration time")
-            .parse()
-            .expect("ACCESS_TOKEN_EXPIRATION_SECS must be an integer");
+    /// Открытые ключи для `/.well-known/jwks.json`
+    pub fn jwks(&self) -> &JwkSet {
+        &self.jwks
+    }
 
+    pub fn generate_token(
+        &self,
+        user_id: Uuid,
+        role: Role,
+        session_id: Uuid,
+    ) -> Result<String, jsonwebtoken::errors::Error> {
         let claims = Claims {
             sub: user_id.to_string(),
             exp: chrono::Utc::now()
-                .checked_add_signed(chrono::Duration::seconds(expiration_time))
+                .checked_add_signed(chrono::Duration::seconds(self.access_ttl_secs))
                 .unwrap()
                 .timestamp() as usize,
             iat: chrono::Utc::now().timestamp() as usize,
+            jti: Uuid::new_v4().to_string(),
+            sid: session_id.to_string(),
+            role,
         };
-        encode(
-            &Header::default(),
-            &claims,
-            &EncodingKey::from_secret(self.secret.as_bytes()),
-        )
+        let mut header = Header::new(self.signing.algorithm);
+        header.kid = self.signing.kid.clone();
+        encode(&header, &claims, &self.signing.key)
     }
 
     pub fn verify_token(&self, token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
-        let data = decode::<Claims>(
-            token,
-            &DecodingKey::from_secret(self.secret.as_bytes()),
-            &Validation::default(),
-        )?;
+        let header = decode_header(token)?;
+        let key = self
+            .verifying
+            .iter()
+            .find(|key| key.kid == header.kid && key.algorithm == header.alg)
+            .ok_or(ErrorKind::InvalidToken)?;
+
+        let data = decode::<Claims>(token, &key.key, &Validation::new(key.algorithm))?;
         Ok(data.claims)
     }
 }
@@ -54,6 +184,13 @@ pub struct Claims {
     pub sub: String,
     pub exp: usize,
     pub iat: usize,
+    /// Идентификатор токена — по нему токен отзывается до истечения `exp`
+    pub jti: String,
+    /// Сессия, в рамках которой выдан токен
+    pub sid: String,
+    /// Роль на момент выдачи — для клиентов; права сервер проверяет по текущей роли в БД
+    #[serde(default)]
+    pub role: Role,
 }
 
 pub fn hash_password(password: &str) -> Result<String, argon2::password_hash::Error> {
@@ -70,3 +207,15 @@ pub fn verify_password(password: &str, hash: &str) -> Result<bool, argon2::passw
     let argon2 = Argon2::default();
     Ok(argon2.verify_password(password.as_bytes(), &parsed).is_ok())
 }
+
+/// Случайный непрозрачный токен (refresh и т.п.), отдаётся клиенту как есть
+pub fn generate_opaque_token() -> String {
+    let mut bytes = [0u8; OPAQUE_TOKEN_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// В базе храним только sha256 от токена: утечка таблицы не даёт рабочих токенов
+pub fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
@@ -0,0 +1,79 @@
+use chrono::{DateTime, Utc};
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use rand_core::{OsRng, RngCore};
+use sha1::Sha1;
+
+type HmacSha1 = Hmac<Sha1>;
+
+const ISSUER: &str = "rust-blog";
+const SECRET_BYTES: usize = 20;
+const STEP_SECS: i64 = 30;
+const DIGITS: usize = 6;
+/// Допуск на расхождение часов: соседний шаг до и после текущего
+const ALLOWED_SKEW: i64 = 1;
+const RECOVERY_CODE_BYTES: usize = 5;
+
+/// Новый секрет в base32 — в таком виде его принимают приложения-аутентификаторы
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; SECRET_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    BASE32_NOPAD.encode(&bytes)
+}
+
+/// URI для QR-кода (Google Authenticator и совместимые)
+pub fn otpauth_uri(secret: &str, account: &str) -> String {
+    let label = format!("{ISSUER}:{account}");
+    format!(
+        "otpauth://totp/{}?secret={secret}&issuer={}&algorithm=SHA1&digits={DIGITS}&period={STEP_SECS}",
+        utf8_percent_encode(&label, NON_ALPHANUMERIC),
+        utf8_percent_encode(ISSUER, NON_ALPHANUMERIC),
+    )
+}
+
+/// Проверяет код по RFC 6238 и возвращает номер совпавшего шага:
+/// по нему отсекается повторное использование того же кода
+pub fn verify(secret: &str, code: &str, now: DateTime<Utc>) -> Option<i64> {
+    let code = code.trim();
+    if code.len() != DIGITS || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let key = BASE32_NOPAD.decode(secret.as_bytes()).ok()?;
+
+    let current = now.timestamp() / STEP_SECS;
+    (current - ALLOWED_SKEW..=current + ALLOWED_SKEW)
+        .find(|&step| format!("{:0DIGITS$}", code_at(&key, step)) == code)
+}
+
+fn code_at(key: &[u8], step: i64) -> u32 {
+    let mut mac = HmacSha1::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(&step.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+
+    // Динамическое усечение из RFC 4226
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        hash[offset] & 0x7f,
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]);
+    binary % 10u32.pow(DIGITS as u32)
+}
+
+/// Одноразовый код восстановления вида `abcd-efgh`
+pub fn generate_recovery_code() -> String {
+    let mut bytes = [0u8; RECOVERY_CODE_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    let code = BASE32_NOPAD.encode(&bytes).to_lowercase();
+    format!("{}-{}", &code[..4], &code[4..])
+}
+
+/// Приводит введённый код восстановления к виду, от которого считается хэш
+pub fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
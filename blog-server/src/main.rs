@@ -5,17 +5,35 @@ mod infrastructure;
 pub mod presentation;
 mod utils;
 
+use crate::application::audit_log::AuditLog;
+use crate::application::comment_service::CommentService;
+use crate::application::outbox_service::OutboxService;
 use crate::application::post_service::PostService;
+use crate::application::rate_limiter::RateLimiter;
+use crate::data::audit_repository::PostgresAuditRepository;
+use crate::data::comment_repository::PostgresCommentRepository;
+use crate::data::login_throttle_repository::PostgresLoginThrottleRepository;
+use crate::data::outbox_repository::PostgresOutboxRepository;
 use crate::data::post_repository::PostgresPostRepository;
-use crate::utils::{start_grpc_server, start_rest_server};
+use crate::data::token_repository::PostgresTokenRepository;
+use crate::utils::{
+    start_grpc_server, start_outbox_worker, start_post_publisher, start_rest_server,
+    start_trash_purger,
+};
 use application::auth_service::AuthService;
 use data::user_repository::PostgresUserRepository;
 use infrastructure::config::AppConfig;
+use infrastructure::cursor::CursorCodec;
 use infrastructure::database::{create_pool, run_migrations};
 use infrastructure::logging::init_logging;
+use infrastructure::mailer::mailer_from_config;
+use infrastructure::oidc::OidcClient;
+use infrastructure::rate_limit::rate_limit_store_from_config;
 use infrastructure::security::JwtKeys;
 
+use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 
 pub mod blog {
     tonic::include_proto!("blog");
@@ -35,18 +53,91 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let user_repo = Arc::new(PostgresUserRepository::new(pool.clone()));
     let post_repo = Arc::new(PostgresPostRepository::new(pool.clone()));
+    let comment_repo = Arc::new(PostgresCommentRepository::new(pool.clone()));
+    let token_repo = Arc::new(PostgresTokenRepository::new(pool.clone()));
+    let outbox_repo = Arc::new(PostgresOutboxRepository::new(pool.clone()));
+    let audit_log = Arc::new(AuditLog::new(Arc::new(PostgresAuditRepository::new(
+        pool.clone(),
+    ))));
 
+    let keys = jwt_keys(&config);
     let auth_service = Arc::new(AuthService::new(
         Arc::clone(&user_repo),
-        JwtKeys::new(config.jwt_secret.clone()),
+        Arc::clone(&token_repo),
+        Arc::new(PostgresLoginThrottleRepository::new(pool.clone())),
+        keys.clone(),
+        config.refresh_token_ttl_secs,
+        config.app_url.clone(),
+        config
+            .oidc
+            .clone()
+            .map(OidcClient::new)
+            .transpose()
+            .expect("invalid OIDC configuration"),
+        Arc::clone(&audit_log),
     ));
 
-    let post_service = Arc::new(PostService::new(Arc::clone(&post_repo)));
+    let post_service = Arc::new(PostService::new(
+        Arc::clone(&post_repo),
+        CursorCodec::new(config.page_token_secret.clone()),
+        Arc::clone(&audit_log),
+    ));
+    let comment_service = Arc::new(CommentService::new(
+        Arc::clone(&comment_repo),
+        Arc::clone(&post_repo),
+    ));
+
+    let outbox_service = Arc::new(OutboxService::new(
+        outbox_repo,
+        mailer_from_config(&config).expect("invalid mailer configuration"),
+    ));
+
+    let rate_limiter = Arc::new(RateLimiter::new(
+        rate_limit_store_from_config(&config, pool.clone()),
+        keys,
+        config.rate_limit_auth_per_min,
+        config.rate_limit_write_per_min,
+        config.rate_limit_read_per_min,
+    ));
 
     tokio::try_join!(
-        start_rest_server(auth_service.clone(), post_service.clone()),
-        start_grpc_server(auth_service, post_service),
+        start_rest_server(
+            auth_service.clone(),
+            post_service.clone(),
+            comment_service.clone(),
+            rate_limiter.clone(),
+            audit_log,
+        ),
+        start_grpc_server(
+            auth_service,
+            post_service.clone(),
+            comment_service,
+            rate_limiter,
+        ),
+        start_post_publisher(
+            post_service.clone(),
+            Duration::from_secs(config.publisher_interval_secs),
+        ),
+        start_trash_purger(
+            post_service,
+            Duration::from_secs(config.trash_purge_interval_secs),
+            chrono::Duration::days(config.trash_retention_days),
+        ),
+        start_outbox_worker(
+            outbox_service,
+            Duration::from_secs(config.outbox_interval_secs),
+        ),
     )?;
 
     Ok(())
 }
+
+fn jwt_keys(config: &AppConfig) -> JwtKeys {
+    match (&config.jwt_keys_dir, &config.jwt_signing_kid) {
+        (Some(dir), Some(kid)) => {
+            JwtKeys::from_pem_dir(Path::new(dir), kid, config.access_token_ttl_secs)
+                .expect("failed to load JWT keys")
+        }
+        _ => JwtKeys::new(config.jwt_secret.clone(), config.access_token_ttl_secs),
+    }
+}
@@ -1,15 +1,12 @@
-use crate::domain::post::Post;
+use crate::domain::mfa::LoginOutcome;
+use crate::domain::personal_token::{PersonalAccessToken, Scope};
+use crate::domain::post::{Post, PostStatus};
+use crate::domain::token::TokenPair;
+use crate::domain::user::{Role, User};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Deserialize)]
-pub struct Pagination {
-    #[serde(default)]
-    pub limit: Option<usize>,
-    #[serde(default)]
-    pub offset: Option<usize>,
-}
-
 #[derive(Debug, Deserialize)]
 pub struct RegisterRequest {
     pub username: String,
@@ -29,6 +26,177 @@ pub struct AuthResponse {
     pub expires_in: i64,
     #[serde(rename = "token_type")]
     pub token_type: String, // "Bearer"
+    pub refresh_token: String,
+}
+
+impl From<TokenPair> for AuthResponse {
+    fn from(pair: TokenPair) -> Self {
+        Self {
+            access_token: pair.access_token,
+            expires_in: pair.expires_in,
+            token_type: "Bearer".to_string(),
+            refresh_token: pair.refresh_token,
+        }
+    }
+}
+
+/// Ответ на логин: токены или, при включённом TOTP, challenge для второго шага
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum LoginResponse {
+    Tokens(AuthResponse),
+    MfaRequired {
+        mfa_required: bool,
+        mfa_token: String,
+        expires_in: i64,
+    },
+}
+
+impl From<LoginOutcome> for LoginResponse {
+    fn from(outcome: LoginOutcome) -> Self {
+        match outcome {
+            LoginOutcome::Tokens(pair) => LoginResponse::Tokens(pair.into()),
+            LoginOutcome::MfaRequired(challenge) => LoginResponse::MfaRequired {
+                mfa_required: true,
+                mfa_token: challenge.mfa_token,
+                expires_in: challenge.expires_in,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyMfaRequest {
+    pub mfa_token: String,
+    /// Код из приложения или код восстановления
+    pub code: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TotpCodeRequest {
+    pub code: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ForgotPasswordRequest {
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    pub password: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyEmailRequest {
+    pub token: String,
+}
+
+/// Возврат с провайдера: `code` и `state` или `error`, если вход не состоялся
+#[derive(Debug, Deserialize)]
+pub struct OidcCallbackQuery {
+    #[serde(default)]
+    pub code: Option<String>,
+    #[serde(default)]
+    pub state: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct LogoutRequest {
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreatePersonalTokenRequest {
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    /// Без срока токен действует до отзыва
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Ответ на создание токена — единственный раз, когда виден сам секрет
+#[derive(Debug, Serialize)]
+pub struct PersonalTokenResponse {
+    #[serde(flatten)]
+    pub details: PersonalAccessToken,
+    pub token: String,
+}
+
+// ======================= ACCOUNT =======================
+
+/// Профиль без секретов (хеша пароля, TOTP-секрета)
+#[derive(Debug, Serialize)]
+pub struct AccountResponse {
+    pub id: Uuid,
+    pub username: String,
+    pub email: String,
+    pub role: Role,
+    pub email_verified: bool,
+    pub totp_enabled: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<User> for AccountResponse {
+    fn from(user: User) -> Self {
+        Self {
+            id: user.id,
+            totp_enabled: user.totp_enabled(),
+            email_verified: user.email_verified_at.is_some(),
+            role: user.role,
+            username: user.username,
+            email: user.email,
+            created_at: user.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChangeEmailRequest {
+    pub email: String,
+    pub current_password: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChangeUsernameRequest {
+    pub username: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetRoleRequest {
+    pub role: Role,
+}
+
+/// Отбор по тому, кто действовал и над чем, и по времени `[from, to)`
+#[derive(Debug, Deserialize)]
+pub struct AuditLogQuery {
+    #[serde(default)]
+    pub actor_id: Option<Uuid>,
+    #[serde(default)]
+    pub target_id: Option<Uuid>,
+    #[serde(default)]
+    pub from: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub to: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub before_id: Option<i64>,
+    #[serde(default)]
+    pub limit: Option<i64>,
 }
 
 // ======================= POSTS =======================
@@ -37,29 +205,85 @@ pub struct AuthResponse {
 pub struct CreatePostRequest {
     pub title: String,
     pub content: String,
+    #[serde(default)]
+    pub status: Option<PostStatus>,
+    #[serde(default)]
+    pub publish_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct UpdatePostRequest {
     pub title: Option<String>,
     pub content: Option<String>,
+    #[serde(default)]
+    pub publish_at: Option<DateTime<Utc>>,
+    /// `None` — не трогать теги, `Some(vec![])` — удалить все
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    /// Версия, которую правил клиент (`If-Match` / `expected_version`); `None` — без проверки
+    #[serde(skip)]
+    pub expected_version: Option<i32>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    pub q: String,
+    #[serde(default)]
+    pub limit: Option<usize>,
+    #[serde(default)]
+    pub offset: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RevisionDiffQuery {
+    pub from: i32,
+    /// Без `to` ревизия сравнивается с текущей версией поста
+    #[serde(default)]
+    pub to: Option<i32>,
+}
+
+/// Курсорный режим — `page_size` + `page_token` из предыдущего ответа;
+/// `limit`/`offset` оставлены для старых клиентов
 #[derive(Debug, Deserialize)]
 pub struct ListPostsQuery {
-    #[serde(default = "default_page_size")]
-    pub page_size: u32,
+    #[serde(default)]
+    pub page_size: Option<usize>,
+    #[serde(default)]
     pub page_token: Option<String>,
+    #[serde(default)]
     pub author_id: Option<Uuid>,
+    #[serde(default)]
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub created_after: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub created_before: Option<DateTime<Utc>>,
+    /// Подстрока заголовка
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+    #[serde(default)]
+    pub offset: Option<usize>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct ListPostsResponse {
     pub posts: Vec<Post>,
     pub next_page_token: Option<String>,
+    pub total: i64,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
 }
 
-// ======================= Utils =======================
-fn default_page_size() -> u32 {
-    20
+// ======================= COMMENTS =======================
+
+#[derive(Debug, Deserialize)]
+pub struct CreateCommentRequest {
+    pub content: String,
+    /// Ответ на другой комментарий того же поста
+    #[serde(default)]
+    pub parent_id: Option<Uuid>,
 }
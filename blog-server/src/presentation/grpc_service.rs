@@ -1,18 +1,44 @@
 use crate::application::auth_service::AuthService;
+use crate::application::comment_service::CommentService;
+use crate::application::policy::Actor;
 use crate::application::post_service::PostService;
 use crate::blog::blog_service_server::BlogService;
 use crate::blog::{
-    AuthResponse, CreatePostRequest as ProtoCreatePostRequest, DeletePostRequest, GetPostRequest,
-    ListPostsRequest, ListPostsResponse, LoginRequest as ProtoLoginRequest, Post as ProtoPost,
-    RegisterRequest as ProtoRegisterRequest, UpdatePostRequest as ProtoUpdatePostRequest,
+    Account, ArchivePostRequest, AuthResponse, ChangeEmailRequest, ChangePasswordRequest,
+    ChangeUsernameRequest, Comment as ProtoComment,
+    CreateCommentRequest as ProtoCreateCommentRequest, CreatePostRequest as ProtoCreatePostRequest,
+    DeleteCommentRequest, DeletePostRequest, DiffPostRevisionsRequest, GetPostBySlugRequest,
+    GetPostRequest, GetPostRevisionRequest, ListCommentsRequest, ListCommentsResponse,
+    ListPostRevisionsRequest, ListPostRevisionsResponse, ListPostsRequest, ListPostsResponse,
+    ListSessionsResponse, ListTagsResponse, ListTrashedPostsResponse,
+    LoginRequest as ProtoLoginRequest, LogoutRequest, Post as ProtoPost,
+    PostRevision as ProtoPostRevision, PostRevisionDiff, PostStatus as ProtoPostStatus,
+    PublishPostRequest, RefreshTokenRequest, RegisterRequest as ProtoRegisterRequest,
+    RestorePostRequest, RestorePostRevisionRequest, RevokeSessionRequest, SearchPostsRequest,
+    SearchPostsResponse, SearchResult as ProtoSearchResult, Session as ProtoSession,
+    SetCommentHiddenRequest, SetPostHiddenRequest, SetUserRoleRequest, Tag as ProtoTag,
+    ToggleReactionRequest, UnpublishPostRequest, UpdatePostRequest as ProtoUpdatePostRequest,
+    VerifyMfaRequest,
 };
+use crate::data::comment_repository::CommentRepository;
 use crate::data::post_repository::PostRepository;
+use crate::data::token_repository::TokenRepository;
 use crate::data::user_repository::UserRepository;
+use crate::domain::comment::Comment;
 use crate::domain::error::DomainError;
-use crate::domain::post::Post;
-use crate::infrastructure::security::Claims;
+use crate::domain::mfa::LoginOutcome;
+use crate::domain::personal_token::{Credential, Scope};
+use crate::domain::post::{Post, PostFilter, PostPage, PostStatus};
+use crate::domain::reaction::ReactionKind;
+use crate::domain::revision::{PostRevision, RevisionDiff};
+use crate::domain::search::SearchResult;
+use crate::domain::session::{ClientInfo, Session};
+use crate::domain::slug::SlugLookup;
+use crate::domain::tag::TagCount;
+use crate::domain::token::TokenPair;
+use crate::domain::user::{Role, User};
 use crate::presentation::dto::{
-    CreatePostRequest, LoginRequest, RegisterRequest, UpdatePostRequest,
+    CreateCommentRequest, CreatePostRequest, LoginRequest, RegisterRequest, UpdatePostRequest,
 };
 use chrono::{DateTime, Utc};
 use prost_types::Timestamp;
@@ -21,38 +47,104 @@ use tonic::{Request, Response, Status};
 use uuid::Uuid;
 
 #[derive(Clone)]
-pub struct BlogGrpcService<R, T>
+pub struct BlogGrpcService<R, K, T, C>
 where
     R: UserRepository + Send + Sync + 'static,
+    K: TokenRepository + Send + Sync + 'static,
     T: PostRepository + Send + Sync + 'static,
+    C: CommentRepository + Send + Sync + 'static,
 {
-    auth_service: Arc<AuthService<R>>,
+    auth_service: Arc<AuthService<R, K>>,
     post_service: Arc<PostService<T>>,
+    comment_service: Arc<CommentService<C, T>>,
 }
 
-impl<R, T> BlogGrpcService<R, T>
+impl<R, K, T, C> BlogGrpcService<R, K, T, C>
 where
     R: UserRepository + Send + Sync + 'static,
+    K: TokenRepository + Send + Sync + 'static,
     T: PostRepository + Send + Sync + 'static,
+    C: CommentRepository + Send + Sync + 'static,
 {
-    pub fn new(auth_service: Arc<AuthService<R>>, post_service: Arc<PostService<T>>) -> Self {
+    pub fn new(
+        auth_service: Arc<AuthService<R, K>>,
+        post_service: Arc<PostService<T>>,
+        comment_service: Arc<CommentService<C, T>>,
+    ) -> Self {
         Self {
             auth_service,
             post_service,
+            comment_service,
         }
     }
+
+    async fn credentials<M>(&self, request: &Request<M>) -> Result<(Actor, Credential), Status> {
+        let token = extract_token_from_request(request)?;
+        let (user, credential) = self
+            .auth_service
+            .authenticate(&token)
+            .await
+            .map_err(|_| Status::unauthenticated("Invalid or expired token"))?;
+
+        Ok((Actor::new(user.id, user.role), credential))
+    }
+
+    /// Все RPC с обязательной авторизацией меняют посты или комментарии
+    async fn authenticate<M>(&self, request: &Request<M>) -> Result<Actor, Status> {
+        let (actor, credential) = self.credentials(request).await?;
+        if !credential.allows(Scope::PostsWrite) {
+            return Err(Status::permission_denied("Token lacks posts:write scope"));
+        }
+        Ok(actor)
+    }
+
+    /// Для публичных RPC: токен необязателен, но если он валиден — открывает черновики автора
+    async fn viewer<M>(&self, request: &Request<M>) -> Option<Uuid> {
+        self.credentials(request)
+            .await
+            .ok()
+            .filter(|(_, credential)| credential.allows(Scope::PostsRead))
+            .map(|(actor, _)| actor.id)
+    }
+
+    async fn change_status(
+        &self,
+        actor: Actor,
+        post_id: &str,
+        status: PostStatus,
+    ) -> Result<Response<ProtoPost>, Status> {
+        let post_id = parse_post_id(post_id)?;
+
+        let post = self
+            .post_service
+            .change_status(actor, post_id, status)
+            .await
+            .map_err(map_domain_error_to_status)?;
+
+        tracing::info!(
+            "Changed post status: user_id={}, post_id={}, status={:?}",
+            actor.id,
+            post.id,
+            post.status
+        );
+
+        Ok(Response::new(post.into()))
+    }
 }
 
 #[tonic::async_trait]
-impl<R, T> BlogService for BlogGrpcService<R, T>
+impl<R, K, T, C> BlogService for BlogGrpcService<R, K, T, C>
 where
     R: UserRepository + Send + Sync + 'static,
+    K: TokenRepository + Send + Sync + 'static,
     T: PostRepository + Send + Sync + 'static,
+    C: CommentRepository + Send + Sync + 'static,
 {
     async fn register(
         &self,
         request: Request<ProtoRegisterRequest>,
     ) -> Result<Response<AuthResponse>, Status> {
+        let client = client_info(&request);
         let req = request.into_inner();
 
         // Базовая валидация
@@ -68,29 +160,26 @@ where
 
         let user = self
             .auth_service
-            .register(&req.into())
+            .register(&req.into(), &client)
             .await
             .map_err(map_domain_error_to_status)?;
 
-        let token = self
+        let tokens = self
             .auth_service
-            .keys()
-            .generate_token(user.id)
-            .map_err(|e| Status::internal(format!("JWT generation failed: {e}")))?;
+            .issue_tokens(user.id, &client)
+            .await
+            .map_err(map_domain_error_to_status)?;
 
         tracing::info!("Registered new user: {} ({})", user.username, user.email);
 
-        Ok(Response::new(AuthResponse {
-            access_token: token,
-            expires_in: 3600,
-            token_type: "Bearer".to_string(),
-        }))
+        Ok(Response::new(tokens.into()))
     }
 
     async fn login(
         &self,
         request: Request<ProtoLoginRequest>,
     ) -> Result<Response<AuthResponse>, Status> {
+        let client = client_info(&request);
         let req = request.into_inner();
 
         if req.username.len() < 6 {
@@ -100,37 +189,204 @@ where
             return Err(Status::invalid_argument("Password must be ≥8 chars"));
         }
 
-        let token = self
+        let outcome = self
             .auth_service
-            .login(&req.into())
+            .login(&req.into(), &client)
             .await
             .map_err(map_domain_error_to_status)?;
 
-        Ok(Response::new(AuthResponse {
-            access_token: token,
-            expires_in: 3600,
-            token_type: "Bearer".to_string(),
+        Ok(Response::new(outcome.into()))
+    }
+
+    async fn verify_mfa(
+        &self,
+        request: Request<VerifyMfaRequest>,
+    ) -> Result<Response<AuthResponse>, Status> {
+        let client = client_info(&request);
+        let req = request.into_inner();
+
+        let tokens = self
+            .auth_service
+            .verify_mfa(&req.mfa_token, &req.code, &client)
+            .await
+            .map_err(map_domain_error_to_status)?;
+
+        Ok(Response::new(tokens.into()))
+    }
+
+    async fn refresh_token(
+        &self,
+        request: Request<RefreshTokenRequest>,
+    ) -> Result<Response<AuthResponse>, Status> {
+        let client = client_info(&request);
+        let req = request.into_inner();
+
+        let tokens = self
+            .auth_service
+            .refresh(&req.refresh_token, &client)
+            .await
+            .map_err(map_domain_error_to_status)?;
+
+        Ok(Response::new(tokens.into()))
+    }
+
+    async fn logout(&self, request: Request<LogoutRequest>) -> Result<Response<()>, Status> {
+        let token = extract_token_from_request(&request)?;
+        let req = request.into_inner();
+        let refresh_token = Some(req.refresh_token.as_str()).filter(|t| !t.is_empty());
+
+        self.auth_service
+            .logout(&token, refresh_token)
+            .await
+            .map_err(map_domain_error_to_status)?;
+
+        Ok(Response::new(()))
+    }
+
+    async fn logout_all(&self, request: Request<()>) -> Result<Response<()>, Status> {
+        let token = extract_token_from_request(&request)?;
+
+        self.auth_service
+            .logout_all(&token)
+            .await
+            .map_err(map_domain_error_to_status)?;
+
+        Ok(Response::new(()))
+    }
+
+    async fn list_sessions(
+        &self,
+        request: Request<()>,
+    ) -> Result<Response<ListSessionsResponse>, Status> {
+        let token = extract_token_from_request(&request)?;
+
+        let sessions = self
+            .auth_service
+            .list_sessions(&token)
+            .await
+            .map_err(map_domain_error_to_status)?;
+
+        Ok(Response::new(ListSessionsResponse {
+            sessions: sessions.into_iter().map(Into::into).collect(),
         }))
     }
 
-    async fn create_post(
+    async fn revoke_session(
         &self,
-        request: Request<ProtoCreatePostRequest>,
-    ) -> Result<Response<ProtoPost>, Status> {
+        request: Request<RevokeSessionRequest>,
+    ) -> Result<Response<()>, Status> {
         let token = extract_token_from_request(&request)?;
-        let user: Claims = self
+        let session_id = Uuid::parse_str(&request.get_ref().session_id)
+            .map_err(|_| Status::invalid_argument("Invalid session_id"))?;
+
+        self.auth_service
+            .revoke_session(&token, session_id)
+            .await
+            .map_err(map_domain_error_to_status)?;
+
+        tracing::info!("Revoked session: session_id={}", session_id);
+
+        Ok(Response::new(()))
+    }
+
+    async fn get_me(&self, request: Request<()>) -> Result<Response<Account>, Status> {
+        let token = extract_token_from_request(&request)?;
+
+        let user = self
             .auth_service
-            .keys()
-            .verify_token(&token)
-            .map_err(|_| Status::unauthenticated("Invalid or expired token"))?;
+            .me(&token)
+            .await
+            .map_err(map_domain_error_to_status)?;
 
+        Ok(Response::new(user.into()))
+    }
+
+    async fn change_password(
+        &self,
+        request: Request<ChangePasswordRequest>,
+    ) -> Result<Response<()>, Status> {
+        let token = extract_token_from_request(&request)?;
+        let client = client_info(&request);
         let req = request.into_inner();
 
-        let user_id = Uuid::parse_str(user.sub.as_str()).unwrap();
+        self.auth_service
+            .change_password(&token, &req.current_password, &req.new_password, &client)
+            .await
+            .map_err(map_domain_error_to_status)?;
+
+        Ok(Response::new(()))
+    }
+
+    async fn change_email(
+        &self,
+        request: Request<ChangeEmailRequest>,
+    ) -> Result<Response<Account>, Status> {
+        let token = extract_token_from_request(&request)?;
+        let client = client_info(&request);
+        let req = request.into_inner();
+
+        let user = self
+            .auth_service
+            .change_email(&token, &req.current_password, &req.email, &client)
+            .await
+            .map_err(map_domain_error_to_status)?;
+
+        tracing::info!("Changed email: user_id={}", user.id);
+
+        Ok(Response::new(user.into()))
+    }
+
+    async fn change_username(
+        &self,
+        request: Request<ChangeUsernameRequest>,
+    ) -> Result<Response<Account>, Status> {
+        let token = extract_token_from_request(&request)?;
+        let req = request.into_inner();
+
+        let user = self
+            .auth_service
+            .change_username(&token, &req.username)
+            .await
+            .map_err(map_domain_error_to_status)?;
+
+        tracing::info!("Changed username: user_id={}", user.id);
+
+        Ok(Response::new(user.into()))
+    }
+
+    async fn set_user_role(
+        &self,
+        request: Request<SetUserRoleRequest>,
+    ) -> Result<Response<Account>, Status> {
+        let token = extract_token_from_request(&request)?;
+        let client = client_info(&request);
+        let req = request.into_inner();
+        let user_id = Uuid::parse_str(&req.user_id)
+            .map_err(|_| Status::invalid_argument("Invalid user_id"))?;
+        let role: Role = req.role.parse().map_err(map_domain_error_to_status)?;
+
+        let user = self
+            .auth_service
+            .set_user_role(&token, user_id, role, &client)
+            .await
+            .map_err(map_domain_error_to_status)?;
+
+        tracing::info!("Changed user role: user_id={}, role={}", user.id, user.role);
+
+        Ok(Response::new(user.into()))
+    }
+
+    async fn create_post(
+        &self,
+        request: Request<ProtoCreatePostRequest>,
+    ) -> Result<Response<ProtoPost>, Status> {
+        let user_id = self.authenticate(&request).await?.id;
+        let client = client_info(&request);
+        let req = request.into_inner();
 
         let post = self
             .post_service
-            .create_post(user_id, req.into())
+            .create_post(user_id, req.try_into()?, &client)
             .await
             .map_err(map_domain_error_to_status)?;
 
@@ -140,132 +396,501 @@ where
             post.id.to_string()
         );
 
-        Ok(Response::new(ProtoPost {
-            post_id: post.id.to_string(),
-            title: post.title,
-            content: post.content,
-            author_id: user_id.to_string(),
-            created_at: Some(post.created_at.to_protobuf()),
-            updated_at: Some(post.updated_at.to_protobuf()),
-        }))
+        Ok(Response::new(post.into()))
     }
 
     async fn get_post(
         &self,
         request: Request<GetPostRequest>,
     ) -> Result<Response<ProtoPost>, Status> {
+        let viewer = self.viewer(&request).await;
         let req = request.into_inner();
-        let post_id = Uuid::parse_str(req.post_id.as_str()).unwrap();
+        let post_id = parse_post_id(req.post_id.as_str())?;
 
         let post = self
             .post_service
-            .get_post(post_id)
+            .get_post(post_id, viewer)
             .await
             .map_err(map_domain_error_to_status)?;
 
-        Ok(Response::new(ProtoPost {
-            post_id: post.id.to_string(),
-            title: post.title,
-            content: post.content,
-            author_id: post.author_id.to_string(),
-            created_at: Some(post.created_at.to_protobuf()),
-            updated_at: Some(post.updated_at.to_protobuf()),
-        }))
+        Ok(Response::new(post.into()))
+    }
+
+    async fn get_post_by_slug(
+        &self,
+        request: Request<GetPostBySlugRequest>,
+    ) -> Result<Response<ProtoPost>, Status> {
+        let viewer = self.viewer(&request).await;
+        let req = request.into_inner();
+
+        let post = match self
+            .post_service
+            .get_post_by_slug(&req.slug, viewer)
+            .await
+            .map_err(map_domain_error_to_status)?
+        {
+            SlugLookup::Found(post) | SlugLookup::Moved(post) => post,
+        };
+
+        Ok(Response::new(post.into()))
     }
 
     async fn list_posts(
         &self,
         request: Request<ListPostsRequest>,
     ) -> Result<Response<ListPostsResponse>, Status> {
+        let viewer = self.viewer(&request).await;
         let req = request.into_inner();
 
-        let posts = self
+        let filter = PostFilter {
+            author_id: req
+                .author_id
+                .map(|id| {
+                    Uuid::parse_str(&id).map_err(|_| Status::invalid_argument("Invalid author_id"))
+                })
+                .transpose()?,
+            tag: req.tag,
+            created_after: req.created_after.map(timestamp_to_chrono).transpose()?,
+            created_before: req.created_before.map(timestamp_to_chrono).transpose()?,
+            title_contains: req.title_contains,
+        };
+
+        let page = self
             .post_service
-            .get_posts(Some(req.limit as usize), Some(req.offset as usize))
+            .get_posts(
+                viewer,
+                filter,
+                (req.limit > 0).then_some(req.limit as usize),
+                (req.offset > 0).then_some(req.offset as usize),
+                Some(req.page_token.as_str()),
+            )
             .await
             .map_err(map_domain_error_to_status)?;
 
-        let proto_posts = ListPostsResponse::from(posts);
+        let proto_posts = ListPostsResponse::from(page);
 
         Ok(Response::new(proto_posts))
     }
 
+    async fn search_posts(
+        &self,
+        request: Request<SearchPostsRequest>,
+    ) -> Result<Response<SearchPostsResponse>, Status> {
+        let viewer = self.viewer(&request).await;
+        let req = request.into_inner();
+
+        let results = self
+            .post_service
+            .search_posts(
+                viewer,
+                &req.query,
+                (req.limit > 0).then_some(req.limit as usize),
+                (req.offset > 0).then_some(req.offset as usize),
+            )
+            .await
+            .map_err(map_domain_error_to_status)?;
+
+        Ok(Response::new(SearchPostsResponse {
+            results: results.into_iter().map(Into::into).collect(),
+        }))
+    }
+
     async fn update_post(
         &self,
         request: Request<ProtoUpdatePostRequest>,
     ) -> Result<Response<ProtoPost>, Status> {
-        let token = extract_token_from_request(&request)?;
-        let user: Claims = self
-            .auth_service
-            .keys()
-            .verify_token(&token)
-            .map_err(|_| Status::unauthenticated("Invalid or expired token"))?;
-
+        let actor = self.authenticate(&request).await?;
+        let client = client_info(&request);
         let req = request.into_inner();
 
-        let user_id = Uuid::parse_str(user.sub.as_str()).unwrap();
-        let post_id = Uuid::parse_str(req.post_id.as_str()).unwrap();
-        let update_req = UpdatePostRequest::from(req);
+        let post_id = parse_post_id(req.post_id.as_str())?;
+        let update_req = UpdatePostRequest::try_from(req)?;
 
         let post = self
             .post_service
-            .update_post(post_id, user_id, update_req)
+            .update_post(actor, post_id, update_req, &client)
             .await
             .map_err(map_domain_error_to_status)?;
 
         tracing::info!(
             "Updated post: user_id={}, post_id={}",
-            user_id,
+            actor.id,
             post.id.to_string()
         );
 
-        Ok(Response::new(ProtoPost {
-            post_id: post.id.to_string(),
-            title: post.title,
-            content: post.content,
-            author_id: post.author_id.to_string(),
-            created_at: Some(post.created_at.to_protobuf()),
-            updated_at: Some(post.updated_at.to_protobuf()),
-        }))
+        Ok(Response::new(post.into()))
     }
 
     async fn delete_post(
         &self,
         request: Request<DeletePostRequest>,
     ) -> Result<Response<()>, Status> {
-        let token = extract_token_from_request(&request)?;
-        let user: Claims = self
-            .auth_service
-            .keys()
-            .verify_token(&token)
-            .map_err(|_| Status::unauthenticated("Invalid or expired token"))?;
+        let actor = self.authenticate(&request).await?;
+        let post_id = parse_post_id(request.get_ref().post_id.as_str())?;
+
+        self.post_service
+            .delete_post(actor, post_id, &client_info(&request))
+            .await
+            .map_err(map_domain_error_to_status)?;
+
+        tracing::info!("Deleted post: user_id={}, post_id={}", actor.id, post_id);
+
+        Ok(Response::new(()))
+    }
+
+    async fn list_trashed_posts(
+        &self,
+        request: Request<()>,
+    ) -> Result<Response<ListTrashedPostsResponse>, Status> {
+        // Корзина только читается, как и в REST, поэтому хватает posts:read
+        let (actor, credential) = self.credentials(&request).await?;
+        if !credential.allows(Scope::PostsRead) {
+            return Err(Status::permission_denied("Token lacks posts:read scope"));
+        }
+        let posts = self
+            .post_service
+            .get_trash(actor)
+            .await
+            .map_err(map_domain_error_to_status)?;
+
+        Ok(Response::new(ListTrashedPostsResponse {
+            posts: posts.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn restore_post(
+        &self,
+        request: Request<RestorePostRequest>,
+    ) -> Result<Response<ProtoPost>, Status> {
+        let actor = self.authenticate(&request).await?;
+        let post_id = parse_post_id(request.get_ref().post_id.as_str())?;
 
+        let post = self
+            .post_service
+            .restore_post(actor, post_id, &client_info(&request))
+            .await
+            .map_err(map_domain_error_to_status)?;
+
+        tracing::info!("Restored post: user_id={}, post_id={}", actor.id, post_id);
+
+        Ok(Response::new(post.into()))
+    }
+
+    async fn publish_post(
+        &self,
+        request: Request<PublishPostRequest>,
+    ) -> Result<Response<ProtoPost>, Status> {
+        let actor = self.authenticate(&request).await?;
         let req = request.into_inner();
+        self.change_status(actor, &req.post_id, PostStatus::Published)
+            .await
+    }
 
-        let user_id = Uuid::parse_str(user.sub.as_str()).unwrap();
-        let post_id = Uuid::parse_str(req.post_id.as_str()).unwrap();
+    async fn unpublish_post(
+        &self,
+        request: Request<UnpublishPostRequest>,
+    ) -> Result<Response<ProtoPost>, Status> {
+        let actor = self.authenticate(&request).await?;
+        let req = request.into_inner();
+        self.change_status(actor, &req.post_id, PostStatus::Draft)
+            .await
+    }
 
-        self.post_service
-            .delete_post(user_id, req)
+    async fn archive_post(
+        &self,
+        request: Request<ArchivePostRequest>,
+    ) -> Result<Response<ProtoPost>, Status> {
+        let actor = self.authenticate(&request).await?;
+        let req = request.into_inner();
+        self.change_status(actor, &req.post_id, PostStatus::Archived)
+            .await
+    }
+
+    async fn toggle_reaction(
+        &self,
+        request: Request<ToggleReactionRequest>,
+    ) -> Result<Response<ProtoPost>, Status> {
+        let user_id = self.authenticate(&request).await?.id;
+        let req = request.into_inner();
+        let post_id = parse_post_id(req.post_id.as_str())?;
+        let kind: ReactionKind = req.kind.parse().map_err(map_domain_error_to_status)?;
+
+        let post = self
+            .post_service
+            .toggle_reaction(user_id, post_id, kind)
+            .await
+            .map_err(map_domain_error_to_status)?;
+
+        Ok(Response::new(post.into()))
+    }
+
+    async fn set_post_hidden(
+        &self,
+        request: Request<SetPostHiddenRequest>,
+    ) -> Result<Response<ProtoPost>, Status> {
+        let actor = self.authenticate(&request).await?;
+        let req = request.into_inner();
+        let post_id = parse_post_id(req.post_id.as_str())?;
+
+        let post = self
+            .post_service
+            .set_hidden(actor, post_id, req.hidden)
+            .await
+            .map_err(map_domain_error_to_status)?;
+
+        tracing::info!(
+            "Changed post visibility: user_id={}, post_id={}, hidden={}",
+            actor.id,
+            post.id,
+            req.hidden
+        );
+
+        Ok(Response::new(post.into()))
+    }
+
+    async fn list_tags(&self, _request: Request<()>) -> Result<Response<ListTagsResponse>, Status> {
+        let tags = self
+            .post_service
+            .get_tags()
+            .await
+            .map_err(map_domain_error_to_status)?;
+
+        Ok(Response::new(ListTagsResponse {
+            tags: tags.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn list_post_revisions(
+        &self,
+        request: Request<ListPostRevisionsRequest>,
+    ) -> Result<Response<ListPostRevisionsResponse>, Status> {
+        let viewer = self.viewer(&request).await;
+        let post_id = parse_post_id(request.get_ref().post_id.as_str())?;
+
+        let revisions = self
+            .post_service
+            .get_revisions(post_id, viewer)
+            .await
+            .map_err(map_domain_error_to_status)?;
+
+        Ok(Response::new(ListPostRevisionsResponse {
+            revisions: revisions.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn get_post_revision(
+        &self,
+        request: Request<GetPostRevisionRequest>,
+    ) -> Result<Response<ProtoPostRevision>, Status> {
+        let viewer = self.viewer(&request).await;
+        let req = request.into_inner();
+        let post_id = parse_post_id(req.post_id.as_str())?;
+
+        let revision = self
+            .post_service
+            .get_revision(post_id, req.revision, viewer)
+            .await
+            .map_err(map_domain_error_to_status)?;
+
+        Ok(Response::new(revision.into()))
+    }
+
+    async fn diff_post_revisions(
+        &self,
+        request: Request<DiffPostRevisionsRequest>,
+    ) -> Result<Response<PostRevisionDiff>, Status> {
+        let viewer = self.viewer(&request).await;
+        let req = request.into_inner();
+        let post_id = parse_post_id(req.post_id.as_str())?;
+
+        let diff = self
+            .post_service
+            .diff_revisions(post_id, req.from, req.to, viewer)
+            .await
+            .map_err(map_domain_error_to_status)?;
+
+        Ok(Response::new(diff.into()))
+    }
+
+    async fn restore_post_revision(
+        &self,
+        request: Request<RestorePostRevisionRequest>,
+    ) -> Result<Response<ProtoPost>, Status> {
+        let actor = self.authenticate(&request).await?;
+        let client = client_info(&request);
+        let req = request.into_inner();
+        let post_id = parse_post_id(req.post_id.as_str())?;
+
+        let post = self
+            .post_service
+            .restore_revision(actor, post_id, req.revision, &client)
+            .await
+            .map_err(map_domain_error_to_status)?;
+
+        tracing::info!(
+            "Restored post revision: user_id={}, post_id={}, revision={}",
+            actor.id,
+            post.id,
+            req.revision
+        );
+
+        Ok(Response::new(post.into()))
+    }
+
+    async fn list_comments(
+        &self,
+        request: Request<ListCommentsRequest>,
+    ) -> Result<Response<ListCommentsResponse>, Status> {
+        let viewer = self.viewer(&request).await;
+        let post_id = parse_post_id(request.get_ref().post_id.as_str())?;
+
+        let comments = self
+            .comment_service
+            .get_comments(post_id, viewer)
             .await
             .map_err(map_domain_error_to_status)?;
 
-        tracing::info!("Deleted post: user_id={}, post_id={}", user_id, post_id);
+        Ok(Response::new(ListCommentsResponse {
+            comments: comments.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn create_comment(
+        &self,
+        request: Request<ProtoCreateCommentRequest>,
+    ) -> Result<Response<ProtoComment>, Status> {
+        let user_id = self.authenticate(&request).await?.id;
+        let req = request.into_inner();
+        let post_id = parse_post_id(req.post_id.as_str())?;
+        let parent_id = req
+            .parent_id
+            .map(|id| parse_comment_id(id.as_str()))
+            .transpose()?;
+
+        let create = CreateCommentRequest {
+            content: req.content,
+            parent_id,
+        };
+        let comment = self
+            .comment_service
+            .create_comment(user_id, post_id, create)
+            .await
+            .map_err(map_domain_error_to_status)?;
+
+        tracing::info!(
+            "Created comment: user_id={}, post_id={}, comment_id={}",
+            user_id,
+            post_id,
+            comment.id
+        );
+
+        Ok(Response::new(comment.into()))
+    }
+
+    async fn delete_comment(
+        &self,
+        request: Request<DeleteCommentRequest>,
+    ) -> Result<Response<()>, Status> {
+        let actor = self.authenticate(&request).await?;
+        let req = request.into_inner();
+        let post_id = parse_post_id(req.post_id.as_str())?;
+        let comment_id = parse_comment_id(req.comment_id.as_str())?;
+
+        self.comment_service
+            .delete_comment(actor, post_id, comment_id)
+            .await
+            .map_err(map_domain_error_to_status)?;
+
+        tracing::info!(
+            "Deleted comment: user_id={}, post_id={}, comment_id={}",
+            actor.id,
+            post_id,
+            comment_id
+        );
 
         Ok(Response::new(()))
     }
+
+    async fn set_comment_hidden(
+        &self,
+        request: Request<SetCommentHiddenRequest>,
+    ) -> Result<Response<ProtoComment>, Status> {
+        let actor = self.authenticate(&request).await?;
+        let req = request.into_inner();
+        let post_id = parse_post_id(req.post_id.as_str())?;
+        let comment_id = parse_comment_id(req.comment_id.as_str())?;
+
+        let comment = self
+            .comment_service
+            .set_hidden(actor, post_id, comment_id, req.hidden)
+            .await
+            .map_err(map_domain_error_to_status)?;
+
+        tracing::info!(
+            "Changed comment visibility: user_id={}, comment_id={}, hidden={}",
+            actor.id,
+            comment_id,
+            req.hidden
+        );
+
+        Ok(Response::new(comment.into()))
+    }
 }
 
-fn map_domain_error_to_status(err: DomainError) -> Status {
+pub(crate) fn map_domain_error_to_status(err: DomainError) -> Status {
     match err {
         DomainError::Unauthorized => Status::unauthenticated("Invalid credentials"),
         DomainError::UserNotFound(_) => Status::not_found("User not found"),
         DomainError::Internal(msg) => Status::internal(msg),
-        DomainError::PostNotFound(_) => Status::not_found("Post not found"),
-        _ => Status::internal("Internal server error"),
+        DomainError::PostNotFound(_) | DomainError::SlugNotFound(_) => {
+            Status::not_found("Post not found")
+        }
+        DomainError::RevisionNotFound(..) => Status::not_found("Revision not found"),
+        DomainError::CommentNotFound(_) => Status::not_found("Comment not found"),
+        DomainError::SessionNotFound(_) => Status::not_found("Session not found"),
+        DomainError::PersonalTokenNotFound(_) => Status::not_found("Token not found"),
+        DomainError::Forbidden => Status::permission_denied("Forbidden"),
+        DomainError::Validation(msg) => Status::invalid_argument(msg),
+        DomainError::UserAlreadyExists(msg) => Status::already_exists(msg),
+        DomainError::VersionConflict(_, version) => Status::aborted(format!(
+            "Post was modified concurrently, current version is {version}"
+        )),
+        DomainError::TooManyRequests(retry_after) => {
+            let mut status = Status::resource_exhausted(format!(
+                "Too many requests, retry after {retry_after}s"
+            ));
+            status
+                .metadata_mut()
+                .insert("retry-after", retry_after.into());
+            status
+        }
     }
 }
 
+fn parse_post_id(post_id: &str) -> Result<Uuid, Status> {
+    Uuid::parse_str(post_id).map_err(|_| Status::invalid_argument("Invalid post_id"))
+}
+
+fn parse_comment_id(comment_id: &str) -> Result<Uuid, Status> {
+    Uuid::parse_str(comment_id).map_err(|_| Status::invalid_argument("Invalid comment_id"))
+}
+
+fn client_info<T>(req: &Request<T>) -> ClientInfo {
+    let user_agent = req
+        .metadata()
+        .get("user-agent")
+        .and_then(|v| v.to_str().ok());
+    let ip = req.remote_addr().map(|addr| addr.ip().to_string());
+    // Как у REST: берём x-request-id клиента или заводим свой, чтобы связать запись аудита с логами
+    let request_id = req
+        .metadata()
+        .get("x-request-id")
+        .and_then(|v| v.to_str().ok())
+        .map(String::from)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    ClientInfo::new(user_agent, ip.as_deref()).with_request_id(request_id)
+}
+
 fn extract_token_from_request<T>(req: &Request<T>) -> Result<String, Status> {
     req.metadata()
         .get("authorization")
@@ -292,7 +917,39 @@ impl ChronoToProtobufTimestamp for DateTime<Utc> {
     }
 
     fn to_protobuf(&self) -> Timestamp {
-        self.clone().into_protobuf()
+        (*self).into_protobuf()
+    }
+}
+
+impl From<PostStatus> for ProtoPostStatus {
+    fn from(status: PostStatus) -> Self {
+        match status {
+            PostStatus::Draft => ProtoPostStatus::Draft,
+            PostStatus::Published => ProtoPostStatus::Published,
+            PostStatus::Archived => ProtoPostStatus::Archived,
+            PostStatus::Scheduled => ProtoPostStatus::Scheduled,
+        }
+    }
+}
+
+/// Некорректная дата — ошибка клиента, а не повод молча выбросить фильтр или расписание
+fn timestamp_to_chrono(ts: Timestamp) -> Result<DateTime<Utc>, Status> {
+    if !(0..1_000_000_000).contains(&ts.nanos) {
+        return Err(Status::invalid_argument(
+            "Timestamp nanos must be in 0..1000000000",
+        ));
+    }
+    DateTime::from_timestamp(ts.seconds, ts.nanos as u32)
+        .ok_or_else(|| Status::invalid_argument("Timestamp is out of range"))
+}
+
+fn post_status_from_proto(status: i32) -> Option<PostStatus> {
+    match ProtoPostStatus::try_from(status).ok()? {
+        ProtoPostStatus::Unspecified => None,
+        ProtoPostStatus::Draft => Some(PostStatus::Draft),
+        ProtoPostStatus::Published => Some(PostStatus::Published),
+        ProtoPostStatus::Archived => Some(PostStatus::Archived),
+        ProtoPostStatus::Scheduled => Some(PostStatus::Scheduled),
     }
 }
 
@@ -301,38 +958,175 @@ impl From<Post> for ProtoPost {
         ProtoPost {
             post_id: p.id.to_string(),
             title: p.title,
+            slug: p.slug,
+            reactions: p
+                .reactions
+                .into_iter()
+                .map(|(kind, count)| (kind.to_string(), count))
+                .collect(),
+            my_reactions: p.my_reactions.iter().map(ToString::to_string).collect(),
             content: p.content,
             author_id: p.author_id.to_string(),
             created_at: Some(p.created_at.to_protobuf()),
             updated_at: Some(p.updated_at.to_protobuf()),
+            status: ProtoPostStatus::from(p.status).into(),
+            published_at: p.published_at.map(ChronoToProtobufTimestamp::into_protobuf),
+            publish_at: p.publish_at.map(ChronoToProtobufTimestamp::into_protobuf),
+            hidden_at: p.hidden_at.map(ChronoToProtobufTimestamp::into_protobuf),
+            deleted_at: p.deleted_at.map(ChronoToProtobufTimestamp::into_protobuf),
+            version: p.version,
+            tags: p.tags,
         }
     }
 }
 
-impl From<Vec<Post>> for ListPostsResponse {
-    fn from(posts: Vec<Post>) -> Self {
+impl From<SearchResult> for ProtoSearchResult {
+    fn from(r: SearchResult) -> Self {
+        ProtoSearchResult {
+            post: Some(r.post.into()),
+            rank: r.rank,
+            snippet: r.snippet,
+        }
+    }
+}
+
+impl From<PostRevision> for ProtoPostRevision {
+    fn from(r: PostRevision) -> Self {
+        ProtoPostRevision {
+            post_id: r.post_id.to_string(),
+            revision: r.revision,
+            title: r.title,
+            content: r.content,
+            created_at: Some(r.created_at.into_protobuf()),
+        }
+    }
+}
+
+impl From<RevisionDiff> for PostRevisionDiff {
+    fn from(d: RevisionDiff) -> Self {
+        PostRevisionDiff {
+            from: d.from,
+            to: d.to,
+            diff: d.diff,
+        }
+    }
+}
+
+impl From<Comment> for ProtoComment {
+    fn from(c: Comment) -> Self {
+        ProtoComment {
+            comment_id: c.id.to_string(),
+            post_id: c.post_id.to_string(),
+            author_id: c.author_id.to_string(),
+            parent_id: c.parent_id.map(|id| id.to_string()),
+            content: c.content,
+            hidden_at: c.hidden_at.map(ChronoToProtobufTimestamp::into_protobuf),
+            created_at: Some(c.created_at.into_protobuf()),
+            updated_at: Some(c.updated_at.into_protobuf()),
+        }
+    }
+}
+
+impl From<User> for Account {
+    fn from(user: User) -> Self {
+        Account {
+            user_id: user.id.to_string(),
+            email_verified: user.email_verified_at.is_some(),
+            totp_enabled: user.totp_enabled(),
+            role: user.role.to_string(),
+            created_at: Some(user.created_at.into_protobuf()),
+            username: user.username,
+            email: user.email,
+        }
+    }
+}
+
+impl From<Session> for ProtoSession {
+    fn from(s: Session) -> Self {
+        ProtoSession {
+            session_id: s.id.to_string(),
+            user_agent: s.user_agent,
+            ip: s.ip,
+            created_at: Some(s.created_at.into_protobuf()),
+            last_seen_at: Some(s.last_seen_at.into_protobuf()),
+            current: s.current,
+        }
+    }
+}
+
+impl From<TagCount> for ProtoTag {
+    fn from(tag: TagCount) -> Self {
+        ProtoTag {
+            name: tag.name,
+            post_count: tag.post_count,
+        }
+    }
+}
+
+impl From<TokenPair> for AuthResponse {
+    fn from(pair: TokenPair) -> Self {
+        AuthResponse {
+            access_token: pair.access_token,
+            expires_in: pair.expires_in,
+            token_type: "Bearer".to_string(),
+            refresh_token: pair.refresh_token,
+            mfa_required: false,
+            mfa_token: String::new(),
+        }
+    }
+}
+
+impl From<LoginOutcome> for AuthResponse {
+    fn from(outcome: LoginOutcome) -> Self {
+        match outcome {
+            LoginOutcome::Tokens(pair) => pair.into(),
+            LoginOutcome::MfaRequired(challenge) => AuthResponse {
+                access_token: String::new(),
+                expires_in: challenge.expires_in,
+                token_type: String::new(),
+                refresh_token: String::new(),
+                mfa_required: true,
+                mfa_token: challenge.mfa_token,
+            },
+        }
+    }
+}
+
+impl From<PostPage> for ListPostsResponse {
+    fn from(page: PostPage) -> Self {
         ListPostsResponse {
-            posts: posts.clone().into_iter().map(Into::into).collect(),
-            total_count: posts.len() as i32,
+            total_count: page.total as i32,
+            posts: page.posts.into_iter().map(Into::into).collect(),
+            next_page_token: page.next_page_token.unwrap_or_default(),
         }
     }
 }
 
-impl From<ProtoUpdatePostRequest> for UpdatePostRequest {
-    fn from(update: ProtoUpdatePostRequest) -> Self {
-        UpdatePostRequest {
+impl TryFrom<ProtoUpdatePostRequest> for UpdatePostRequest {
+    type Error = Status;
+
+    fn try_from(update: ProtoUpdatePostRequest) -> Result<Self, Status> {
+        Ok(UpdatePostRequest {
             title: update.title,
             content: update.content,
-        }
+            publish_at: update.publish_at.map(timestamp_to_chrono).transpose()?,
+            tags: update.tags.map(|list| list.tags),
+            expected_version: update.expected_version,
+        })
     }
 }
 
-impl From<ProtoCreatePostRequest> for CreatePostRequest {
-    fn from(update: ProtoCreatePostRequest) -> Self {
-        CreatePostRequest {
+impl TryFrom<ProtoCreatePostRequest> for CreatePostRequest {
+    type Error = Status;
+
+    fn try_from(update: ProtoCreatePostRequest) -> Result<Self, Status> {
+        Ok(CreatePostRequest {
             title: update.title,
             content: update.content,
-        }
+            status: post_status_from_proto(update.status),
+            publish_at: update.publish_at.map(timestamp_to_chrono).transpose()?,
+            tags: update.tags,
+        })
     }
 }
 
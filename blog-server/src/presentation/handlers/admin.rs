@@ -0,0 +1,74 @@
+use crate::application::audit_log::AuditLog;
+use crate::application::auth_service::AuthService;
+use crate::data::token_repository::PostgresTokenRepository;
+use crate::data::user_repository::PostgresUserRepository;
+use crate::domain::audit::AuditFilter;
+use crate::domain::error::DomainError;
+use crate::presentation::dto::{AccountResponse, AuditLogQuery, SetRoleRequest};
+use crate::presentation::handlers::post::request_id;
+use crate::presentation::utils::{bearer_token, client_info};
+use actix_web::{HttpRequest, HttpResponse, Responder, Scope, get, put, web};
+use std::sync::Arc;
+use tracing::info;
+use uuid::Uuid;
+
+type Auth = web::Data<Arc<AuthService<PostgresUserRepository, PostgresTokenRepository>>>;
+
+/// Управление пользователями: только администраторы и только из сессии
+pub fn scope() -> Scope {
+    web::scope("/admin")
+        .service(set_user_role)
+        .service(get_audit_log)
+}
+
+#[put("/users/{id}/role")]
+async fn set_user_role(
+    req: HttpRequest,
+    service: Auth,
+    path: web::Path<Uuid>,
+    payload: web::Json<SetRoleRequest>,
+) -> Result<impl Responder, DomainError> {
+    let access_token = bearer_token(&req).ok_or(DomainError::Unauthorized)?;
+
+    let user = service
+        .set_user_role(
+            access_token,
+            path.into_inner(),
+            payload.role,
+            &client_info(&req),
+        )
+        .await?;
+
+    info!(
+        request_id = %request_id(&req),
+        user_id = %user.id,
+        role = %user.role,
+        "user role changed"
+    );
+
+    Ok(HttpResponse::Ok().json(AccountResponse::from(user)))
+}
+
+/// Журнал аудита, новые записи первыми; следующая страница — `before_id=next_before_id`
+#[get("/audit")]
+async fn get_audit_log(
+    req: HttpRequest,
+    service: Auth,
+    audit: web::Data<Arc<AuditLog>>,
+    query: web::Query<AuditLogQuery>,
+) -> Result<impl Responder, DomainError> {
+    let access_token = bearer_token(&req).ok_or(DomainError::Unauthorized)?;
+    service.require_admin(access_token).await?;
+
+    let query = query.into_inner();
+    let filter = AuditFilter {
+        actor_id: query.actor_id,
+        target_id: query.target_id,
+        from: query.from,
+        to: query.to,
+        before_id: query.before_id,
+    };
+    let page = audit.search(&filter, query.limit).await?;
+
+    Ok(HttpResponse::Ok().json(page))
+}
@@ -1,81 +1,370 @@
 use crate::application::auth_service::AuthService;
+use crate::data::token_repository::PostgresTokenRepository;
 use crate::data::user_repository::PostgresUserRepository;
 use crate::domain::error::DomainError;
-use crate::presentation::dto::{AuthResponse, LoginRequest, RegisterRequest};
-use actix_web::{HttpResponse, Responder, Scope, post, web};
+use crate::presentation::dto::{
+    AuthResponse, CreatePersonalTokenRequest, ForgotPasswordRequest, LoginRequest, LoginResponse,
+    LogoutRequest, OidcCallbackQuery, PersonalTokenResponse, RefreshRequest, RegisterRequest,
+    ResetPasswordRequest, TotpCodeRequest, VerifyEmailRequest, VerifyMfaRequest,
+};
+use crate::presentation::handlers::post::request_id;
+use crate::presentation::utils::{bearer_token, client_info};
+use actix_web::http::header;
+use actix_web::{HttpRequest, HttpResponse, Responder, Scope, delete, get, post, web};
+use serde_json::json;
 use std::sync::Arc;
 use tracing::info;
+use uuid::Uuid;
+
+type Auth = web::Data<Arc<AuthService<PostgresUserRepository, PostgresTokenRepository>>>;
 
 pub fn scope() -> Scope {
     web::scope("/auth")
         .service(register)
         .service(login)
+        .service(login_mfa)
         .service(token)
+        .service(refresh)
+        .service(logout)
+        .service(logout_all)
+        .service(list_sessions)
+        .service(revoke_session)
+        .service(create_token)
+        .service(list_tokens)
+        .service(revoke_token)
+        .service(enroll_totp)
+        .service(confirm_totp)
+        .service(disable_totp)
+        .service(regenerate_recovery_codes)
+        .service(forgot_password)
+        .service(reset_password)
+        .service(verify_email)
+        .service(resend_verification_email)
+        .service(oidc_authorize)
+        .service(oidc_callback)
 }
 
 #[post("/register")]
 async fn register(
-    service: web::Data<Arc<AuthService<PostgresUserRepository>>>,
+    req: HttpRequest,
+    service: Auth,
     payload: web::Json<RegisterRequest>,
 ) -> Result<impl Responder, DomainError> {
-    let user = service.register(&payload.0).await?;
+    let client = client_info(&req);
+    let user = service.register(&payload.0, &client).await?;
 
     info!(user_id = %user.id, email = %user.email, "user registered");
 
-    let log_req = LoginRequest {
-        username: user.username,
-        password: payload.password.clone(),
-    };
-
-    let expires_in = 3600 * 24; // 24 часа
-    let jwt = service.login(&log_req).await?;
+    let tokens = service.issue_tokens(user.id, &client).await?;
 
-    info!(username = %log_req.username, "user logged in");
+    info!(username = %user.username, "user logged in");
 
-    Ok(HttpResponse::Created().json(AuthResponse {
-        access_token: jwt,
-        expires_in,
-        token_type: "Bearer".to_string(),
-    }))
+    Ok(HttpResponse::Created().json(AuthResponse::from(tokens)))
 }
 
 #[post("/login")]
 async fn login(
-    service: web::Data<Arc<AuthService<PostgresUserRepository>>>,
+    req: HttpRequest,
+    service: Auth,
     payload: web::Json<LoginRequest>,
 ) -> Result<impl Responder, DomainError> {
-    dotenvy::dotenv().ok();
-    let expires_in: i64 = std::env::var("ACCESS_TOKEN_EXPIRATION_SECS")
-        .expect("Missing access token expiration time")
-        .parse()
-        .expect("ACCESS_TOKEN_EXPIRATION_SECS must be an integer");
-
-    let jwt = service.login(&payload.0).await?;
+    let outcome = service.login(&payload.0, &client_info(&req)).await?;
 
     info!(username = %payload.username, "user logged in");
 
-    Ok(HttpResponse::Ok().json(AuthResponse {
-        access_token: jwt,
-        expires_in,
-        token_type: "Bearer".to_string(),
-    }))
+    Ok(HttpResponse::Ok().json(LoginResponse::from(outcome)))
+}
+
+/// Второй шаг входа при включённом TOTP
+#[post("/login/mfa")]
+async fn login_mfa(
+    req: HttpRequest,
+    service: Auth,
+    payload: web::Json<VerifyMfaRequest>,
+) -> Result<impl Responder, DomainError> {
+    let tokens = service
+        .verify_mfa(&payload.mfa_token, &payload.code, &client_info(&req))
+        .await?;
+
+    info!(request_id = %request_id(&req), "user passed two-factor check");
+
+    Ok(HttpResponse::Ok().json(AuthResponse::from(tokens)))
 }
 
 #[post("/token")]
 async fn token(
-    service: web::Data<Arc<AuthService<PostgresUserRepository>>>,
+    req: HttpRequest,
+    service: Auth,
     payload: web::Json<LoginRequest>,
 ) -> Result<impl Responder, DomainError> {
-    dotenvy::dotenv().ok();
-    let expires_in: i64 = std::env::var("ACCESS_TOKEN_EXPIRATION_SECS")
-        .expect("Missing access token expiration time")
-        .parse()
-        .expect("ACCESS_TOKEN_EXPIRATION_SECS must be an integer");
-
-    let jwt = service.login(&payload.0).await?;
-    Ok(HttpResponse::Ok().json(AuthResponse {
-        access_token: jwt,
-        expires_in,
-        token_type: "Bearer".to_string(),
+    let outcome = service.login(&payload.0, &client_info(&req)).await?;
+    Ok(HttpResponse::Ok().json(LoginResponse::from(outcome)))
+}
+
+/// Обмен refresh-токена на новую пару; предъявленный токен больше не действует
+#[post("/refresh")]
+async fn refresh(
+    req: HttpRequest,
+    service: Auth,
+    payload: web::Json<RefreshRequest>,
+) -> Result<impl Responder, DomainError> {
+    let tokens = service
+        .refresh(&payload.refresh_token, &client_info(&req))
+        .await?;
+    Ok(HttpResponse::Ok().json(AuthResponse::from(tokens)))
+}
+
+/// Тело необязательно: с `refresh_token` гасится и он
+#[post("/logout")]
+async fn logout(
+    req: HttpRequest,
+    service: Auth,
+    payload: Option<web::Json<LogoutRequest>>,
+) -> Result<impl Responder, DomainError> {
+    let access_token = bearer_token(&req).ok_or(DomainError::Unauthorized)?;
+    let payload = payload.map(|json| json.0).unwrap_or_default();
+
+    service
+        .logout(access_token, payload.refresh_token.as_deref())
+        .await?;
+
+    info!(request_id = %request_id(&req), "user logged out");
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[post("/logout-all")]
+async fn logout_all(req: HttpRequest, service: Auth) -> Result<impl Responder, DomainError> {
+    let access_token = bearer_token(&req).ok_or(DomainError::Unauthorized)?;
+
+    service.logout_all(access_token).await?;
+
+    info!(request_id = %request_id(&req), "user logged out everywhere");
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[get("/sessions")]
+async fn list_sessions(req: HttpRequest, service: Auth) -> Result<impl Responder, DomainError> {
+    let access_token = bearer_token(&req).ok_or(DomainError::Unauthorized)?;
+
+    let sessions = service.list_sessions(access_token).await?;
+
+    Ok(HttpResponse::Ok().json(json!({ "sessions": sessions })))
+}
+
+/// Закрыть можно и текущую сессию — это равносильно logout
+#[delete("/sessions/{id}")]
+async fn revoke_session(
+    req: HttpRequest,
+    service: Auth,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, DomainError> {
+    let access_token = bearer_token(&req).ok_or(DomainError::Unauthorized)?;
+    let session_id = path.into_inner();
+
+    service.revoke_session(access_token, session_id).await?;
+
+    info!(request_id = %request_id(&req), session_id = %session_id, "session revoked");
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Персональный токен для автоматизации; секрет возвращается только в этом ответе
+#[post("/tokens")]
+async fn create_token(
+    req: HttpRequest,
+    service: Auth,
+    payload: web::Json<CreatePersonalTokenRequest>,
+) -> Result<impl Responder, DomainError> {
+    let access_token = bearer_token(&req).ok_or(DomainError::Unauthorized)?;
+
+    let (details, secret) = service
+        .create_personal_token(access_token, payload.into_inner())
+        .await?;
+
+    info!(request_id = %request_id(&req), token_id = %details.id, "personal access token created");
+
+    Ok(HttpResponse::Created().json(PersonalTokenResponse {
+        details,
+        token: secret,
     }))
 }
+
+#[get("/tokens")]
+async fn list_tokens(req: HttpRequest, service: Auth) -> Result<impl Responder, DomainError> {
+    let access_token = bearer_token(&req).ok_or(DomainError::Unauthorized)?;
+
+    let tokens = service.list_personal_tokens(access_token).await?;
+
+    Ok(HttpResponse::Ok().json(json!({ "tokens": tokens })))
+}
+
+#[delete("/tokens/{id}")]
+async fn revoke_token(
+    req: HttpRequest,
+    service: Auth,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, DomainError> {
+    let access_token = bearer_token(&req).ok_or(DomainError::Unauthorized)?;
+    let token_id = path.into_inner();
+
+    service
+        .revoke_personal_token(access_token, token_id)
+        .await?;
+
+    info!(request_id = %request_id(&req), token_id = %token_id, "personal access token revoked");
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Секрет и otpauth-URI для QR-кода; TOTP включается только после `/mfa/totp/confirm`
+#[post("/mfa/totp")]
+async fn enroll_totp(req: HttpRequest, service: Auth) -> Result<impl Responder, DomainError> {
+    let access_token = bearer_token(&req).ok_or(DomainError::Unauthorized)?;
+
+    let enrollment = service.enroll_totp(access_token).await?;
+
+    Ok(HttpResponse::Ok().json(enrollment))
+}
+
+#[post("/mfa/totp/confirm")]
+async fn confirm_totp(
+    req: HttpRequest,
+    service: Auth,
+    payload: web::Json<TotpCodeRequest>,
+) -> Result<impl Responder, DomainError> {
+    let access_token = bearer_token(&req).ok_or(DomainError::Unauthorized)?;
+
+    let recovery_codes = service.confirm_totp(access_token, &payload.code).await?;
+
+    info!(request_id = %request_id(&req), "two-factor authentication enabled");
+
+    Ok(HttpResponse::Ok().json(json!({ "recovery_codes": recovery_codes })))
+}
+
+#[post("/mfa/totp/disable")]
+async fn disable_totp(
+    req: HttpRequest,
+    service: Auth,
+    payload: web::Json<TotpCodeRequest>,
+) -> Result<impl Responder, DomainError> {
+    let access_token = bearer_token(&req).ok_or(DomainError::Unauthorized)?;
+
+    service.disable_totp(access_token, &payload.code).await?;
+
+    info!(request_id = %request_id(&req), "two-factor authentication disabled");
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[post("/mfa/recovery-codes")]
+async fn regenerate_recovery_codes(
+    req: HttpRequest,
+    service: Auth,
+    payload: web::Json<TotpCodeRequest>,
+) -> Result<impl Responder, DomainError> {
+    let access_token = bearer_token(&req).ok_or(DomainError::Unauthorized)?;
+
+    let recovery_codes = service
+        .regenerate_recovery_codes(access_token, &payload.code)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(json!({ "recovery_codes": recovery_codes })))
+}
+
+/// Ответ одинаковый, есть такой адрес или нет
+#[post("/password/forgot")]
+async fn forgot_password(
+    service: Auth,
+    payload: web::Json<ForgotPasswordRequest>,
+) -> Result<impl Responder, DomainError> {
+    service.request_password_reset(&payload.email).await?;
+    Ok(HttpResponse::Accepted().finish())
+}
+
+#[post("/password/reset")]
+async fn reset_password(
+    req: HttpRequest,
+    service: Auth,
+    payload: web::Json<ResetPasswordRequest>,
+) -> Result<impl Responder, DomainError> {
+    service
+        .reset_password(&payload.token, &payload.password)
+        .await?;
+
+    info!(request_id = %request_id(&req), "password reset");
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[post("/email/verify")]
+async fn verify_email(
+    req: HttpRequest,
+    service: Auth,
+    payload: web::Json<VerifyEmailRequest>,
+) -> Result<impl Responder, DomainError> {
+    service.verify_email(&payload.token).await?;
+
+    info!(request_id = %request_id(&req), "email verified");
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[post("/email/verify/resend")]
+async fn resend_verification_email(
+    req: HttpRequest,
+    service: Auth,
+) -> Result<impl Responder, DomainError> {
+    let access_token = bearer_token(&req).ok_or(DomainError::Unauthorized)?;
+
+    service.resend_verification_email(access_token).await?;
+
+    Ok(HttpResponse::Accepted().finish())
+}
+
+/// Начало входа через SSO: перенаправляет на страницу входа провайдера
+#[get("/oidc/authorize")]
+async fn oidc_authorize(req: HttpRequest, service: Auth) -> Result<impl Responder, DomainError> {
+    let url = service.oidc_authorization_url().await?;
+
+    info!(request_id = %request_id(&req), "oidc login started");
+
+    Ok(HttpResponse::Found()
+        .insert_header((header::LOCATION, url))
+        .insert_header((header::CACHE_CONTROL, "no-store"))
+        .finish())
+}
+
+/// Сюда провайдер возвращает пользователя; в ответ — обычная пара токенов блога
+#[get("/oidc/callback")]
+async fn oidc_callback(
+    req: HttpRequest,
+    service: Auth,
+    query: web::Query<OidcCallbackQuery>,
+) -> Result<impl Responder, DomainError> {
+    let query = query.into_inner();
+    if let Some(error) = query.error {
+        info!(request_id = %request_id(&req), error = %error, "oidc login declined by provider");
+        return Err(DomainError::Unauthorized);
+    }
+    let (Some(code), Some(state)) = (query.code, query.state) else {
+        return Err(DomainError::Validation(
+            "code and state are required".to_string(),
+        ));
+    };
+
+    let tokens = service
+        .oidc_login(&code, &state, &client_info(&req))
+        .await?;
+
+    Ok(HttpResponse::Ok().json(AuthResponse::from(tokens)))
+}
+
+/// Открытые ключи подписи токенов для других сервисов
+#[get("/.well-known/jwks.json")]
+pub async fn jwks(service: Auth) -> impl Responder {
+    HttpResponse::Ok()
+        .insert_header((header::CACHE_CONTROL, "public, max-age=300"))
+        .json(service.jwks())
+}
@@ -0,0 +1,113 @@
+use crate::application::comment_service::CommentService;
+use crate::data::comment_repository::PostgresCommentRepository;
+use crate::data::post_repository::PostgresPostRepository;
+use crate::domain::error::DomainError;
+use crate::presentation::dto::CreateCommentRequest;
+use crate::presentation::handlers::post::request_id;
+use crate::presentation::utils::{AuthenticatedUser, OptionalUser};
+use actix_web::{HttpRequest, HttpResponse, delete, get, post, web};
+use serde_json::json;
+use std::sync::Arc;
+use tracing::info;
+use uuid::Uuid;
+
+#[get("/posts/{id}/comments")]
+async fn get_comments(
+    user: OptionalUser,
+    comments: web::Data<Arc<CommentService<PostgresCommentRepository, PostgresPostRepository>>>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, DomainError> {
+    let comments = comments.get_comments(path.into_inner(), user.id()).await?;
+
+    Ok(HttpResponse::Ok().json(json!({ "comments": comments })))
+}
+
+#[post("/{id}/comments")]
+async fn create_comment(
+    req: HttpRequest,
+    user: AuthenticatedUser,
+    comments: web::Data<Arc<CommentService<PostgresCommentRepository, PostgresPostRepository>>>,
+    path: web::Path<Uuid>,
+    payload: web::Json<CreateCommentRequest>,
+) -> Result<HttpResponse, DomainError> {
+    let comment = comments
+        .create_comment(user.id, path.into_inner(), payload.0)
+        .await?;
+
+    info!(
+        request_id = %request_id(&req),
+        username = %user.username,
+        post_id = %comment.post_id,
+        comment_id = %comment.id,
+        "comment created"
+    );
+
+    Ok(HttpResponse::Created().json(comment))
+}
+
+#[delete("/{id}/comments/{comment_id}")]
+async fn delete_comment(
+    req: HttpRequest,
+    user: AuthenticatedUser,
+    comments: web::Data<Arc<CommentService<PostgresCommentRepository, PostgresPostRepository>>>,
+    path: web::Path<(Uuid, Uuid)>,
+) -> Result<HttpResponse, DomainError> {
+    let (post_id, comment_id) = path.into_inner();
+    comments
+        .delete_comment(user.actor(), post_id, comment_id)
+        .await?;
+
+    info!(
+        request_id = %request_id(&req),
+        username = %user.username,
+        post_id = %post_id,
+        comment_id = %comment_id,
+        "comment deleted"
+    );
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[post("/{id}/comments/{comment_id}/hide")]
+async fn hide_comment(
+    req: HttpRequest,
+    user: AuthenticatedUser,
+    comments: web::Data<Arc<CommentService<PostgresCommentRepository, PostgresPostRepository>>>,
+    path: web::Path<(Uuid, Uuid)>,
+) -> Result<HttpResponse, DomainError> {
+    set_hidden(req, user, comments, path.into_inner(), true).await
+}
+
+#[post("/{id}/comments/{comment_id}/unhide")]
+async fn unhide_comment(
+    req: HttpRequest,
+    user: AuthenticatedUser,
+    comments: web::Data<Arc<CommentService<PostgresCommentRepository, PostgresPostRepository>>>,
+    path: web::Path<(Uuid, Uuid)>,
+) -> Result<HttpResponse, DomainError> {
+    set_hidden(req, user, comments, path.into_inner(), false).await
+}
+
+async fn set_hidden(
+    req: HttpRequest,
+    user: AuthenticatedUser,
+    comments: web::Data<Arc<CommentService<PostgresCommentRepository, PostgresPostRepository>>>,
+    (post_id, comment_id): (Uuid, Uuid),
+    hidden: bool,
+) -> Result<HttpResponse, DomainError> {
+    let comment = comments
+        .set_hidden(user.actor(), post_id, comment_id, hidden)
+        .await?;
+
+    info!(
+        request_id = %request_id(&req),
+        username = %user.username,
+        role = %user.role,
+        post_id = %post_id,
+        comment_id = %comment_id,
+        hidden,
+        "comment visibility changed"
+    );
+
+    Ok(HttpResponse::Ok().json(comment))
+}
@@ -1,2 +1,7 @@
+pub mod admin;
 pub mod auth;
+pub mod comment;
 pub mod post;
+pub mod revision;
+pub mod tag;
+pub mod user;
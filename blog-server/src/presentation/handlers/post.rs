@@ -1,10 +1,14 @@
 use crate::application::post_service::PostService;
-use crate::blog::DeletePostRequest;
 use crate::data::post_repository::PostgresPostRepository;
 use crate::domain::error::DomainError;
-use crate::presentation::dto::{CreatePostRequest, Pagination, UpdatePostRequest};
-use crate::presentation::utils::{AuthenticatedUser, ensure_owner};
-use actix_web::web::post;
+use crate::domain::post::{Post, PostFilter, PostStatus};
+use crate::domain::reaction::ReactionKind;
+use crate::domain::slug::SlugLookup;
+use crate::presentation::dto::{
+    CreatePostRequest, ListPostsQuery, ListPostsResponse, SearchQuery, UpdatePostRequest,
+};
+use crate::presentation::utils::{AuthenticatedUser, OptionalUser, client_info};
+use actix_web::http::header::{self, ETag, EntityTag, Header, IfMatch};
 use actix_web::{HttpMessage, HttpRequest, HttpResponse, delete, get, post, put, web};
 use serde_json::json;
 use std::sync::Arc;
@@ -18,7 +22,9 @@ async fn create_post(
     post: web::Data<Arc<PostService<PostgresPostRepository>>>,
     payload: web::Json<CreatePostRequest>,
 ) -> Result<HttpResponse, DomainError> {
-    let post = post.create_post(user.id, payload.0).await?;
+    let post = post
+        .create_post(user.id, payload.0, &client_info(&req))
+        .await?;
 
     info!(
         request_id = %request_id(&req),
@@ -38,19 +44,20 @@ async fn update_post(
     path: web::Path<Uuid>,
 ) -> Result<HttpResponse, DomainError> {
     let post_id = path.into_inner();
-    let owner = post.get_post(post_id).await?;
-    ensure_owner(&owner.author_id, &user.id)?;
-
-    let post = post.update_post(post_id, user.id, payload.0).await?;
+    let mut update = payload.into_inner();
+    update.expected_version = expected_version(&req)?;
+    let post = post
+        .update_post(user.actor(), post_id, update, &client_info(&req))
+        .await?;
 
     info!(
         request_id = %request_id(&req),
         username = %user.username,
-        post_id = %owner.id,
+        post_id = %post.id,
         "post updated"
     );
 
-    Ok(HttpResponse::Ok().json(post))
+    Ok(HttpResponse::Ok().insert_header(etag(&post)).json(post))
 }
 
 #[delete("/{id}")]
@@ -61,64 +68,308 @@ async fn delete_post(
     path: web::Path<Uuid>,
 ) -> Result<HttpResponse, DomainError> {
     let post_id = path.into_inner();
-    let owner = post.get_post(post_id).await?;
-    ensure_owner(&owner.author_id, &user.id)?;
-
-    let request = DeletePostRequest {
-        post_id: post_id.to_string(),
-    };
-
-    post.delete_post(user.id, request).await?;
+    post.delete_post(user.actor(), post_id, &client_info(&req))
+        .await?;
 
     info!(
         request_id = %request_id(&req),
         username = %user.username,
-        post_id = %owner.id,
+        post_id = %post_id,
         "post deleted"
     );
 
     Ok(HttpResponse::NoContent().json("deleted"))
 }
 
+/// Пост из корзины снова появляется в выдаче со всеми комментариями и реакциями
+#[post("/{id}/restore")]
+async fn restore_post(
+    req: HttpRequest,
+    user: AuthenticatedUser,
+    post: web::Data<Arc<PostService<PostgresPostRepository>>>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, DomainError> {
+    let post = post
+        .restore_post(user.actor(), path.into_inner(), &client_info(&req))
+        .await?;
+
+    info!(
+        request_id = %request_id(&req),
+        username = %user.username,
+        post_id = %post.id,
+        "post restored"
+    );
+
+    Ok(HttpResponse::Ok().insert_header(etag(&post)).json(post))
+}
+
+#[post("/{id}/publish")]
+async fn publish_post(
+    req: HttpRequest,
+    user: AuthenticatedUser,
+    post: web::Data<Arc<PostService<PostgresPostRepository>>>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, DomainError> {
+    change_status(req, user, post, path.into_inner(), PostStatus::Published).await
+}
+
+#[post("/{id}/unpublish")]
+async fn unpublish_post(
+    req: HttpRequest,
+    user: AuthenticatedUser,
+    post: web::Data<Arc<PostService<PostgresPostRepository>>>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, DomainError> {
+    change_status(req, user, post, path.into_inner(), PostStatus::Draft).await
+}
+
+#[post("/{id}/archive")]
+async fn archive_post(
+    req: HttpRequest,
+    user: AuthenticatedUser,
+    post: web::Data<Arc<PostService<PostgresPostRepository>>>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, DomainError> {
+    change_status(req, user, post, path.into_inner(), PostStatus::Archived).await
+}
+
+async fn change_status(
+    req: HttpRequest,
+    user: AuthenticatedUser,
+    post: web::Data<Arc<PostService<PostgresPostRepository>>>,
+    post_id: Uuid,
+    status: PostStatus,
+) -> Result<HttpResponse, DomainError> {
+    let post = post.change_status(user.actor(), post_id, status).await?;
+
+    info!(
+        request_id = %request_id(&req),
+        username = %user.username,
+        post_id = %post.id,
+        status = ?post.status,
+        "post status changed"
+    );
+
+    Ok(HttpResponse::Ok().json(post))
+}
+
+/// Скрыть пост из выдачи может модератор или администратор; автор его по-прежнему видит
+#[post("/{id}/hide")]
+async fn hide_post(
+    req: HttpRequest,
+    user: AuthenticatedUser,
+    post: web::Data<Arc<PostService<PostgresPostRepository>>>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, DomainError> {
+    set_hidden(req, user, post, path.into_inner(), true).await
+}
+
+#[post("/{id}/unhide")]
+async fn unhide_post(
+    req: HttpRequest,
+    user: AuthenticatedUser,
+    post: web::Data<Arc<PostService<PostgresPostRepository>>>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, DomainError> {
+    set_hidden(req, user, post, path.into_inner(), false).await
+}
+
+async fn set_hidden(
+    req: HttpRequest,
+    user: AuthenticatedUser,
+    post: web::Data<Arc<PostService<PostgresPostRepository>>>,
+    post_id: Uuid,
+    hidden: bool,
+) -> Result<HttpResponse, DomainError> {
+    let post = post.set_hidden(user.actor(), post_id, hidden).await?;
+
+    info!(
+        request_id = %request_id(&req),
+        username = %user.username,
+        role = %user.role,
+        post_id = %post.id,
+        hidden,
+        "post visibility changed"
+    );
+
+    Ok(HttpResponse::Ok().json(post))
+}
+
+/// Повторный вызов с тем же видом реакции снимает её
+#[post("/{id}/reactions/{kind}")]
+async fn toggle_reaction(
+    req: HttpRequest,
+    user: AuthenticatedUser,
+    post: web::Data<Arc<PostService<PostgresPostRepository>>>,
+    path: web::Path<(Uuid, ReactionKind)>,
+) -> Result<HttpResponse, DomainError> {
+    let (post_id, kind) = path.into_inner();
+    let post = post.toggle_reaction(user.id, post_id, kind).await?;
+
+    info!(
+        request_id = %request_id(&req),
+        username = %user.username,
+        post_id = %post.id,
+        kind = %kind,
+        "reaction toggled"
+    );
+
+    Ok(HttpResponse::Ok().json(post))
+}
+
 #[get("/posts")]
 async fn get_posts(
     req: HttpRequest,
+    user: OptionalUser,
     post: web::Data<Arc<PostService<PostgresPostRepository>>>,
-    query: web::Query<Pagination>,
+    query: web::Query<ListPostsQuery>,
 ) -> Result<HttpResponse, DomainError> {
-    let pagination = query.into_inner();
-    let posts = post.get_posts(pagination.limit, pagination.offset).await?;
+    let query = query.into_inner();
+    let limit = query.page_size.or(query.limit);
+    let filter = PostFilter {
+        author_id: query.author_id,
+        tag: query.tag,
+        created_after: query.created_after,
+        created_before: query.created_before,
+        title_contains: query.title,
+    };
+    let page = post
+        .get_posts(
+            user.id(),
+            filter,
+            limit,
+            query.offset,
+            query.page_token.as_deref(),
+        )
+        .await?;
 
     info!(
         request_id = %request_id(&req),
         "posts retrieved"
     );
 
+    Ok(HttpResponse::Ok().json(ListPostsResponse {
+        total: page.total,
+        posts: page.posts,
+        next_page_token: page.next_page_token,
+        limit,
+        offset: query.offset,
+    }))
+}
+
+/// Свои удалённые посты; публичный маршрут, поэтому токен проверяется здесь
+#[get("/posts/trash")]
+async fn get_trash(
+    req: HttpRequest,
+    user: OptionalUser,
+    post: web::Data<Arc<PostService<PostgresPostRepository>>>,
+) -> Result<HttpResponse, DomainError> {
+    let user = user.0.ok_or(DomainError::Unauthorized)?;
+    let posts = post.get_trash(user.actor()).await?;
+
+    info!(
+        request_id = %request_id(&req),
+        username = %user.username,
+        posts = posts.len(),
+        "trash fetched"
+    );
+
+    Ok(HttpResponse::Ok().json(json!({ "posts": posts })))
+}
+
+#[get("/posts/search")]
+async fn search_posts(
+    req: HttpRequest,
+    user: OptionalUser,
+    post: web::Data<Arc<PostService<PostgresPostRepository>>>,
+    query: web::Query<SearchQuery>,
+) -> Result<HttpResponse, DomainError> {
+    let query = query.into_inner();
+    let results = post
+        .search_posts(user.id(), &query.q, query.limit, query.offset)
+        .await?;
+
+    info!(
+        request_id = %request_id(&req),
+        results = results.len(),
+        "posts searched"
+    );
+
     Ok(HttpResponse::Ok().json(json!({
-        "posts": posts,
-        "total": posts.len(),
-        "limit": pagination.limit,
-        "offset": pagination.offset
+        "results": results,
+        "limit": query.limit,
+        "offset": query.offset
     })))
 }
 
 #[get("/posts/{id}")]
 async fn get_post(
     req: HttpRequest,
+    user: OptionalUser,
     post: web::Data<Arc<PostService<PostgresPostRepository>>>,
     path: web::Path<Uuid>,
 ) -> Result<HttpResponse, DomainError> {
     let post_id = path.into_inner();
-    let post = post.get_post(post_id).await?;
+    let post = post.get_post(post_id, user.id()).await?;
 
     info!(
         request_id = %request_id(&req),
         "post retrieved"
     );
 
-    Ok(HttpResponse::Ok().json(post))
+    Ok(HttpResponse::Ok().insert_header(etag(&post)).json(post))
 }
-fn request_id(req: &HttpRequest) -> String {
+
+#[get("/posts/by-slug/{slug}")]
+async fn get_post_by_slug(
+    req: HttpRequest,
+    user: OptionalUser,
+    post: web::Data<Arc<PostService<PostgresPostRepository>>>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, DomainError> {
+    let slug = path.into_inner();
+
+    match post.get_post_by_slug(&slug, user.id()).await? {
+        SlugLookup::Found(post) => {
+            info!(
+                request_id = %request_id(&req),
+                "post retrieved"
+            );
+
+            Ok(HttpResponse::Ok().insert_header(etag(&post)).json(post))
+        }
+        // Заголовок поменялся — старые ссылки ведут на актуальный адрес
+        SlugLookup::Moved(post) => Ok(HttpResponse::MovedPermanently()
+            .insert_header((
+                header::LOCATION,
+                format!("/api/posts/by-slug/{}", post.slug),
+            ))
+            .finish()),
+    }
+}
+
+/// ETag поста — номер его версии
+fn etag(post: &Post) -> ETag {
+    ETag(EntityTag::new_strong(post.version.to_string()))
+}
+
+/// Версия из `If-Match`. Без заголовка или с `*` правка не проверяется,
+/// иначе ожидается ровно один сильный ETag, выданный `get_post`
+fn expected_version(req: &HttpRequest) -> Result<Option<i32>, DomainError> {
+    if !req.headers().contains_key(header::IF_MATCH) {
+        return Ok(None);
+    }
+    let invalid = || DomainError::Validation("If-Match must be a single post ETag".to_string());
+    match IfMatch::parse(req).map_err(|_| invalid())? {
+        IfMatch::Any => Ok(None),
+        IfMatch::Items(tags) => match tags.as_slice() {
+            [tag] if !tag.weak => tag.tag().parse().map(Some).map_err(|_| invalid()),
+            _ => Err(invalid()),
+        },
+    }
+}
+
+pub(crate) fn request_id(req: &HttpRequest) -> String {
     req.extensions()
         .get::<crate::presentation::middleware::RequestId>()
         .map(|rid| rid.0.clone())
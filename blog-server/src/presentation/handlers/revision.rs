@@ -0,0 +1,71 @@
+use crate::application::post_service::PostService;
+use crate::data::post_repository::PostgresPostRepository;
+use crate::domain::error::DomainError;
+use crate::presentation::dto::RevisionDiffQuery;
+use crate::presentation::handlers::post::request_id;
+use crate::presentation::utils::{AuthenticatedUser, OptionalUser, client_info};
+use actix_web::{HttpRequest, HttpResponse, get, post, web};
+use serde_json::json;
+use std::sync::Arc;
+use tracing::info;
+use uuid::Uuid;
+
+#[get("/posts/{id}/revisions")]
+async fn get_revisions(
+    user: OptionalUser,
+    post: web::Data<Arc<PostService<PostgresPostRepository>>>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, DomainError> {
+    let revisions = post.get_revisions(path.into_inner(), user.id()).await?;
+
+    Ok(HttpResponse::Ok().json(json!({ "revisions": revisions })))
+}
+
+#[get("/posts/{id}/revisions/diff")]
+async fn diff_revisions(
+    user: OptionalUser,
+    post: web::Data<Arc<PostService<PostgresPostRepository>>>,
+    path: web::Path<Uuid>,
+    query: web::Query<RevisionDiffQuery>,
+) -> Result<HttpResponse, DomainError> {
+    let diff = post
+        .diff_revisions(path.into_inner(), query.from, query.to, user.id())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(diff))
+}
+
+#[get("/posts/{id}/revisions/{revision}")]
+async fn get_revision(
+    user: OptionalUser,
+    post: web::Data<Arc<PostService<PostgresPostRepository>>>,
+    path: web::Path<(Uuid, i32)>,
+) -> Result<HttpResponse, DomainError> {
+    let (post_id, revision) = path.into_inner();
+    let revision = post.get_revision(post_id, revision, user.id()).await?;
+
+    Ok(HttpResponse::Ok().json(revision))
+}
+
+#[post("/{id}/revisions/{revision}/restore")]
+async fn restore_revision(
+    req: HttpRequest,
+    user: AuthenticatedUser,
+    post: web::Data<Arc<PostService<PostgresPostRepository>>>,
+    path: web::Path<(Uuid, i32)>,
+) -> Result<HttpResponse, DomainError> {
+    let (post_id, revision) = path.into_inner();
+    let post = post
+        .restore_revision(user.actor(), post_id, revision, &client_info(&req))
+        .await?;
+
+    info!(
+        request_id = %request_id(&req),
+        username = %user.username,
+        post_id = %post.id,
+        revision,
+        "post revision restored"
+    );
+
+    Ok(HttpResponse::Ok().json(post))
+}
@@ -0,0 +1,15 @@
+use crate::application::post_service::PostService;
+use crate::data::post_repository::PostgresPostRepository;
+use crate::domain::error::DomainError;
+use actix_web::{HttpResponse, get, web};
+use serde_json::json;
+use std::sync::Arc;
+
+#[get("/tags")]
+async fn get_tags(
+    post: web::Data<Arc<PostService<PostgresPostRepository>>>,
+) -> Result<HttpResponse, DomainError> {
+    let tags = post.get_tags().await?;
+
+    Ok(HttpResponse::Ok().json(json!({ "tags": tags })))
+}
@@ -0,0 +1,95 @@
+use crate::application::auth_service::AuthService;
+use crate::data::token_repository::PostgresTokenRepository;
+use crate::data::user_repository::PostgresUserRepository;
+use crate::domain::error::DomainError;
+use crate::presentation::dto::{
+    AccountResponse, ChangeEmailRequest, ChangePasswordRequest, ChangeUsernameRequest,
+};
+use crate::presentation::handlers::post::request_id;
+use crate::presentation::utils::{bearer_token, client_info};
+use actix_web::{HttpRequest, HttpResponse, Responder, Scope, get, put, web};
+use std::sync::Arc;
+use tracing::info;
+
+type Auth = web::Data<Arc<AuthService<PostgresUserRepository, PostgresTokenRepository>>>;
+
+/// Настройки аккаунта доступны только из сессии, не по персональному токену
+pub fn scope() -> Scope {
+    web::scope("/users")
+        .service(me)
+        .service(change_password)
+        .service(change_email)
+        .service(change_username)
+}
+
+#[get("/me")]
+async fn me(req: HttpRequest, service: Auth) -> Result<impl Responder, DomainError> {
+    let access_token = bearer_token(&req).ok_or(DomainError::Unauthorized)?;
+
+    let user = service.me(access_token).await?;
+
+    Ok(HttpResponse::Ok().json(AccountResponse::from(user)))
+}
+
+/// Остальные сессии закрываются, текущая продолжает работать
+#[put("/me/password")]
+async fn change_password(
+    req: HttpRequest,
+    service: Auth,
+    payload: web::Json<ChangePasswordRequest>,
+) -> Result<impl Responder, DomainError> {
+    let access_token = bearer_token(&req).ok_or(DomainError::Unauthorized)?;
+
+    service
+        .change_password(
+            access_token,
+            &payload.current_password,
+            &payload.new_password,
+            &client_info(&req),
+        )
+        .await?;
+
+    info!(request_id = %request_id(&req), "password changed");
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// На новый адрес уходит письмо для подтверждения
+#[put("/me/email")]
+async fn change_email(
+    req: HttpRequest,
+    service: Auth,
+    payload: web::Json<ChangeEmailRequest>,
+) -> Result<impl Responder, DomainError> {
+    let access_token = bearer_token(&req).ok_or(DomainError::Unauthorized)?;
+
+    let user = service
+        .change_email(
+            access_token,
+            &payload.current_password,
+            &payload.email,
+            &client_info(&req),
+        )
+        .await?;
+
+    info!(request_id = %request_id(&req), user_id = %user.id, "email changed");
+
+    Ok(HttpResponse::Ok().json(AccountResponse::from(user)))
+}
+
+#[put("/me/username")]
+async fn change_username(
+    req: HttpRequest,
+    service: Auth,
+    payload: web::Json<ChangeUsernameRequest>,
+) -> Result<impl Responder, DomainError> {
+    let access_token = bearer_token(&req).ok_or(DomainError::Unauthorized)?;
+
+    let user = service
+        .change_username(access_token, &payload.username)
+        .await?;
+
+    info!(request_id = %request_id(&req), username = %user.username, "username changed");
+
+    Ok(HttpResponse::Ok().json(AccountResponse::from(user)))
+}
@@ -1,18 +1,26 @@
 use crate::application::auth_service::AuthService;
+use crate::application::rate_limiter::{RateClass, RateLimiter};
+use crate::data::token_repository::PostgresTokenRepository;
 use crate::data::user_repository::PostgresUserRepository;
-use crate::infrastructure::security::JwtKeys;
+use crate::domain::personal_token::Scope;
+use crate::presentation::grpc_service::map_domain_error_to_status;
 use crate::presentation::utils::extract_user_from_token;
 use actix_service::{Service, Transform};
+use actix_web::body::EitherBody;
 use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::Method;
 use actix_web::http::header::{HeaderName, HeaderValue};
 use actix_web::{Error, HttpMessage, web};
-use futures_util::future::LocalBoxFuture;
+use futures_util::future::{BoxFuture, LocalBoxFuture};
 use std::cell::RefCell;
 use std::future::{Ready, ready};
 use std::rc::Rc;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::time::Instant;
+use tonic::codegen::http;
+use tonic::transport::server::TcpConnectInfo;
+use tower::Layer;
 use tracing::info;
 use uuid::Uuid;
 
@@ -80,15 +88,7 @@ where
     }
 }
 
-pub struct JwtAuthMiddleware {
-    keys: JwtKeys,
-}
-
-impl JwtAuthMiddleware {
-    pub fn new(keys: JwtKeys) -> Self {
-        Self { keys }
-    }
-}
+pub struct JwtAuthMiddleware;
 
 impl<S, B> Transform<S, ServiceRequest> for JwtAuthMiddleware
 where
@@ -104,14 +104,12 @@ where
     fn new_transform(&self, service: S) -> Self::Future {
         ready(Ok(JwtAuthService {
             service: Rc::new(RefCell::new(service)),
-            keys: self.keys.clone(),
         }))
     }
 }
 
 pub struct JwtAuthService<S> {
     service: Rc<RefCell<S>>,
-    keys: JwtKeys,
 }
 
 impl<S, B> Service<ServiceRequest> for JwtAuthService<S>
@@ -128,11 +126,10 @@ where
     }
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
-        let keys = self.keys.clone();
         let service = Rc::clone(&self.service);
 
         let auth_service = req
-            .app_data::<web::Data<Arc<AuthService<PostgresUserRepository>>>>()
+            .app_data::<web::Data<Arc<AuthService<PostgresUserRepository, PostgresTokenRepository>>>>()
             .cloned();
 
         let auth_header = req
@@ -152,7 +149,9 @@ where
                 actix_web::error::ErrorUnauthorized("invalid authorization header")
             })?;
 
-            let user = extract_user_from_token(token, &keys, auth_service.get_ref()).await?;
+            let user = extract_user_from_token(token, auth_service.get_ref()).await?;
+            // Все маршруты за этим middleware меняют посты или их обсуждение
+            user.require(Scope::PostsWrite)?;
 
             req.extensions_mut().insert(user);
             let fut = {
@@ -241,3 +240,171 @@ where
         })
     }
 }
+
+/// Класс REST-запроса: всё под `/api/auth` — Auth, чтение — Read, остальное — Write.
+/// `None` — запрос не ограничивается
+fn http_rate_class(method: &Method, path: &str) -> Option<RateClass> {
+    if path == "/api/health" {
+        None
+    } else if path.starts_with("/api/auth/") {
+        Some(RateClass::Auth)
+    } else if method == Method::GET || method == Method::HEAD {
+        Some(RateClass::Read)
+    } else {
+        Some(RateClass::Write)
+    }
+}
+
+/// Token bucket на пользователя или IP; при исчерпании бюджета — 429 с `Retry-After`
+pub struct RateLimitMiddleware;
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimitMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RateLimitService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RateLimitService {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct RateLimitService<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let limiter = req.app_data::<web::Data<Arc<RateLimiter>>>().cloned();
+        let class = http_rate_class(req.method(), req.path());
+
+        Box::pin(async move {
+            if let (Some(limiter), Some(class)) = (limiter, class) {
+                let bearer = req
+                    .headers()
+                    .get(actix_web::http::header::AUTHORIZATION)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.strip_prefix("Bearer "));
+                let ip = req.peer_addr().map(|addr| addr.ip().to_string());
+                let subject = limiter.subject(bearer, ip);
+
+                if let Err(e) = limiter.check(class, &subject).await {
+                    return Ok(req.error_response(e).map_into_right_body());
+                }
+            }
+
+            let res = service.call(req).await?;
+            Ok(res.map_into_left_body())
+        })
+    }
+}
+
+/// Класс RPC: вход и сессии — Auth, Get/List/Search/Diff — Read, остальное — Write
+fn grpc_rate_class(path: &str) -> RateClass {
+    let method = path.rsplit('/').next().unwrap_or_default();
+    match method {
+        "Register" | "Login" | "VerifyMfa" | "RefreshToken" | "Logout" | "LogoutAll"
+        | "ListSessions" | "RevokeSession" => RateClass::Auth,
+        _ if ["Get", "List", "Search", "Diff"]
+            .iter()
+            .any(|prefix| method.starts_with(prefix)) =>
+        {
+            RateClass::Read
+        }
+        _ => RateClass::Write,
+    }
+}
+
+/// Тот же ограничитель для gRPC: tower-слой перед сервисом, отказ — `RESOURCE_EXHAUSTED`
+#[derive(Clone)]
+pub struct GrpcRateLimitLayer {
+    limiter: Arc<RateLimiter>,
+}
+
+impl GrpcRateLimitLayer {
+    pub fn new(limiter: Arc<RateLimiter>) -> Self {
+        Self { limiter }
+    }
+}
+
+impl<S> Layer<S> for GrpcRateLimitLayer {
+    type Service = GrpcRateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        GrpcRateLimitService {
+            inner,
+            limiter: Arc::clone(&self.limiter),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct GrpcRateLimitService<S> {
+    inner: S,
+    limiter: Arc<RateLimiter>,
+}
+
+impl<S, ReqBody> tower::Service<http::Request<ReqBody>> for GrpcRateLimitService<S>
+where
+    S: tower::Service<http::Request<ReqBody>, Response = http::Response<tonic::body::Body>>
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        // Готов тот экземпляр, для которого вызывали poll_ready, — его и забираем
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let limiter = Arc::clone(&self.limiter);
+
+        Box::pin(async move {
+            let class = grpc_rate_class(req.uri().path());
+            let bearer = req
+                .headers()
+                .get(http::header::AUTHORIZATION)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.strip_prefix("Bearer "));
+            let ip = req
+                .extensions()
+                .get::<TcpConnectInfo>()
+                .and_then(|info| info.remote_addr())
+                .map(|addr| addr.ip().to_string());
+            let subject = limiter.subject(bearer, ip);
+
+            if let Err(e) = limiter.check(class, &subject).await {
+                return Ok(map_domain_error_to_status(e).into_http());
+            }
+            inner.call(req).await
+        })
+    }
+}
@@ -1,25 +1,39 @@
 use actix_web::dev::Payload;
-use actix_web::{Error, FromRequest, HttpMessage, HttpRequest, error::ErrorUnauthorized};
-use futures_util::future::{Ready, ready};
+use actix_web::{Error, FromRequest, HttpMessage, HttpRequest, error::ErrorUnauthorized, web};
+use futures_util::future::{LocalBoxFuture, Ready, ready};
+use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::application::auth_service::AuthService;
+use crate::application::policy::Actor;
+use crate::data::token_repository::PostgresTokenRepository;
 use crate::data::user_repository::PostgresUserRepository;
 use crate::domain::error::DomainError;
-use crate::infrastructure::security::JwtKeys;
-
-pub fn ensure_owner(item_id: &Uuid, user_id: &Uuid) -> Result<(), DomainError> {
-    if item_id != user_id {
-        Err(DomainError::Unauthorized)
-    } else {
-        Ok(())
-    }
-}
+use crate::domain::personal_token::{Credential, Scope};
+use crate::domain::session::ClientInfo;
+use crate::domain::user::Role;
+use crate::presentation::middleware::RequestId;
 
 #[derive(Debug, Clone)]
 pub struct AuthenticatedUser {
     pub id: Uuid,
     pub username: String,
+    pub role: Role,
+    pub credential: Credential,
+}
+
+impl AuthenticatedUser {
+    pub fn require(&self, scope: Scope) -> Result<(), DomainError> {
+        if self.credential.allows(scope) {
+            Ok(())
+        } else {
+            Err(DomainError::Forbidden)
+        }
+    }
+
+    pub fn actor(&self) -> Actor {
+        Actor::new(self.id, self.role)
+    }
 }
 
 impl FromRequest for AuthenticatedUser {
@@ -34,23 +48,74 @@ impl FromRequest for AuthenticatedUser {
     }
 }
 
+/// Пользователь на публичных маршрутах: `None`, если токена нет или он невалиден
+#[derive(Debug, Clone)]
+pub struct OptionalUser(pub Option<AuthenticatedUser>);
+
+impl OptionalUser {
+    pub fn id(&self) -> Option<Uuid> {
+        self.0.as_ref().map(|user| user.id)
+    }
+}
+
+impl FromRequest for OptionalUser {
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let auth_service = req
+            .app_data::<web::Data<Arc<AuthService<PostgresUserRepository, PostgresTokenRepository>>>>()
+            .cloned();
+        let token = bearer_token(req).map(|value| value.to_string());
+
+        Box::pin(async move {
+            let (Some(auth_service), Some(token)) = (auth_service, token) else {
+                return Ok(OptionalUser(None));
+            };
+            // Токен без posts:read читает как аноним
+            let user = extract_user_from_token(&token, auth_service.get_ref())
+                .await
+                .ok()
+                .filter(|user| user.credential.allows(Scope::PostsRead));
+            Ok(OptionalUser(user))
+        })
+    }
+}
+
+pub fn bearer_token(req: &HttpRequest) -> Option<&str> {
+    req.headers()
+        .get(actix_web::http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+}
+
+/// User-Agent, адрес клиента и request id для сессий и журнала аудита
+pub fn client_info(req: &HttpRequest) -> ClientInfo {
+    let user_agent = req
+        .headers()
+        .get(actix_web::http::header::USER_AGENT)
+        .and_then(|value| value.to_str().ok());
+    let ip = req.peer_addr().map(|addr| addr.ip().to_string());
+    let client = ClientInfo::new(user_agent, ip.as_deref());
+    match req.extensions().get::<RequestId>() {
+        Some(request_id) => client.with_request_id(request_id.0.clone()),
+        None => client,
+    }
+}
+
 pub async fn extract_user_from_token(
     token: &str,
-    keys: &JwtKeys,
-    auth_service: &AuthService<PostgresUserRepository>,
+    auth_service: &AuthService<PostgresUserRepository, PostgresTokenRepository>,
 ) -> Result<AuthenticatedUser, Error> {
-    let claims = keys
-        .verify_token(token)
-        .map_err(|_| ErrorUnauthorized("invalid token"))?;
-    let user_id = Uuid::parse_str(&claims.sub).map_err(|_| ErrorUnauthorized("invalid token"))?;
-
-    let user = auth_service
-        .get_user(user_id)
+    let (user, credential) = auth_service
+        .authenticate(token)
         .await
-        .map_err(|_| ErrorUnauthorized("user not found"))?;
+        .map_err(|_| ErrorUnauthorized("invalid token"))?;
 
     Ok(AuthenticatedUser {
         id: user.id,
         username: user.username,
+        role: user.role,
+        credential,
     })
 }
@@ -1,27 +1,44 @@
+use crate::application::audit_log::AuditLog;
 use crate::application::auth_service::AuthService;
+use crate::application::comment_service::CommentService;
+use crate::application::outbox_service::OutboxService;
 use crate::application::post_service::PostService;
+use crate::application::rate_limiter::RateLimiter;
 use crate::blog;
+use crate::data::comment_repository::CommentRepository;
+use crate::data::outbox_repository::OutboxRepository;
 use crate::data::post_repository::PostRepository;
+use crate::data::token_repository::TokenRepository;
 use crate::data::user_repository::UserRepository;
 use crate::infrastructure::config::AppConfig;
 use crate::presentation::grpc_service::BlogGrpcService;
 use crate::presentation::handlers;
-use crate::presentation::middleware::{JwtAuthMiddleware, RequestIdMiddleware, TimingMiddleware};
+use crate::presentation::middleware::{
+    GrpcRateLimitLayer, JwtAuthMiddleware, RateLimitMiddleware, RequestIdMiddleware,
+    TimingMiddleware,
+};
 use actix_cors::Cors;
 use actix_web::middleware::{DefaultHeaders, Logger};
 use actix_web::{App, HttpResponse, HttpServer, Responder, web};
 use chrono::{DateTime, Utc};
 use serde::Serialize;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::signal;
 use tonic::transport::Server;
+use tracing::{error, info};
 
 pub async fn start_rest_server<
     R: UserRepository + Send + Sync + 'static,
+    K: TokenRepository + Send + Sync + 'static,
     T: PostRepository + Send + Sync + 'static,
+    C: CommentRepository + Send + Sync + 'static,
 >(
-    auth_service: Arc<AuthService<R>>,
+    auth_service: Arc<AuthService<R, K>>,
     post_service: Arc<PostService<T>>,
+    comment_service: Arc<CommentService<C, T>>,
+    rate_limiter: Arc<RateLimiter>,
+    audit_log: Arc<AuditLog>,
 ) -> anyhow::Result<()> {
     let config = AppConfig::from_env().expect("invalid configuration");
     let config_bind = AppConfig::from_env().expect("invalid configuration");
@@ -35,7 +52,10 @@ pub async fn start_rest_server<
     HttpServer::new(move || {
         let cors = build_cors(&config);
 
+        // Последний wrap выполняется первым: ограничитель стоит внутри, чтобы 429
+        // получал CORS-заголовки, request id и попадал в лог
         App::new()
+            .wrap(RateLimitMiddleware)
             .wrap(Logger::default())
             .wrap(RequestIdMiddleware)
             .wrap(TimingMiddleware)
@@ -49,19 +69,46 @@ pub async fn start_rest_server<
             .wrap(cors)
             .app_data(web::Data::new(post_service.clone()))
             .app_data(web::Data::new(auth_service.clone()))
+            .app_data(web::Data::new(comment_service.clone()))
+            .app_data(web::Data::new(rate_limiter.clone()))
+            .app_data(web::Data::new(audit_log.clone()))
+            .service(handlers::auth::jwks)
             .service(
                 web::scope("/api")
                     .route("/health", web::get().to(health))
                     .service(handlers::post::get_posts)
+                    // до get_post, иначе "search" и "trash" разбираются как {id}
+                    .service(handlers::post::search_posts)
+                    .service(handlers::post::get_trash)
                     .service(handlers::post::get_post)
+                    .service(handlers::post::get_post_by_slug)
+                    .service(handlers::revision::get_revisions)
+                    .service(handlers::revision::diff_revisions)
+                    .service(handlers::revision::get_revision)
+                    .service(handlers::comment::get_comments)
+                    .service(handlers::tag::get_tags)
                     .service(
                         web::scope("/posts")
-                            .wrap(JwtAuthMiddleware::new(auth_service.keys().clone()))
+                            .wrap(JwtAuthMiddleware)
                             .service(handlers::post::create_post)
                             .service(handlers::post::delete_post)
-                            .service(handlers::post::update_post),
+                            .service(handlers::post::restore_post)
+                            .service(handlers::post::update_post)
+                            .service(handlers::post::publish_post)
+                            .service(handlers::post::unpublish_post)
+                            .service(handlers::post::archive_post)
+                            .service(handlers::post::hide_post)
+                            .service(handlers::post::unhide_post)
+                            .service(handlers::post::toggle_reaction)
+                            .service(handlers::revision::restore_revision)
+                            .service(handlers::comment::create_comment)
+                            .service(handlers::comment::delete_comment)
+                            .service(handlers::comment::hide_comment)
+                            .service(handlers::comment::unhide_comment),
                     )
-                    .service(handlers::auth::scope()),
+                    .service(handlers::auth::scope())
+                    .service(handlers::user::scope())
+                    .service(handlers::admin::scope()),
             )
     })
     .bind(bind_address)?
@@ -74,18 +121,23 @@ pub async fn start_rest_server<
 
 pub async fn start_grpc_server<
     R: UserRepository + Send + Sync + 'static,
+    K: TokenRepository + Send + Sync + 'static,
     T: PostRepository + Send + Sync + 'static,
+    C: CommentRepository + Send + Sync + 'static,
 >(
-    auth_service: Arc<AuthService<R>>,
+    auth_service: Arc<AuthService<R, K>>,
     post_service: Arc<PostService<T>>,
+    comment_service: Arc<CommentService<C, T>>,
+    rate_limiter: Arc<RateLimiter>,
 ) -> anyhow::Result<()> {
     let addr = "0.0.0.0:50051".parse().unwrap();
 
-    let grpc_service = BlogGrpcService::new(auth_service, post_service);
+    let grpc_service = BlogGrpcService::new(auth_service, post_service, comment_service);
 
     println!("gRPC server starting on {}", addr);
 
     let server = Server::builder()
+        .layer(GrpcRateLimitLayer::new(rate_limiter))
         .add_service(blog::blog_service_server::BlogServiceServer::new(
             grpc_service,
         ))
@@ -98,12 +150,98 @@ pub async fn start_grpc_server<
     Ok(())
 }
 
+pub async fn start_post_publisher<T: PostRepository + Send + Sync + 'static>(
+    post_service: Arc<PostService<T>>,
+    interval: Duration,
+) -> anyhow::Result<()> {
+    println!("Scheduled post publisher running every {:?}", interval);
+
+    let mut ticker = tokio::time::interval(interval);
+    let shutdown = signal::ctrl_c();
+    tokio::pin!(shutdown);
+
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => {
+                println!("Post publisher received shutdown signal");
+                return Ok(());
+            }
+            _ = ticker.tick() => match post_service.publish_due_posts().await {
+                Ok(0) => {}
+                Ok(count) => info!(count, "scheduled posts published"),
+                Err(e) => error!("scheduled publishing failed: {}", e),
+            },
+        }
+    }
+}
+
+/// Раз в `interval` окончательно удаляет посты, пролежавшие в корзине дольше `retention`
+pub async fn start_trash_purger<T: PostRepository + Send + Sync + 'static>(
+    post_service: Arc<PostService<T>>,
+    interval: Duration,
+    retention: chrono::Duration,
+) -> anyhow::Result<()> {
+    println!(
+        "Trash purger running every {:?}, retention {} days",
+        interval,
+        retention.num_days()
+    );
+
+    let mut ticker = tokio::time::interval(interval);
+    let shutdown = signal::ctrl_c();
+    tokio::pin!(shutdown);
+
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => {
+                println!("Trash purger received shutdown signal");
+                return Ok(());
+            }
+            _ = ticker.tick() => match post_service.purge_deleted_posts(retention).await {
+                Ok(0) => {}
+                Ok(count) => info!(count, "deleted posts purged"),
+                Err(e) => error!("trash purge failed: {}", e),
+            },
+        }
+    }
+}
+
+pub async fn start_outbox_worker<O: OutboxRepository + Send + Sync + 'static>(
+    outbox_service: Arc<OutboxService<O>>,
+    interval: Duration,
+) -> anyhow::Result<()> {
+    println!("Email outbox worker running every {:?}", interval);
+
+    let mut ticker = tokio::time::interval(interval);
+    let shutdown = signal::ctrl_c();
+    tokio::pin!(shutdown);
+
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => {
+                println!("Email outbox worker received shutdown signal");
+                return Ok(());
+            }
+            _ = ticker.tick() => match outbox_service.deliver_pending().await {
+                Ok(0) => {}
+                Ok(count) => info!(count, "emails delivered"),
+                Err(e) => error!("email delivery failed: {}", e),
+            },
+        }
+    }
+}
+
 fn build_cors(config: &AppConfig) -> Cors {
     let mut cors = Cors::default()
         .allowed_methods(vec!["GET", "POST", "PUT", "DELETE"])
         .allowed_headers(vec![
             actix_web::http::header::CONTENT_TYPE,
             actix_web::http::header::AUTHORIZATION,
+            actix_web::http::header::IF_MATCH,
+        ])
+        .expose_headers(vec![
+            actix_web::http::header::RETRY_AFTER,
+            actix_web::http::header::ETAG,
         ])
         .supports_credentials()
         .max_age(3600);
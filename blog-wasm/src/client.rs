@@ -1,5 +1,5 @@
 use crate::error::BlogClientError;
-use crate::Post;
+use crate::{Post, Session};
 use async_trait::async_trait;
 use gloo_net::http::Request;
 use gloo_storage::{LocalStorage, Storage};
@@ -15,8 +15,15 @@ pub trait BlogClientTrait: Send + Sync + 'static {
         email: String,
         password: String,
     ) -> Result<(), BlogClientError>;
-    async fn login(&mut self, email: String, password: String) -> Result<(), BlogClientError>;
+    async fn login(
+        &mut self,
+        email: String,
+        password: String,
+    ) -> Result<LoginStep, BlogClientError>;
+    /// Второй шаг входа: код из приложения или код восстановления
+    async fn verify_mfa(&mut self, mfa_token: String, code: String) -> Result<(), BlogClientError>;
     async fn get_post_by_id(&mut self, id: Uuid) -> Result<Post, BlogClientError>;
+    async fn get_post_by_slug(&mut self, slug: &str) -> Result<Post, BlogClientError>;
     async fn list_posts(
         &mut self,
         author_id: Option<Uuid>,
@@ -28,17 +35,27 @@ pub trait BlogClientTrait: Send + Sync + 'static {
         title: String,
         content: String,
     ) -> Result<Post, BlogClientError>;
+    /// С `expected_version` сервер ответит 412, если пост с тех пор изменился
     async fn update_post(
         &mut self,
         id: Uuid,
         title: Option<String>,
         content: Option<String>,
+        expected_version: Option<i32>,
     ) -> Result<Post, BlogClientError>;
     async fn delete_post(&mut self, id: Uuid) -> Result<(), BlogClientError>;
+    async fn list_sessions(&mut self) -> Result<Vec<Session>, BlogClientError>;
+    async fn revoke_session(&mut self, id: Uuid) -> Result<(), BlogClientError>;
 }
 
 const TOKEN_KEY: &str = "blog_token";
 
+/// Итог проверки пароля: вход выполнен или нужен код второго фактора
+pub enum LoginStep {
+    Done,
+    MfaRequired(String),
+}
+
 #[derive(Clone)]
 pub struct BlogClientHttp {
     pub base_url: String,
@@ -47,18 +64,24 @@ pub struct BlogClientHttp {
 
 #[derive(Debug, Deserialize)]
 struct PostsResponse {
-    limit: u32,
-    offset: u32,
     posts: Vec<Post>,
-    total: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct SessionsResponse {
+    sessions: Vec<Session>,
 }
 
 #[derive(Debug, Deserialize)]
 struct AuthResponse {
     pub access_token: String,
-    pub expires_in: i64,
-    #[serde(rename = "token_type")]
-    pub token_type: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum LoginResponse {
+    MfaRequired { mfa_token: String },
+    Tokens(AuthResponse),
 }
 
 impl BlogClientHttp {
@@ -124,13 +147,34 @@ impl BlogClientTrait for BlogClientHttp {
         Ok(())
     }
 
-    async fn login(&mut self, username: String, password: String) -> Result<(), BlogClientError> {
+    async fn login(
+        &mut self,
+        username: String,
+        password: String,
+    ) -> Result<LoginStep, BlogClientError> {
         let url = format!("{}/api/auth/login", self.base_url);
         let body = json!({
             "username": username,
             "password": password,
         });
 
+        let request = Request::post(&url).json(&body)?;
+        match Self::send(request).await? {
+            LoginResponse::Tokens(auth) => {
+                self.set_token(auth.access_token);
+                Ok(LoginStep::Done)
+            }
+            LoginResponse::MfaRequired { mfa_token } => Ok(LoginStep::MfaRequired(mfa_token)),
+        }
+    }
+
+    async fn verify_mfa(&mut self, mfa_token: String, code: String) -> Result<(), BlogClientError> {
+        let url = format!("{}/api/auth/login/mfa", self.base_url);
+        let body = json!({
+            "mfa_token": mfa_token,
+            "code": code,
+        });
+
         let request = Request::post(&url).json(&body)?;
         let auth: AuthResponse = Self::send(request).await?;
         self.set_token(auth.access_token);
@@ -149,18 +193,33 @@ impl BlogClientTrait for BlogClientHttp {
         Self::send(request).await
     }
 
+    async fn get_post_by_slug(&mut self, slug: &str) -> Result<Post, BlogClientError> {
+        let url = format!("{}/api/posts/by-slug/{}", self.base_url, slug);
+        let mut builder = Request::get(&url);
+
+        if let Some(token) = self.auth_header() {
+            builder = builder.header("Authorization", token.as_str());
+        }
+
+        let request = builder.header("Accept", "application/json").build()?;
+        Self::send(request).await
+    }
+
     async fn list_posts(
         &mut self,
-        _author_id: Option<Uuid>,
+        author_id: Option<Uuid>,
         limit: Option<u32>,
         offset: Option<u32>,
     ) -> Result<Vec<Post>, BlogClientError> {
         let limit = limit.unwrap_or(10).min(100);
         let offset = offset.unwrap_or(0);
-        let url = format!(
+        let mut url = format!(
             "{}/api/posts?limit={}&offset={}",
             self.base_url, limit, offset
         );
+        if let Some(author_id) = author_id {
+            url.push_str(&format!("&author_id={}", author_id));
+        }
 
         let request = Request::get(&url).build()?;
         let resp: PostsResponse = Self::send(request).await?;
@@ -196,6 +255,7 @@ impl BlogClientTrait for BlogClientHttp {
         id: Uuid,
         title: Option<String>,
         content: Option<String>,
+        expected_version: Option<i32>,
     ) -> Result<Post, BlogClientError> {
         let url = format!("{}/api/posts/{}", self.base_url, id);
         let body = json!({
@@ -209,6 +269,10 @@ impl BlogClientTrait for BlogClientHttp {
             builder = builder.header("Authorization", token.as_str());
         }
 
+        if let Some(version) = expected_version {
+            builder = builder.header("If-Match", &format!("\"{}\"", version));
+        }
+
         let request = builder
             .header("Content-Type", "application/json")
             .json(&body)?;
@@ -238,4 +302,40 @@ impl BlogClientTrait for BlogClientHttp {
             })
         }
     }
+
+    async fn list_sessions(&mut self) -> Result<Vec<Session>, BlogClientError> {
+        let url = format!("{}/api/auth/sessions", self.base_url);
+        let mut builder = Request::get(&url);
+
+        if let Some(token) = self.auth_header() {
+            builder = builder.header("Authorization", token.as_str());
+        }
+
+        let request = builder.header("Accept", "application/json").build()?;
+        let resp: SessionsResponse = Self::send(request).await?;
+        Ok(resp.sessions)
+    }
+
+    async fn revoke_session(&mut self, id: Uuid) -> Result<(), BlogClientError> {
+        let url = format!("{}/api/auth/sessions/{}", self.base_url, id);
+
+        let mut request = Request::delete(&url);
+
+        if let Some(header) = self.auth_header() {
+            request = request.header("Authorization", &header);
+        }
+
+        let response = request.send().await?;
+
+        if response.ok() {
+            Ok(())
+        } else {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            Err(BlogClientError::Http {
+                status,
+                message: text,
+            })
+        }
+    }
 }
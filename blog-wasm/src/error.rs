@@ -6,10 +6,4 @@ pub enum BlogClientError {
     Http { status: u16, message: String },
     #[error(transparent)]
     RequestError(#[from] gloo_net::Error),
-    #[error("Not found")]
-    NotFound,
-    #[error("Unauthorized")]
-    Unauthorized,
-    #[error("Invalid request: {0}")]
-    InvalidRequest(String),
 }
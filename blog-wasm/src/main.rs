@@ -1,4 +1,5 @@
-use crate::client::{BlogClientHttp, BlogClientTrait};
+use crate::client::{BlogClientHttp, BlogClientTrait, LoginStep};
+use crate::error::BlogClientError;
 use chrono::{DateTime, Utc};
 use derive_more::Display;
 use dioxus::prelude::*;
@@ -18,11 +19,42 @@ pub struct Post {
     pub id: Uuid,
     pub author_id: Uuid,
     pub title: String,
+    #[serde(default)]
+    pub slug: String,
     pub content: String,
+    /// Совпадает с ETag; отправляется при сохранении, чтобы не затереть чужую правку
+    #[serde(default)]
+    pub version: i32,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
 }
 
+/// Сессия пользователя из `GET /api/auth/sessions`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub id: Uuid,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    #[serde(default)]
+    pub current: bool,
+}
+
+impl Session {
+    fn device(&self) -> &str {
+        self.user_agent.as_deref().unwrap_or("Unknown device")
+    }
+
+    fn ip(&self) -> &str {
+        self.ip.as_deref().unwrap_or("-")
+    }
+}
+
+fn format_time(at: DateTime<Utc>) -> String {
+    at.format("%Y-%m-%d %H:%M").to_string()
+}
+
 #[derive(Clone, Routable, Debug, PartialEq)]
 #[rustfmt::skip]
 enum Route {
@@ -33,6 +65,8 @@ enum Route {
         Posts {},
         #[route("/posts/:id")]
         PostDetail { id: Uuid },
+        #[route("/p/:slug")]
+        PostBySlug { slug: String },
         #[route("/create")]
         CreatePost {},
         #[route("/edit/:id")]
@@ -41,6 +75,8 @@ enum Route {
         Login {},
         #[route("/register")]
         Register {},
+        #[route("/sessions")]
+        Sessions {},
 }
 
 const FAVICON: Asset = asset!("/assets/favicon.ico");
@@ -120,7 +156,7 @@ fn Posts() -> Element {
                     div { class: "grid gap-8 md:grid-cols-2 lg:grid-cols-3",
                         for post in posts {
                             article { class: "bg-white rounded-2xl shadow-lg hover:shadow-2xl transition overflow-hidden",
-                                Link { to: Route::PostDetail { id: post.id },
+                                Link { to: post_route(post),
                                     div { class: "p-8",
                                         h2 { class: "text-2xl font-bold text-gray-900 mb-3 line-clamp-2", "{post.title}" }
                                         p { class: "text-gray-600 line-clamp-3", "{post.content.chars().take(150).collect::<String>()}..." }
@@ -139,6 +175,38 @@ fn Posts() -> Element {
     }
 }
 
+fn post_route(post: &Post) -> Route {
+    if post.slug.is_empty() {
+        Route::PostDetail { id: post.id }
+    } else {
+        Route::PostBySlug {
+            slug: post.slug.clone(),
+        }
+    }
+}
+
+#[component]
+fn PostBySlug(slug: String) -> Element {
+    let token = use_context::<Signal<Option<String>>>();
+
+    // Старый slug сервер перенаправляет на актуальный, fetch следует редиректу сам
+    let post = use_resource(use_reactive!(|slug| async move {
+        let mut client = BlogClientHttp {
+            base_url: BASE_URL.to_string(),
+            token: (*token.read()).clone(),
+        };
+        client.get_post_by_slug(&slug).await
+    }));
+
+    rsx! {
+        match post.read().as_ref() {
+            Some(Ok(post)) => rsx! { PostDetail { id: post.id } },
+            Some(Err(e)) => rsx! { p { class: "text-center text-red-600 text-xl", "Error: {e}" } },
+            None => rsx! { p { class: "text-center text-gray-500 text-xl", "Loading..." } },
+        }
+    }
+}
+
 #[component]
 fn PostDetail(id: Uuid) -> Element {
     let token = use_context::<Signal<Option<String>>>();
@@ -216,14 +284,12 @@ fn CreatePost() -> Element {
         return rsx! { "Redirecting to login..." };
     }
 
-    let mut title = use_signal(|| String::new());
-    let mut content = use_signal(|| String::new());
+    let mut title = use_signal(String::new);
+    let mut content = use_signal(String::new);
 
     let on_submit = move |_| {
         let title = title.read().clone();
         let content = content.read().clone();
-        let token_sig = token_sig.clone();
-        let navigator = navigator.clone();
 
         if title.trim().is_empty() || content.trim().is_empty() {
             return;
@@ -291,6 +357,9 @@ fn EditPost(id: Uuid) -> Element {
     let token_sig = use_context::<Signal<Option<String>>>();
     let navigator = use_navigator();
     let mut post_state = use_signal(|| None::<Result<Post, ()>>);
+    // Версия, с которой начата правка, и свежий пост с сервера, если его успели изменить
+    let mut version = use_signal(|| None::<i32>);
+    let mut conflict = use_signal(|| None::<Post>);
 
     // Редирект, если не авторизован
     if token_sig.read().is_none() {
@@ -299,7 +368,7 @@ fn EditPost(id: Uuid) -> Element {
     }
 
     // Загружаем пост (исправленный use_future для Dioxus 0.7)
-    let post_future = use_future(move || {
+    use_future(move || {
         let token = token_sig.read().clone();
         async move {
             let mut client = BlogClientHttp {
@@ -307,7 +376,10 @@ fn EditPost(id: Uuid) -> Element {
                 token,
             };
             match client.get_post_by_id(id).await {
-                Ok(post) => post_state.set(Some(Ok(post))),
+                Ok(post) => {
+                    version.set(Some(post.version));
+                    post_state.set(Some(Ok(post)));
+                }
                 Err(_) => post_state.set(Some(Err(()))),
             }
         }
@@ -318,8 +390,7 @@ fn EditPost(id: Uuid) -> Element {
     let mut content = use_signal(String::new);
 
     // Синхронизируем форму с загруженным постом (один раз)
-    let post_state_clone = post_state.clone();
-    if let Some(Ok(post)) = post_state_clone.read().as_ref() {
+    if let Some(Ok(post)) = post_state.read().as_ref() {
         if title.read().is_empty() {
             title.set(post.title.clone());
             content.set(post.content.clone());
@@ -341,23 +412,71 @@ fn EditPost(id: Uuid) -> Element {
                 }
 
                 let token = token_sig.read().clone();
+                let expected_version = *version.read();
                 spawn(async move {
                     let mut client = BlogClientHttp {
                         base_url: BASE_URL.to_string(),
                         token,
                     };
-                    if client
-                        .update_post(id, Some(new_title), Some(new_content))
+                    match client
+                        .update_post(id, Some(new_title), Some(new_content), expected_version)
                         .await
-                        .is_ok()
                     {
-                        navigator.push(Route::PostDetail { id });
-                    } else {
-                        navigator.push(Route::Login {});
+                        Ok(_) => {
+                            navigator.push(Route::PostDetail { id });
+                        }
+                        // Пост сохранил кто-то другой — показываем его версию и даём выбрать
+                        Err(BlogClientError::Http { status: 412, .. }) => {
+                            match client.get_post_by_id(id).await {
+                                Ok(latest) => conflict.set(Some(latest)),
+                                Err(_) => post_state.set(Some(Err(()))),
+                            }
+                        }
+                        Err(_) => {
+                            navigator.push(Route::Login {});
+                        }
                     }
                 });
             };
 
+            let conflict_dialog = conflict.read().clone().map(|latest| {
+                let latest_version = latest.version;
+                rsx! {
+                    div { class: "fixed inset-0 bg-black/50 flex items-center justify-center z-50",
+                        div { class: "bg-white rounded-2xl shadow-xl p-8 max-w-lg w-full mx-4",
+                            h2 { class: "text-2xl font-bold text-gray-900 mb-4", "This post has changed" }
+                            p { class: "text-gray-700 mb-2",
+                                "Someone else saved it while you were editing (now version {latest_version}):"
+                            }
+                            p { class: "text-gray-900 font-semibold mb-6", "{latest.title}" }
+                            p { class: "text-gray-700 mb-6",
+                                "Load their version and lose your edits, or keep your edits and save again to overwrite theirs."
+                            }
+                            div { class: "flex justify-end gap-4",
+                                button {
+                                    onclick: move |_| {
+                                        title.set(latest.title.clone());
+                                        content.set(latest.content.clone());
+                                        version.set(Some(latest.version));
+                                        conflict.set(None);
+                                    },
+                                    class: "px-6 py-3 border border-gray-300 text-gray-700 rounded-xl hover:bg-gray-50 transition font-medium",
+                                    "Load their version"
+                                }
+                                button {
+                                    onclick: move |_| {
+                                        version.set(Some(latest_version));
+                                        conflict.set(None);
+                                    },
+                                    class: "px-6 py-3 bg-indigo-600 text-white rounded-xl hover:bg-indigo-700 transition shadow-md font-medium",
+                                    "Keep my edits"
+                                }
+                            }
+                        }
+                    }
+                }
+            });
+
             rsx! {
                 div { class: "max-w-4xl mx-auto px-6 py-12",
                     div { class: "bg-white rounded-2xl shadow-xl p-8 md:p-12",
@@ -398,6 +517,7 @@ fn EditPost(id: Uuid) -> Element {
                             }
                         }
                     }
+                    {conflict_dialog}
                 }
             }
         }
@@ -425,8 +545,12 @@ fn Login() -> Element {
     let mut token_sig = use_context::<Signal<Option<String>>>();
     let navigator = use_navigator();
 
-    let mut username = use_signal(|| String::new());
-    let mut password = use_signal(|| String::new());
+    let mut username = use_signal(String::new);
+    let mut password = use_signal(String::new);
+    // Пароль принят, ждём код второго фактора
+    let mut mfa_token = use_signal(|| None::<String>);
+    let mut code = use_signal(String::new);
+    let mut code_error = use_signal(|| false);
 
     let on_submit = move |evt: FormEvent| {
         evt.prevent_default();
@@ -437,13 +561,101 @@ fn Login() -> Element {
                 base_url: BASE_URL.to_string(),
                 token: None,
             };
-            if client.login(username, password).await.is_ok() {
+            match client.login(username, password).await {
+                Ok(LoginStep::Done) => {
+                    token_sig.set(LocalStorage::get::<String>(TOKEN_KEY).ok());
+                    navigator.push(Route::Home {});
+                }
+                Ok(LoginStep::MfaRequired(token)) => mfa_token.set(Some(token)),
+                Err(_) => {}
+            }
+        });
+    };
+
+    let on_submit_code = move |evt: FormEvent| {
+        evt.prevent_default();
+        let Some(token) = mfa_token.read().clone() else {
+            return;
+        };
+        let entered = code.read().trim().to_string();
+        spawn(async move {
+            let mut client = BlogClientHttp {
+                base_url: BASE_URL.to_string(),
+                token: None,
+            };
+            if client.verify_mfa(token, entered).await.is_ok() {
                 token_sig.set(LocalStorage::get::<String>(TOKEN_KEY).ok());
                 navigator.push(Route::Home {});
+            } else {
+                code_error.set(true);
             }
         });
     };
 
+    if mfa_token.read().is_some() {
+        return rsx! {
+            div {
+                class: "min-h-screen flex items-center justify-center bg-gray-50 px-4",
+
+                div {
+                    class: "w-full max-w-md bg-white rounded-2xl shadow-xl p-8 md:p-10",
+
+                    h1 {
+                        class: "text-3xl font-bold text-center text-gray-900 mb-4",
+                        "Two-factor authentication"
+                    }
+                    p {
+                        class: "text-center text-gray-600 mb-8",
+                        "Enter the code from your authenticator app or a recovery code"
+                    }
+
+                    form {
+                        onsubmit: on_submit_code,
+                        class: "space-y-6",
+
+                        input {
+                            class: "w-full px-5 py-4 text-lg border border-gray-300 rounded-xl shadow-sm
+                                 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-blue-500
+                                 transition placeholder-gray-500 text-black tracking-widest text-center",
+                            r#type: "text",
+                            autocomplete: "one-time-code",
+                            placeholder: "123456",
+                            value: "{code}",
+                            oninput: move |evt| {
+                                code_error.set(false);
+                                code.set(evt.value())
+                            },
+                        }
+
+                        if code_error() {
+                            p { class: "text-center text-red-600", "Invalid code, try again" }
+                        }
+
+                        button {
+                            class: "w-full py-4 bg-blue-600 hover:bg-blue-700 text-white font-semibold
+                                 rounded-xl shadow-md transition disabled:opacity-50 disabled:cursor-not-allowed",
+                            r#type: "submit",
+                            disabled: code.read().trim().is_empty(),
+                            "Verify"
+                        }
+
+                        div { class: "text-center mt-6",
+                            button {
+                                class: "text-blue-600 hover:text-blue-800 font-medium transition",
+                                r#type: "button",
+                                onclick: move |_| {
+                                    mfa_token.set(None);
+                                    code.set(String::new());
+                                },
+                                "Back to login"
+                            }
+                        }
+                    }
+                }
+            }
+        };
+    }
+
     rsx! {
         div {
             class: "min-h-screen flex items-center justify-center bg-gray-50 px-4",
@@ -513,9 +725,9 @@ fn Register() -> Element {
     let mut token_sig = use_context::<Signal<Option<String>>>();
     let navigator = use_navigator();
 
-    let mut username = use_signal(|| String::new());
-    let mut email = use_signal(|| String::new());
-    let mut password = use_signal(|| String::new());
+    let mut username = use_signal(String::new);
+    let mut email = use_signal(String::new);
+    let mut password = use_signal(String::new);
 
     let on_submit = move |evt: FormEvent| {
         evt.prevent_default();
@@ -626,6 +838,85 @@ fn Register() -> Element {
     }
 }
 
+#[component]
+fn Sessions() -> Element {
+    let mut token_sig = use_context::<Signal<Option<String>>>();
+    let navigator = use_navigator();
+
+    if token_sig.read().is_none() {
+        navigator.push(Route::Login {});
+        return rsx! { "Redirecting to login..." };
+    }
+
+    let mut sessions = use_resource(move || async move {
+        let mut client = BlogClientHttp {
+            base_url: BASE_URL.to_string(),
+            token: (*token_sig.read()).clone(),
+        };
+        client.list_sessions().await
+    });
+
+    let on_revoke = move |session: Session| {
+        spawn(async move {
+            let mut client = BlogClientHttp {
+                base_url: BASE_URL.to_string(),
+                token: (*token_sig.read()).clone(),
+            };
+            if client.revoke_session(session.id).await.is_err() {
+                return;
+            }
+            // Закрыли свою же сессию — токен больше недействителен
+            if session.current {
+                LocalStorage::delete(TOKEN_KEY);
+                token_sig.set(None);
+                navigator.push(Route::Login {});
+            } else {
+                sessions.restart();
+            }
+        });
+    };
+
+    rsx! {
+        div { class: "max-w-4xl mx-auto px-6 py-12",
+            h1 { class: "text-4xl font-bold text-white-900 mb-10 text-center", "Active Sessions" }
+
+            match sessions.read().as_ref() {
+                Some(Ok(list)) => rsx! {
+                    div { class: "space-y-4",
+                        for session in list.iter().cloned() {
+                            div {
+                                key: "{session.id}",
+                                class: "bg-white rounded-2xl shadow-lg p-6 flex justify-between items-center",
+                                div {
+                                    p { class: "text-lg font-semibold text-gray-900",
+                                        "{session.device()}"
+                                        if session.current {
+                                            span { class: "ml-3 px-2 py-1 text-xs bg-green-100 text-green-700 rounded-lg", "This device" }
+                                        }
+                                    }
+                                    p { class: "text-sm text-gray-600",
+                                        "IP: {session.ip()}"
+                                    }
+                                    p { class: "text-sm text-gray-500",
+                                        "Signed in {format_time(session.created_at)}, last seen {format_time(session.last_seen_at)}"
+                                    }
+                                }
+                                button {
+                                    onclick: move |_| on_revoke(session.clone()),
+                                    class: "px-5 py-2.5 bg-red-600 text-white rounded-xl hover:bg-red-700 transition shadow-md",
+                                    "Revoke"
+                                }
+                            }
+                        }
+                    }
+                },
+                Some(Err(e)) => rsx! { p { class: "text-center text-red-600 text-xl", "Error: {e}" } },
+                None => rsx! { p { class: "text-center text-gray-500 text-xl", "Loading sessions..." } },
+            }
+        }
+    }
+}
+
 #[component]
 fn Navbar() -> Element {
     let mut token = use_context::<Signal<Option<String>>>();
@@ -648,6 +939,7 @@ fn Navbar() -> Element {
                         Link { to: Route::Posts {}, class: "text-gray-700 hover:text-indigo-600 font-medium transition", "Posts" }
                         if is_logged_in {
                             Link { to: Route::CreatePost {}, class: "text-gray-700 hover:text-indigo-600 font-medium transition", "New Post" }
+                            Link { to: Route::Sessions {}, class: "text-gray-700 hover:text-indigo-600 font-medium transition", "Sessions" }
                         }
                     }
                 }